@@ -0,0 +1,193 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use futures::executor::block_on;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::IoSliceMut;
+
+use merge_io::buffered::PrefetchReadIO;
+use merge_io::MergeIO;
+
+const LARGE: usize = 64 * 1024;
+const SMALL_OPS: usize = 4096;
+
+fn merged(len: usize) -> MergeIO<Cursor<Vec<u8>>, Cursor<Vec<u8>>> {
+    MergeIO::new(
+        Cursor::new(vec![0u8; len]),
+        Cursor::new(Vec::with_capacity(len)),
+    )
+}
+
+fn single_byte(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_byte");
+    group.throughput(Throughput::Bytes(SMALL_OPS as u64));
+    group.bench_function("read", |b| {
+        b.iter_batched_ref(
+            || merged(SMALL_OPS),
+            |stream| {
+                block_on(async {
+                    let mut byte = [0u8; 1];
+                    for _ in 0..SMALL_OPS {
+                        stream.read_exact(&mut byte).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("write", |b| {
+        b.iter_batched_ref(
+            || merged(SMALL_OPS),
+            |stream| {
+                block_on(async {
+                    for _ in 0..SMALL_OPS {
+                        stream.write_all(&[1]).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn large_buffer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_buffer");
+    group.throughput(Throughput::Bytes(LARGE as u64));
+    group.bench_function("read", |b| {
+        let mut buf = vec![0u8; LARGE];
+        b.iter_batched_ref(
+            || merged(LARGE),
+            |stream| block_on(stream.read_exact(&mut buf)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("write", |b| {
+        let buf = vec![1u8; LARGE];
+        b.iter_batched_ref(
+            || merged(0),
+            |stream| block_on(stream.write_all(&buf)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn vectored(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vectored");
+    group.throughput(Throughput::Bytes(8 * 512));
+    group.bench_function("read", |b| {
+        let mut bufs = vec![[0u8; 512]; 8];
+        b.iter_batched_ref(
+            || merged(8 * 512),
+            |stream| {
+                let mut slices: Vec<_> = bufs.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+                block_on(stream.read_vectored(&mut slices)).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn duplex_ping_pong(c: &mut Criterion) {
+    c.bench_function("duplex_ping_pong", |b| {
+        b.iter(|| {
+            let (mut left, mut right) = merge_io::duplex(4);
+            block_on(async {
+                let ping = async {
+                    let mut buf = [0u8; 64];
+                    for _ in 0..100 {
+                        left.write_all(&buf).await.unwrap();
+                        left.read_exact(&mut buf).await.unwrap();
+                    }
+                };
+                let pong = async {
+                    let mut buf = [0u8; 64];
+                    for _ in 0..100 {
+                        right.read_exact(&mut buf).await.unwrap();
+                        right.write_all(&buf).await.unwrap();
+                    }
+                };
+                futures::join!(ping, pong);
+            })
+        })
+    });
+}
+
+fn overhead(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overhead");
+    group.throughput(Throughput::Bytes(SMALL_OPS as u64));
+    group.bench_function("cursor", |b| {
+        b.iter_batched_ref(
+            || Cursor::new(vec![0u8; SMALL_OPS]),
+            |cursor| {
+                block_on(async {
+                    let mut byte = [0u8; 1];
+                    for _ in 0..SMALL_OPS {
+                        cursor.read_exact(&mut byte).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("merge_io", |b| {
+        b.iter_batched_ref(
+            || merged(SMALL_OPS),
+            |stream| {
+                block_on(async {
+                    let mut byte = [0u8; 1];
+                    for _ in 0..SMALL_OPS {
+                        stream.read_exact(&mut byte).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn prefetch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prefetch");
+    group.throughput(Throughput::Bytes(SMALL_OPS as u64));
+    group.bench_function("baseline", |b| {
+        b.iter_batched_ref(
+            || merged(SMALL_OPS),
+            |stream| {
+                block_on(async {
+                    let mut buf = [0u8; 16];
+                    for _ in 0..SMALL_OPS / 16 {
+                        stream.read_exact(&mut buf).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("prefetch_read_io", |b| {
+        b.iter_batched_ref(
+            || PrefetchReadIO::new(merged(SMALL_OPS), 1024),
+            |stream| {
+                block_on(async {
+                    let mut buf = [0u8; 16];
+                    for _ in 0..SMALL_OPS / 16 {
+                        stream.read_exact(&mut buf).await.unwrap();
+                    }
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    single_byte,
+    large_buffer,
+    vectored,
+    duplex_ping_pong,
+    overhead,
+    prefetch
+);
+criterion_main!(benches);
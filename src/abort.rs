@@ -0,0 +1,183 @@
+//! Forceful termination of I/O objects from the outside.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::task::AtomicWaker;
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Default)]
+struct AbortState {
+    aborted: AtomicBool,
+    read_waker: AtomicWaker,
+    write_waker: AtomicWaker,
+}
+
+/// Aborts the [`AbortableIO`](crate::abort::AbortableIO) it was created
+/// with.
+#[derive(Debug, Clone)]
+pub struct AbortHandle {
+    state: Arc<AbortState>,
+}
+
+impl AbortHandle {
+    /// Aborts the stream, waking the tasks waiting on it.
+    pub fn abort(&self) {
+        self.state.aborted.store(true, Ordering::SeqCst);
+        self.state.read_waker.wake();
+        self.state.write_waker.wake();
+    }
+
+    /// Returns whether [`abort`](crate::abort::AbortHandle::abort) has been
+    /// called.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::SeqCst)
+    }
+}
+
+/// Fails all operations with
+/// [`ErrorKind::ConnectionAborted`](std::io::ErrorKind::ConnectionAborted)
+/// once its [`AbortHandle`](crate::abort::AbortHandle) has been used, without
+/// touching the inner I/O object anymore.
+///
+/// Operations pending at that moment are woken and fail as well.
+#[pin_project]
+#[derive(Debug)]
+pub struct AbortableIO<T> {
+    #[pin]
+    inner: T,
+    state: Arc<AbortState>,
+}
+
+impl<T> AbortableIO<T> {
+    /// Creates new [`AbortableIO`](crate::abort::AbortableIO) and the
+    /// [`AbortHandle`](crate::abort::AbortHandle) controlling it.
+    pub fn new(inner: T) -> (Self, AbortHandle) {
+        let state = Arc::new(AbortState::default());
+        let handle = AbortHandle {
+            state: Arc::clone(&state),
+        };
+        (AbortableIO { inner, state }, handle)
+    }
+
+    /// Returns whether the stream has been aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `AbortableIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn poll_abortable<U>(
+    state: &AbortState,
+    waker: &AtomicWaker,
+    cx: &mut Context<'_>,
+    op: impl FnOnce(&mut Context<'_>) -> Poll<Result<U>>,
+) -> Poll<Result<U>> {
+    let aborted = || {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::ConnectionAborted,
+            "stream was aborted",
+        )))
+    };
+    if state.aborted.load(Ordering::SeqCst) {
+        return aborted();
+    }
+    match op(cx) {
+        Poll::Ready(result) => Poll::Ready(result),
+        Poll::Pending => {
+            waker.register(cx.waker());
+            // Catch an abort that happened before the waker was registered.
+            if state.aborted.load(Ordering::SeqCst) {
+                return aborted();
+            }
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> AsyncRead for AbortableIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.read_waker, cx, |cx| {
+            inner.poll_read(cx, buf)
+        })
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.read_waker, cx, |cx| {
+            inner.poll_read_vectored(cx, bufs)
+        })
+    }
+}
+
+impl<T> AsyncWrite for AbortableIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.write_waker, cx, |cx| {
+            inner.poll_write(cx, buf)
+        })
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.write_waker, cx, |cx| {
+            inner.poll_write_vectored(cx, bufs)
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.write_waker, cx, |cx| {
+            inner.poll_flush(cx)
+        })
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let inner = this.inner;
+        poll_abortable(this.state, &this.state.write_waker, cx, |cx| {
+            inner.poll_close(cx)
+        })
+    }
+}
@@ -0,0 +1,168 @@
+//! Flushing and closing streams when they're dropped.
+//!
+//! The flush and close run on a Tokio runtime.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::io::AsyncWriteExt;
+use std::fmt;
+use std::io::{Error, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::runtime::Handle;
+
+type ErrorCallback = Box<dyn Fn(Error) + Send>;
+
+/// Spawns a task flushing and closing the inner I/O object when dropped.
+///
+/// Rust has no async drop, so data buffered by a writer is easily lost when
+/// the writer goes out of scope without being closed. Errors from the
+/// spawned task are passed to the callback set with
+/// [`on_close_error`](crate::async_drop::AsyncDropIO::on_close_error), or
+/// ignored if there isn't one. Nothing is spawned if the stream was already
+/// closed successfully.
+pub struct AsyncDropIO<T>
+where
+    T: AsyncWrite + Unpin + Send + 'static,
+{
+    inner: Option<T>,
+    handle: Handle,
+    on_close_error: Option<ErrorCallback>,
+    closed: bool,
+}
+
+impl<T> AsyncDropIO<T>
+where
+    T: AsyncWrite + Unpin + Send + 'static,
+{
+    /// Creates new [`AsyncDropIO`](crate::async_drop::AsyncDropIO) spawning
+    /// its cleanup on `handle`.
+    pub fn new(inner: T, handle: Handle) -> Self {
+        AsyncDropIO {
+            inner: Some(inner),
+            handle,
+            on_close_error: None,
+            closed: false,
+        }
+    }
+
+    /// Sets the callback for errors raised while flushing or closing after
+    /// the drop.
+    pub fn on_close_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Error) + Send + 'static,
+    {
+        self.on_close_error = Some(Box::new(f));
+        self
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.as_mut().unwrap()
+    }
+
+    /// Deconstructs `AsyncDropIO` into the inner I/O object, which is then
+    /// no longer flushed and closed on drop.
+    pub fn into_inner(mut self) -> T {
+        self.inner.take().unwrap()
+    }
+
+    fn pin_inner(&mut self) -> Pin<&mut T> {
+        Pin::new(self.inner.as_mut().unwrap())
+    }
+}
+
+impl<T> Drop for AsyncDropIO<T>
+where
+    T: AsyncWrite + Unpin + Send + 'static,
+{
+    fn drop(&mut self) {
+        let mut inner = match self.inner.take() {
+            Some(inner) => inner,
+            None => return,
+        };
+        if self.closed {
+            return;
+        }
+        let on_close_error = self.on_close_error.take();
+        self.handle.spawn(async move {
+            let result = match inner.flush().await {
+                Ok(()) => inner.close().await,
+                Err(err) => Err(err),
+            };
+            if let (Err(err), Some(f)) = (result, on_close_error) {
+                f(err);
+            }
+        });
+    }
+}
+
+impl<T> fmt::Debug for AsyncDropIO<T>
+where
+    T: AsyncWrite + Unpin + Send + fmt::Debug + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncDropIO")
+            .field("inner", &self.inner)
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> AsyncRead for AsyncDropIO<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.pin_inner().poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.pin_inner().poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for AsyncDropIO<T>
+where
+    T: AsyncWrite + Unpin + Send + 'static,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.pin_inner().poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.pin_inner().poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.pin_inner().poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let poll = self.pin_inner().poll_close(cx);
+        if let Poll::Ready(Ok(())) = poll {
+            self.closed = true;
+        }
+        poll
+    }
+}
@@ -0,0 +1,322 @@
+//! Transparent base64 encoding for text-only transports.
+
+use crate::util::{invalid_data, poll_drain, start_drain};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// The largest amount of data encoded by a single write.
+const MAX_CHUNK: usize = 48 * 1024;
+
+/// The set of characters a [`Base64IO`](crate::base64::Base64IO) encodes
+/// to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, using `+` and `/`.
+    Standard,
+    /// The URL- and filename-safe alphabet, using `-` and `_`.
+    UrlSafe,
+}
+
+/// Configuration of a [`Base64IO`](crate::base64::Base64IO).
+///
+/// The default is the standard alphabet with padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Config {
+    alphabet: Base64Alphabet,
+    padding: bool,
+}
+
+impl Base64Config {
+    /// Creates new [`Base64Config`](crate::base64::Base64Config).
+    pub fn new(alphabet: Base64Alphabet, padding: bool) -> Self {
+        Base64Config { alphabet, padding }
+    }
+
+    /// Returns the alphabet.
+    pub fn alphabet(&self) -> Base64Alphabet {
+        self.alphabet
+    }
+
+    /// Returns whether incomplete groups are padded with `=`.
+    pub fn padding(&self) -> bool {
+        self.padding
+    }
+
+    fn chars(&self) -> &'static [u8; 64] {
+        match self.alphabet {
+            Base64Alphabet::Standard => STANDARD,
+            Base64Alphabet::UrlSafe => URL_SAFE,
+        }
+    }
+
+    fn decode_char(&self, c: u8) -> Option<u8> {
+        match c {
+            b'A'..=b'Z' => Some(c - b'A'),
+            b'a'..=b'z' => Some(c - b'a' + 26),
+            b'0'..=b'9' => Some(c - b'0' + 52),
+            _ if c == self.chars()[62] => Some(62),
+            _ if c == self.chars()[63] => Some(63),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Base64Config {
+    fn default() -> Self {
+        Base64Config::new(Base64Alphabet::Standard, true)
+    }
+}
+
+/// Base64-encodes writes and decodes reads.
+///
+/// Writes are encoded as they come, in groups of three bytes; the bytes of
+/// an incomplete group are held back until more data arrives or the stream
+/// is flushed, which encodes them as a shorter group.
+///
+/// Reads accept base64 split arbitrarily across reads, skip ASCII
+/// whitespace and fail with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) on anything
+/// that isn't valid base64 in the configured alphabet. Padding is accepted
+/// whether or not it is configured, and a padded group may be followed by
+/// more data, so flushing in the middle of a padded stream is fine. Without
+/// padding a short group can't be told apart from the start of the next one,
+/// so unpadded streams should only be flushed after a multiple of three
+/// bytes, or at the end.
+#[pin_project]
+#[derive(Debug)]
+pub struct Base64IO<T> {
+    #[pin]
+    inner: T,
+    config: Base64Config,
+    quad: [u8; 4],
+    quad_len: usize,
+    pads: usize,
+    raw: Vec<u8>,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    pending: [u8; 2],
+    pending_len: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<T> Base64IO<T> {
+    /// Creates new [`Base64IO`](crate::base64::Base64IO).
+    pub fn new(inner: T, config: Base64Config) -> Self {
+        Base64IO {
+            inner,
+            config,
+            quad: [0; 4],
+            quad_len: 0,
+            pads: 0,
+            raw: Vec::new(),
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            pending: [0; 2],
+            pending_len: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> Base64Config {
+        self.config
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `Base64IO` into the inner I/O object, dropping any
+    /// partially decoded data and any encoded data not yet written.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Appends the decoded bytes of the first `len` values of `quad`.
+fn decode_quad(quad: &[u8; 4], len: usize, out: &mut Vec<u8>) {
+    let bytes = [
+        quad[0] << 2 | quad[1] >> 4,
+        (quad[1] & 0x0f) << 4 | quad[2] >> 2,
+        (quad[2] & 0x03) << 6 | quad[3],
+    ];
+    out.extend_from_slice(&bytes[..len - 1]);
+}
+
+/// Appends the encoding of up to three bytes.
+fn encode_group(config: &Base64Config, group: &[u8], out: &mut Vec<u8>) {
+    let chars = config.chars();
+    let b = [
+        group[0],
+        group.get(1).copied().unwrap_or(0),
+        group.get(2).copied().unwrap_or(0),
+    ];
+    let values = [
+        b[0] >> 2,
+        (b[0] & 0x03) << 4 | b[1] >> 4,
+        (b[1] & 0x0f) << 2 | b[2] >> 6,
+        b[2] & 0x3f,
+    ];
+    for &value in &values[..group.len() + 1] {
+        out.push(chars[value as usize]);
+    }
+    if config.padding {
+        for _ in group.len()..3 {
+            out.push(b'=');
+        }
+    }
+}
+
+impl<T> AsyncRead for Base64IO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        loop {
+            if *this.decoded_pos < this.decoded.len() {
+                let available = &this.decoded[*this.decoded_pos..];
+                let n = buf.len().min(available.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                *this.decoded_pos += n;
+                if *this.decoded_pos == this.decoded.len() {
+                    this.decoded.clear();
+                    *this.decoded_pos = 0;
+                }
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let want = (buf.len() / 3 * 4).clamp(4, MAX_CHUNK);
+            this.raw.resize(want, 0);
+            let n = match this.inner.as_mut().poll_read(cx, this.raw) {
+                Poll::Ready(Ok(n)) => n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            if n == 0 {
+                // Only an unpadded short group can still be pending.
+                return match (*this.quad_len, *this.pads) {
+                    (0, 0) => Poll::Ready(Ok(0)),
+                    (2, 0) | (3, 0) => {
+                        decode_quad(this.quad, *this.quad_len, this.decoded);
+                        *this.quad_len = 0;
+                        continue;
+                    }
+                    _ => Poll::Ready(Err(invalid_data("base64 data ended mid-group"))),
+                };
+            }
+
+            for &c in &this.raw[..n] {
+                if c.is_ascii_whitespace() {
+                    continue;
+                }
+                if c == b'=' {
+                    if *this.quad_len < 2 {
+                        return Poll::Ready(Err(invalid_data("misplaced base64 padding")));
+                    }
+                    *this.pads += 1;
+                } else if *this.pads > 0 {
+                    return Poll::Ready(Err(invalid_data("incomplete base64 padding")));
+                } else {
+                    match this.config.decode_char(c) {
+                        Some(value) => {
+                            this.quad[*this.quad_len] = value;
+                            *this.quad_len += 1;
+                        }
+                        None => return Poll::Ready(Err(invalid_data("invalid base64 character"))),
+                    }
+                }
+                if *this.quad_len + *this.pads == 4 {
+                    decode_quad(this.quad, *this.quad_len, this.decoded);
+                    *this.quad_len = 0;
+                    *this.pads = 0;
+                }
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for Base64IO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let data = &buf[..buf.len().min(MAX_CHUNK)];
+        let mut rest = data;
+        if *this.pending_len > 0 {
+            let take = (3 - *this.pending_len).min(rest.len());
+            let mut group = [0u8; 3];
+            group[..*this.pending_len].copy_from_slice(&this.pending[..*this.pending_len]);
+            group[*this.pending_len..*this.pending_len + take].copy_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if *this.pending_len + take < 3 {
+                this.pending[..*this.pending_len + take]
+                    .copy_from_slice(&group[..*this.pending_len + take]);
+                *this.pending_len += take;
+                return Poll::Ready(Ok(data.len()));
+            }
+            encode_group(this.config, &group, this.write_buf);
+            *this.pending_len = 0;
+        }
+        let mut groups = rest.chunks_exact(3);
+        for group in &mut groups {
+            encode_group(this.config, group, this.write_buf);
+        }
+        let tail = groups.remainder();
+        this.pending[..tail.len()].copy_from_slice(tail);
+        *this.pending_len = tail.len();
+
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if *this.pending_len > 0 {
+            encode_group(
+                this.config,
+                &this.pending[..*this.pending_len],
+                this.write_buf,
+            );
+            *this.pending_len = 0;
+        }
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(())) => self.project().inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Wrappers enforcing caps on the amount of data transferred.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reports EOF once `limit` bytes have been read, created by
+/// [`MergeIO::limit_read`](crate::MergeIO::limit_read).
+#[pin_project]
+#[derive(Debug)]
+pub struct BoundedReadIO<T> {
+    #[pin]
+    inner: T,
+    remaining: u64,
+}
+
+impl<T> BoundedReadIO<T> {
+    /// Creates new [`BoundedReadIO`](crate::bounded::BoundedReadIO) that
+    /// allows reading at most `limit` bytes.
+    pub fn new(inner: T, limit: u64) -> Self {
+        BoundedReadIO {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    /// Returns the number of bytes that can still be read.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `BoundedReadIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for BoundedReadIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if *this.remaining == 0 {
+            return Poll::Ready(Ok(0));
+        }
+        let max = (buf.len() as u64).min(*this.remaining) as usize;
+        let poll = this.inner.poll_read(cx, &mut buf[..max]);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.remaining -= n as u64;
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for BoundedReadIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Fails writes once `quota` bytes have been written, created by
+/// [`MergeIO::limit_write`](crate::MergeIO::limit_write).
+///
+/// A write crossing the quota is truncated to the remaining bytes; the next
+/// write fails with [`ErrorKind::StorageFull`](std::io::ErrorKind::StorageFull)
+/// unless configured otherwise via
+/// [`with_error_kind`](crate::bounded::BoundedWriteIO::with_error_kind).
+#[pin_project]
+#[derive(Debug)]
+pub struct BoundedWriteIO<T> {
+    #[pin]
+    inner: T,
+    remaining: u64,
+    error_kind: ErrorKind,
+}
+
+impl<T> BoundedWriteIO<T> {
+    /// Creates new [`BoundedWriteIO`](crate::bounded::BoundedWriteIO) that
+    /// allows writing at most `quota` bytes.
+    pub fn new(inner: T, quota: u64) -> Self {
+        BoundedWriteIO {
+            inner,
+            remaining: quota,
+            error_kind: ErrorKind::StorageFull,
+        }
+    }
+
+    /// Sets the kind of the error returned once the quota is exhausted.
+    pub fn with_error_kind(mut self, kind: ErrorKind) -> Self {
+        self.error_kind = kind;
+        self
+    }
+
+    /// Returns the number of bytes that can still be written.
+    pub fn remaining_quota(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Returns `true` if no more bytes can be written.
+    pub fn quota_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `BoundedWriteIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for BoundedWriteIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for BoundedWriteIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        if buf.is_empty() {
+            return this.inner.poll_write(cx, buf);
+        }
+        if *this.remaining == 0 {
+            return Poll::Ready(Err(Error::new(*this.error_kind, "write quota exhausted")));
+        }
+        let max = (buf.len() as u64).min(*this.remaining) as usize;
+        let poll = this.inner.poll_write(cx, &buf[..max]);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.remaining -= n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
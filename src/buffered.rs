@@ -0,0 +1,265 @@
+//! Buffering wrappers for [`MergeIO`](crate::MergeIO).
+
+use crate::util::poll_drain;
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{Error, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Wraps [`MergeIO`](crate::MergeIO) and coalesces small writes into larger
+/// ones.
+///
+/// Writes are accepted into an internal buffer without touching the inner
+/// writer until the next one wouldn't fit in `capacity` bytes, at which
+/// point the buffer is written out first. Writes of at least `capacity`
+/// bytes go to the inner writer directly once the buffer is empty.
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) and
+/// [`poll_close`](futures::io::AsyncWrite::poll_close) write out the buffer
+/// before flushing or closing the inner writer.
+#[pin_project]
+#[derive(Debug)]
+pub struct BufferedWriteIO<R, W> {
+    #[pin]
+    inner: MergeIO<R, W>,
+    capacity: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<R, W> BufferedWriteIO<R, W> {
+    /// Creates new [`BufferedWriteIO`](crate::buffered::BufferedWriteIO)
+    /// buffering up to `capacity` bytes.
+    pub fn new(inner: MergeIO<R, W>, capacity: usize) -> Self {
+        BufferedWriteIO {
+            inner,
+            capacity,
+            write_buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of bytes buffered but not yet written out.
+    pub fn buffered_len(&self) -> usize {
+        self.write_buf.len()
+    }
+
+    /// Returns the size of the write buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `BufferedWriteIO` into the inner
+    /// [`MergeIO`](crate::MergeIO), dropping any data not yet written out.
+    pub fn into_inner(self) -> MergeIO<R, W> {
+        self.inner
+    }
+}
+
+impl<R, W> AsyncRead for BufferedWriteIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<R, W> AsyncWrite for BufferedWriteIO<R, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if this.write_buf.len() + buf.len() > *this.capacity {
+            match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+                Poll::Ready(Ok(())) => {}
+                poll => return poll.map_ok(|()| 0),
+            }
+        }
+        if buf.len() >= *this.capacity {
+            return this.inner.poll_write(cx, buf);
+        }
+        this.write_buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
+
+/// Wraps [`MergeIO`](crate::MergeIO) and reads ahead into a buffer of
+/// `prefetch_size` bytes.
+///
+/// Once at least half of the buffer has been consumed, the next read tops it
+/// up with whatever the inner reader has ready before serving from it, so
+/// bursts of small reads are served from memory.
+/// No background task is involved: read-ahead only happens while
+/// [`poll_read`](futures::io::AsyncRead::poll_read) is being called. Reads
+/// of at least `prefetch_size` bytes go to the inner reader directly once
+/// the buffer is empty. An error hit while reading ahead is returned after
+/// the data buffered before it.
+#[pin_project]
+#[derive(Debug)]
+pub struct PrefetchReadIO<R, W> {
+    #[pin]
+    inner: MergeIO<R, W>,
+    read_buf: Vec<u8>,
+    pos: usize,
+    end: usize,
+    eof: bool,
+    error: Option<Error>,
+}
+
+impl<R, W> PrefetchReadIO<R, W> {
+    /// Creates new [`PrefetchReadIO`](crate::buffered::PrefetchReadIO)
+    /// reading ahead up to `prefetch_size` bytes.
+    pub fn new(inner: MergeIO<R, W>, prefetch_size: usize) -> Self {
+        PrefetchReadIO {
+            inner,
+            read_buf: vec![0; prefetch_size],
+            pos: 0,
+            end: 0,
+            eof: false,
+            error: None,
+        }
+    }
+
+    /// Returns the number of bytes read ahead but not yet consumed.
+    pub fn buffered_len(&self) -> usize {
+        self.end - self.pos
+    }
+
+    /// Returns the size of the read-ahead buffer.
+    pub fn prefetch_size(&self) -> usize {
+        self.read_buf.len()
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `PrefetchReadIO` into the inner
+    /// [`MergeIO`](crate::MergeIO), dropping any data read ahead.
+    pub fn into_inner(self) -> MergeIO<R, W> {
+        self.inner
+    }
+}
+
+impl<R, W> AsyncRead for PrefetchReadIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if *this.pos == *this.end && this.error.is_none() && buf.len() >= this.read_buf.len() {
+            *this.pos = 0;
+            *this.end = 0;
+            return this.inner.poll_read(cx, buf);
+        }
+
+        // Top up once at least half of the buffer has been consumed, so the
+        // leftover data moved to the front stays small.
+        if *this.end - *this.pos <= this.read_buf.len() / 2 {
+            this.read_buf.copy_within(*this.pos..*this.end, 0);
+            *this.end -= *this.pos;
+            *this.pos = 0;
+            while !*this.eof && this.error.is_none() && *this.end < this.read_buf.len() {
+                match this
+                    .inner
+                    .as_mut()
+                    .poll_read(cx, &mut this.read_buf[*this.end..])
+                {
+                    Poll::Ready(Ok(0)) => *this.eof = true,
+                    Poll::Ready(Ok(n)) => *this.end += n,
+                    Poll::Ready(Err(err)) => *this.error = Some(err),
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        if *this.pos < *this.end {
+            let n = buf.len().min(*this.end - *this.pos);
+            buf[..n].copy_from_slice(&this.read_buf[*this.pos..*this.pos + n]);
+            *this.pos += n;
+            return Poll::Ready(Ok(n));
+        }
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        if *this.eof || buf.is_empty() {
+            *this.eof = false;
+            return Poll::Ready(Ok(0));
+        }
+        Poll::Pending
+    }
+}
+
+impl<R, W> AsyncWrite for PrefetchReadIO<R, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,196 @@
+//! Querying how much data a stream can transfer right away.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::io::Cursor;
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A reader that can tell how many bytes are available to read.
+pub trait ReadCapacity {
+    /// Returns how many bytes can be read without waiting, or `None` if that
+    /// isn't known.
+    fn read_bytes_available(&self) -> Option<usize>;
+}
+
+/// A writer that can tell how many bytes it can accept.
+pub trait WriteCapacity {
+    /// Returns how many bytes can be written without waiting, or `None` if
+    /// that isn't known. Writers growing without bound report `usize::MAX`.
+    fn write_bytes_available(&self) -> Option<usize>;
+}
+
+impl ReadCapacity for Cursor<Vec<u8>> {
+    fn read_bytes_available(&self) -> Option<usize> {
+        let len = self.get_ref().len() as u64;
+        Some(len.saturating_sub(self.position()) as usize)
+    }
+}
+
+impl WriteCapacity for Cursor<Vec<u8>> {
+    fn write_bytes_available(&self) -> Option<usize> {
+        Some(usize::MAX)
+    }
+}
+
+impl ReadCapacity for &[u8] {
+    fn read_bytes_available(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl WriteCapacity for Vec<u8> {
+    fn write_bytes_available(&self) -> Option<usize> {
+        Some(usize::MAX)
+    }
+}
+
+impl<R, W> ReadCapacity for MergeIO<R, W>
+where
+    R: ReadCapacity,
+{
+    fn read_bytes_available(&self) -> Option<usize> {
+        self.reader().read_bytes_available()
+    }
+}
+
+impl<R, W> WriteCapacity for MergeIO<R, W>
+where
+    W: WriteCapacity,
+{
+    fn write_bytes_available(&self) -> Option<usize> {
+        self.writer().write_bytes_available()
+    }
+}
+
+/// Exposes the [`ReadCapacity`](crate::capacity::ReadCapacity) and
+/// [`WriteCapacity`](crate::capacity::WriteCapacity) of the inner I/O object
+/// as inherent methods, passing I/O through untouched.
+///
+/// This allows sizing a read buffer exactly to the data available:
+///
+/// ```
+/// # futures::executor::block_on(async {
+/// use futures::io::{AsyncReadExt, Cursor};
+/// use merge_io::capacity::CapacityReportingIO;
+/// use merge_io::MergeIO;
+///
+/// let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+/// let mut stream = CapacityReportingIO::new(stream);
+///
+/// let mut buf = vec![0; stream.read_bytes_available().unwrap_or(1024)];
+/// stream.read_exact(&mut buf).await.unwrap();
+/// assert_eq!(buf, [1, 2, 3]);
+/// # });
+/// ```
+#[pin_project]
+#[derive(Debug)]
+pub struct CapacityReportingIO<T> {
+    #[pin]
+    inner: T,
+}
+
+impl<T> CapacityReportingIO<T> {
+    /// Creates new
+    /// [`CapacityReportingIO`](crate::capacity::CapacityReportingIO).
+    pub fn new(inner: T) -> Self {
+        CapacityReportingIO { inner }
+    }
+
+    /// Returns how many bytes can be read without waiting, if known.
+    pub fn read_bytes_available(&self) -> Option<usize>
+    where
+        T: ReadCapacity,
+    {
+        self.inner.read_bytes_available()
+    }
+
+    /// Returns how many bytes can be written without waiting, if known.
+    pub fn write_bytes_available(&self) -> Option<usize>
+    where
+        T: WriteCapacity,
+    {
+        self.inner.write_bytes_available()
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CapacityReportingIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> ReadCapacity for CapacityReportingIO<T>
+where
+    T: ReadCapacity,
+{
+    fn read_bytes_available(&self) -> Option<usize> {
+        self.inner.read_bytes_available()
+    }
+}
+
+impl<T> WriteCapacity for CapacityReportingIO<T>
+where
+    T: WriteCapacity,
+{
+    fn write_bytes_available(&self) -> Option<usize> {
+        self.inner.write_bytes_available()
+    }
+}
+
+impl<T> AsyncRead for CapacityReportingIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for CapacityReportingIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
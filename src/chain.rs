@@ -0,0 +1,110 @@
+//! Switching to another reader once the first one is exhausted.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reads from the reader of the inner [`MergeIO`](crate::MergeIO) until EOF
+/// and from `second` after that, while all writes keep going to the inner
+/// writer.
+#[pin_project]
+#[derive(Debug)]
+pub struct ChainReadIO<R1, R2, W> {
+    #[pin]
+    first: MergeIO<R1, W>,
+    #[pin]
+    second: R2,
+    switched: bool,
+}
+
+impl<R1, R2, W> ChainReadIO<R1, R2, W> {
+    /// Creates new [`ChainReadIO`](crate::chain::ChainReadIO) switching to
+    /// `second` when `first`'s reader is exhausted.
+    pub fn new(first: MergeIO<R1, W>, second: R2) -> Self {
+        ChainReadIO {
+            first,
+            second,
+            switched: false,
+        }
+    }
+
+    /// Returns whether reads have switched to the second reader.
+    pub fn has_switched(&self) -> bool {
+        self.switched
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R1, W> {
+        &self.first
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R1, W> {
+        &mut self.first
+    }
+
+    /// Provides access to the second reader.
+    pub fn second(&self) -> &R2 {
+        &self.second
+    }
+
+    /// Provides `mut` access to the second reader.
+    pub fn second_mut(&mut self) -> &mut R2 {
+        &mut self.second
+    }
+
+    /// Deconstructs `ChainReadIO` into the inner [`MergeIO`](crate::MergeIO)
+    /// and the second reader.
+    pub fn into_inner(self) -> (MergeIO<R1, W>, R2) {
+        (self.first, self.second)
+    }
+}
+
+impl<R1, R2, W> AsyncRead for ChainReadIO<R1, R2, W>
+where
+    R1: AsyncRead,
+    R2: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if !*this.switched {
+            match this.first.poll_read(cx, buf) {
+                Poll::Ready(Ok(0)) if !buf.is_empty() => *this.switched = true,
+                poll => return poll,
+            }
+        }
+        this.second.poll_read(cx, buf)
+    }
+}
+
+impl<R1, R2, W> AsyncWrite for ChainReadIO<R1, R2, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().first.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().first.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().first.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().first.poll_close(cx)
+    }
+}
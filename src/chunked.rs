@@ -0,0 +1,167 @@
+//! Fixed-size chunking of reads and writes.
+
+use crate::util::{poll_drain, start_drain};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Presents the data from the inner I/O object in chunks of `chunk_size`
+/// bytes, and hands the data written to it on in chunks of the same size.
+///
+/// Reads are served from a whole chunk assembled from as many inner reads as
+/// it takes, so a read into a buffer of at least `chunk_size` bytes returns
+/// exactly one chunk; smaller buffers get the chunk piece by piece. The
+/// final chunk before EOF may be shorter.
+///
+/// Written data is buffered until a whole chunk is available, with
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) and
+/// [`poll_close`](futures::io::AsyncWrite::poll_close) sending a partial
+/// chunk if there's one. A chunk the inner I/O object only partially accepts
+/// is finished before any more data is taken, so no inner write ever spans
+/// two chunks.
+#[pin_project]
+#[derive(Debug)]
+pub struct ChunkedIO<T> {
+    #[pin]
+    inner: T,
+    chunk_size: usize,
+    read_buf: Vec<u8>,
+    filled: usize,
+    pos: usize,
+    ready: bool,
+    write_buf: Vec<u8>,
+    draining: bool,
+}
+
+impl<T> ChunkedIO<T> {
+    /// Creates new [`ChunkedIO`](crate::chunked::ChunkedIO) with chunks of
+    /// `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn new(inner: T, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be positive");
+        ChunkedIO {
+            inner,
+            chunk_size,
+            read_buf: vec![0; chunk_size],
+            filled: 0,
+            pos: 0,
+            ready: false,
+            write_buf: Vec::with_capacity(chunk_size),
+            draining: false,
+        }
+    }
+
+    /// Returns the chunk size.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ChunkedIO` into the inner I/O object, dropping any
+    /// partially read or written chunk.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for ChunkedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        loop {
+            if *this.ready {
+                let n = buf.len().min(*this.filled - *this.pos);
+                buf[..n].copy_from_slice(&this.read_buf[*this.pos..*this.pos + n]);
+                *this.pos += n;
+                if *this.pos == *this.filled {
+                    *this.ready = false;
+                    *this.filled = 0;
+                    *this.pos = 0;
+                }
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let range = *this.filled..;
+            match this.inner.as_mut().poll_read(cx, &mut this.read_buf[range]) {
+                Poll::Ready(Ok(0)) if *this.filled == 0 => return Poll::Ready(Ok(0)),
+                Poll::Ready(Ok(0)) => *this.ready = true,
+                Poll::Ready(Ok(n)) => {
+                    *this.filled += n;
+                    *this.ready = *this.filled == *this.chunk_size;
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for ChunkedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if *this.draining {
+            match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+                Poll::Ready(Ok(())) => *this.draining = false,
+                poll => return poll.map_ok(|()| 0),
+            }
+        }
+
+        let n = buf.len().min(*this.chunk_size - this.write_buf.len());
+        this.write_buf.extend_from_slice(&buf[..n]);
+        if this.write_buf.len() == *this.chunk_size {
+            start_drain(this.inner, cx, this.write_buf);
+            *this.draining = !this.write_buf.is_empty();
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        *this.draining = !this.write_buf.is_empty();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {
+                *this.draining = false;
+                this.inner.poll_flush(cx)
+            }
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        *this.draining = !this.write_buf.is_empty();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {
+                *this.draining = false;
+                this.inner.poll_close(cx)
+            }
+            poll => poll,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! The circuit-breaker pattern for I/O objects.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// The state of a [`CircuitBreakerIO`](crate::circuit_breaker::CircuitBreakerIO).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Operations are passed through.
+    Closed,
+    /// Operations fail right away.
+    Open,
+    /// The reset timeout has elapsed and the next operation decides whether
+    /// the circuit closes again.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Breaker {
+    fn state(&self) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(at) if at.elapsed() >= self.reset_timeout => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    fn poll_op<T>(&mut self, op: impl FnOnce() -> Poll<Result<T>>) -> Poll<Result<T>> {
+        let state = self.state();
+        if state == CircuitState::Open {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::ConnectionAborted,
+                "circuit breaker is open",
+            )));
+        }
+        let poll = op();
+        match &poll {
+            Poll::Ready(Ok(_)) => self.reset(),
+            Poll::Ready(Err(_)) => {
+                self.failures = self.failures.saturating_add(1);
+                if state == CircuitState::HalfOpen || self.failures >= self.failure_threshold {
+                    self.opened_at = Some(Instant::now());
+                }
+            }
+            Poll::Pending => {}
+        }
+        poll
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.opened_at = None;
+    }
+}
+
+/// Stops using the inner I/O object after `failure_threshold` consecutive
+/// failed operations.
+///
+/// While the circuit is open all operations fail with
+/// [`ErrorKind::ConnectionAborted`](std::io::ErrorKind::ConnectionAborted)
+/// without touching the inner I/O object. Once `reset_timeout` has elapsed
+/// the circuit is half-open: operations are let through again, and the first
+/// one to complete closes the circuit on success or reopens it on failure.
+#[pin_project]
+#[derive(Debug)]
+pub struct CircuitBreakerIO<T> {
+    #[pin]
+    inner: T,
+    breaker: Breaker,
+}
+
+impl<T> CircuitBreakerIO<T> {
+    /// Creates new [`CircuitBreakerIO`](crate::circuit_breaker::CircuitBreakerIO)
+    /// in the closed state.
+    pub fn new(inner: T, failure_threshold: u32, reset_timeout: Duration) -> Self {
+        CircuitBreakerIO {
+            inner,
+            breaker: Breaker {
+                failure_threshold,
+                reset_timeout,
+                failures: 0,
+                opened_at: None,
+            },
+        }
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+
+    /// Closes the circuit and clears the failure count.
+    pub fn force_reset(&mut self) {
+        self.breaker.reset()
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CircuitBreakerIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for CircuitBreakerIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_read(cx, buf))
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_read_vectored(cx, bufs))
+    }
+}
+
+impl<T> AsyncWrite for CircuitBreakerIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_write_vectored(cx, bufs))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_flush(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let inner = this.inner;
+        this.breaker.poll_op(|| inner.poll_close(cx))
+    }
+}
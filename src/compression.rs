@@ -0,0 +1,303 @@
+//! Single-direction zlib compression.
+//!
+//! [`CompressedWriteIO`](crate::compression::CompressedWriteIO) and
+//! [`DecompressedReadIO`](crate::compression::DecompressedReadIO) each wrap
+//! one half, so they can be combined with each other or with any other codec:
+//!
+//! ```
+//! use merge_io::compression::{CompressedWriteIO, DecompressedReadIO};
+//! use merge_io::MergeIO;
+//!
+//! let reader = futures::io::Cursor::new(Vec::<u8>::new());
+//! let writer = Vec::<u8>::new();
+//! let stream = MergeIO::new(DecompressedReadIO::new(reader), CompressedWriteIO::new(writer));
+//! # drop(stream);
+//! ```
+
+use crate::util::{poll_drain, start_drain};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The amount of spare room given to the compressor per call.
+const CHUNK: usize = 8 * 1024;
+
+/// Compresses everything written into a zlib stream.
+///
+/// Compressed data is buffered until the inner writer accepts it.
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) ends the current
+/// deflate block so the peer can decompress everything written so far, and
+/// [`poll_close`](futures::io::AsyncWrite::poll_close) finishes the zlib
+/// stream; writing after that fails.
+#[pin_project]
+#[derive(Debug)]
+pub struct CompressedWriteIO<W> {
+    #[pin]
+    inner: W,
+    compress: Compress,
+    write_buf: Vec<u8>,
+    needs_sync: bool,
+    finished: bool,
+}
+
+impl<W> CompressedWriteIO<W> {
+    /// Creates new [`CompressedWriteIO`](crate::compression::CompressedWriteIO)
+    /// with the default compression level.
+    pub fn new(inner: W) -> Self {
+        Self::with_level(inner, Compression::default())
+    }
+
+    /// Creates new [`CompressedWriteIO`](crate::compression::CompressedWriteIO)
+    /// with the given compression level.
+    pub fn with_level(inner: W, level: Compression) -> Self {
+        CompressedWriteIO {
+            inner,
+            compress: Compress::new(level, true),
+            write_buf: Vec::new(),
+            needs_sync: false,
+            finished: false,
+        }
+    }
+
+    /// Returns the number of uncompressed bytes written so far.
+    pub fn total_in(&self) -> u64 {
+        self.compress.total_in()
+    }
+
+    /// Returns the number of compressed bytes produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.compress.total_out()
+    }
+
+    /// Provides access to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CompressedWriteIO` into the inner writer, dropping any
+    /// compressed data not yet written.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Runs `flush` on the compressor until all its output is in `buf`.
+fn compress_all(compress: &mut Compress, buf: &mut Vec<u8>, flush: FlushCompress) -> Result<()> {
+    loop {
+        buf.reserve(CHUNK);
+        let status = compress
+            .compress_vec(&[], buf, flush)
+            .map_err(Error::other)?;
+        let done = match flush {
+            FlushCompress::Finish => status == Status::StreamEnd,
+            _ => buf.len() < buf.capacity(),
+        };
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+impl<W> AsyncWrite for CompressedWriteIO<W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if *this.finished {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::BrokenPipe,
+                "compressed stream already finished",
+            )));
+        }
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let before = this.compress.total_in();
+        while this.compress.total_in() == before {
+            this.write_buf.reserve(CHUNK);
+            if let Err(err) = this
+                .compress
+                .compress_vec(buf, this.write_buf, FlushCompress::None)
+            {
+                return Poll::Ready(Err(Error::other(err)));
+            }
+        }
+        *this.needs_sync = true;
+        // Deflate keeps most of its input back until it has a block's worth,
+        // so there's usually little or nothing to send here.
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok((this.compress.total_in() - before) as usize))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if *this.needs_sync {
+            if let Err(err) = compress_all(this.compress, this.write_buf, FlushCompress::Sync) {
+                return Poll::Ready(Err(err));
+            }
+            *this.needs_sync = false;
+        }
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if !*this.finished {
+            if let Err(err) = compress_all(this.compress, this.write_buf, FlushCompress::Finish) {
+                return Poll::Ready(Err(err));
+            }
+            *this.finished = true;
+            *this.needs_sync = false;
+        }
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
+
+/// Decompresses a zlib stream read from the inner reader.
+///
+/// Reads fail with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+/// on corrupt input and with
+/// [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the
+/// inner reader ends in the middle of the stream. Once the zlib stream is
+/// complete reads return EOF, leaving anything after it unread.
+#[pin_project]
+#[derive(Debug)]
+pub struct DecompressedReadIO<R> {
+    #[pin]
+    inner: R,
+    decompress: Decompress,
+    read_buf: Vec<u8>,
+    pos: usize,
+    filled: usize,
+    done: bool,
+}
+
+impl<R> DecompressedReadIO<R> {
+    /// Creates new
+    /// [`DecompressedReadIO`](crate::compression::DecompressedReadIO).
+    pub fn new(inner: R) -> Self {
+        DecompressedReadIO {
+            inner,
+            decompress: Decompress::new(true),
+            read_buf: vec![0; CHUNK],
+            pos: 0,
+            filled: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the number of compressed bytes consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.decompress.total_in()
+    }
+
+    /// Returns the number of decompressed bytes produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.decompress.total_out()
+    }
+
+    /// Provides access to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Deconstructs `DecompressedReadIO` into the inner reader, dropping any
+    /// compressed data read ahead.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> AsyncRead for DecompressedReadIO<R>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        loop {
+            if *this.done {
+                return Poll::Ready(Ok(0));
+            }
+            if *this.pos < *this.filled {
+                let (before_in, before_out) =
+                    (this.decompress.total_in(), this.decompress.total_out());
+                let status = match this.decompress.decompress(
+                    &this.read_buf[*this.pos..*this.filled],
+                    buf,
+                    FlushDecompress::None,
+                ) {
+                    Ok(status) => status,
+                    Err(err) => return Poll::Ready(Err(Error::new(ErrorKind::InvalidData, err))),
+                };
+                let consumed = (this.decompress.total_in() - before_in) as usize;
+                let produced = (this.decompress.total_out() - before_out) as usize;
+                *this.pos += consumed;
+                if status == Status::StreamEnd {
+                    *this.done = true;
+                }
+                if produced > 0 {
+                    return Poll::Ready(Ok(produced));
+                }
+                if consumed > 0 {
+                    continue;
+                }
+            }
+
+            // The decompressor needs more input than is buffered.
+            this.read_buf.copy_within(*this.pos..*this.filled, 0);
+            *this.filled -= *this.pos;
+            *this.pos = 0;
+            if *this.filled == this.read_buf.len() {
+                let len = this.read_buf.len();
+                this.read_buf.resize(len * 2, 0);
+            }
+            let range = *this.filled..;
+            match this.inner.as_mut().poll_read(cx, &mut this.read_buf[range]) {
+                Poll::Ready(Ok(0)) if this.decompress.total_in() == 0 && *this.filled == 0 => {
+                    return Poll::Ready(Ok(0))
+                }
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "stream ended before the end of the compressed data",
+                    )))
+                }
+                Poll::Ready(Ok(n)) => *this.filled += n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
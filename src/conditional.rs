@@ -0,0 +1,198 @@
+//! Pausing and resuming I/O at runtime.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Default)]
+struct Wakers {
+    read: Mutex<Option<Waker>>,
+    write: Mutex<Option<Waker>>,
+}
+
+fn wake(slot: &Mutex<Option<Waker>>) {
+    let waker = slot.lock().unwrap_or_else(|err| err.into_inner()).take();
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// Returns whether `gate` is open, storing the task's waker in `slot` if it
+/// isn't.
+fn poll_gate(gate: &AtomicBool, slot: &Mutex<Option<Waker>>, cx: &mut Context<'_>) -> bool {
+    if gate.load(Ordering::SeqCst) {
+        return true;
+    }
+    *slot.lock().unwrap_or_else(|err| err.into_inner()) = Some(cx.waker().clone());
+    // The gate might have been opened before the waker was stored, so check
+    // again to avoid missing the wake-up.
+    gate.load(Ordering::SeqCst)
+}
+
+/// Only touches the inner I/O object while the corresponding gate is open.
+///
+/// While `read_gate` is `false`, reads return `Pending` and keep the task's
+/// waker; while `write_gate` is `false`, the same goes for writes, flushes
+/// and closes. After opening a gate the stored waker has to be woken for the
+/// operation to resume, which the
+/// [`GateHandle`](crate::conditional::GateHandle) returned by
+/// [`handle`](crate::conditional::ConditionalIO::handle) does.
+#[pin_project]
+#[derive(Debug)]
+pub struct ConditionalIO<T> {
+    #[pin]
+    inner: T,
+    handle: GateHandle,
+}
+
+/// Opens and closes the gates of a
+/// [`ConditionalIO`](crate::conditional::ConditionalIO), waking the task
+/// waiting on a gate when it is opened.
+#[derive(Debug, Clone)]
+pub struct GateHandle {
+    read_gate: Arc<AtomicBool>,
+    write_gate: Arc<AtomicBool>,
+    wakers: Arc<Wakers>,
+}
+
+impl GateHandle {
+    /// Opens the read gate and wakes the task waiting on it.
+    pub fn open_read(&self) {
+        self.read_gate.store(true, Ordering::SeqCst);
+        wake(&self.wakers.read);
+    }
+
+    /// Closes the read gate.
+    pub fn close_read(&self) {
+        self.read_gate.store(false, Ordering::SeqCst);
+    }
+
+    /// Opens the write gate and wakes the task waiting on it.
+    pub fn open_write(&self) {
+        self.write_gate.store(true, Ordering::SeqCst);
+        wake(&self.wakers.write);
+    }
+
+    /// Closes the write gate.
+    pub fn close_write(&self) {
+        self.write_gate.store(false, Ordering::SeqCst);
+    }
+
+    /// Wakes the tasks waiting on either gate, for when a gate was opened
+    /// directly through its `AtomicBool`.
+    pub fn wake(&self) {
+        wake(&self.wakers.read);
+        wake(&self.wakers.write);
+    }
+}
+
+impl<T> ConditionalIO<T> {
+    /// Creates new [`ConditionalIO`](crate::conditional::ConditionalIO)
+    /// controlled by `read_gate` and `write_gate`.
+    pub fn new(inner: T, read_gate: Arc<AtomicBool>, write_gate: Arc<AtomicBool>) -> Self {
+        ConditionalIO {
+            inner,
+            handle: GateHandle {
+                read_gate,
+                write_gate,
+                wakers: Arc::default(),
+            },
+        }
+    }
+
+    /// Returns a handle for opening and closing the gates.
+    pub fn handle(&self) -> GateHandle {
+        self.handle.clone()
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ConditionalIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn poll_read_gate(&self, cx: &mut Context<'_>) -> bool {
+        poll_gate(&self.handle.read_gate, &self.handle.wakers.read, cx)
+    }
+
+    fn poll_write_gate(&self, cx: &mut Context<'_>) -> bool {
+        poll_gate(&self.handle.write_gate, &self.handle.wakers.write, cx)
+    }
+}
+
+impl<T> AsyncRead for ConditionalIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if !self.poll_read_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        if !self.poll_read_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for ConditionalIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        if !self.poll_write_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        if !self.poll_write_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if !self.poll_write_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if !self.poll_write_gate(cx) {
+            return Poll::Pending;
+        }
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,132 @@
+//! Byte-counting wrapper for [`MergeIO`](crate::MergeIO).
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Wraps [`MergeIO`](crate::MergeIO) and counts the bytes that flow through
+/// it in each direction.
+///
+/// The counters are two `u64`s stored next to the `MergeIO`, which is the
+/// only space overhead.
+#[pin_project]
+#[derive(Debug)]
+pub struct CountedMergeIO<R, W> {
+    #[pin]
+    inner: MergeIO<R, W>,
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+impl<R, W> CountedMergeIO<R, W> {
+    /// Creates new [`CountedMergeIO`](crate::counted::CountedMergeIO) with
+    /// both counters set to zero.
+    pub fn new(inner: MergeIO<R, W>) -> Self {
+        CountedMergeIO {
+            inner,
+            bytes_read: 0,
+            bytes_written: 0,
+        }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Resets both counters to zero.
+    pub fn reset_counters(&mut self) {
+        self.bytes_read = 0;
+        self.bytes_written = 0;
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CountedMergeIO` into the inner
+    /// [`MergeIO`](crate::MergeIO).
+    pub fn into_inner(self) -> MergeIO<R, W> {
+        self.inner
+    }
+}
+
+impl<R, W> AsyncRead for CountedMergeIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.bytes_read += n as u64;
+        }
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.bytes_read += n as u64;
+        }
+        poll
+    }
+}
+
+impl<R, W> AsyncWrite for CountedMergeIO<R, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.bytes_written += n as u64;
+        }
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.bytes_written += n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
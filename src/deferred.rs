@@ -0,0 +1,203 @@
+//! Establishing connections lazily.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use std::fmt;
+use std::future::Future;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Creates the stream of a
+/// [`DeferredConnectIO`](crate::deferred::DeferredConnectIO).
+///
+/// Implemented for every `FnOnce() -> Fut` where `Fut` resolves to a
+/// `Result` of a stream, such as a [`MergeIO`](crate::MergeIO).
+pub trait ConnectOnce {
+    /// The stream created.
+    type Stream;
+    /// The future resolving to the stream.
+    type Future: Future<Output = Result<Self::Stream>>;
+
+    /// Starts creating the stream.
+    fn connect(self) -> Self::Future;
+}
+
+impl<F, Fut, S> ConnectOnce for F
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<S>>,
+{
+    type Stream = S;
+    type Future = Fut;
+
+    fn connect(self) -> Self::Future {
+        self()
+    }
+}
+
+/// A stream that isn't created until it is first read from or written to.
+///
+/// The factory is called by the first
+/// [`poll_read`](futures::io::AsyncRead::poll_read) or
+/// [`poll_write`](futures::io::AsyncWrite::poll_write), and every later
+/// operation uses the stream it resolved to. Flushing and closing before
+/// that succeed right away without connecting.
+///
+/// If the factory fails, the read or write that was connecting returns its
+/// error. There's no retrying; reads and writes after that fail with
+/// [`ErrorKind::NotConnected`](std::io::ErrorKind::NotConnected).
+pub struct DeferredConnectIO<F: ConnectOnce> {
+    factory: Option<F>,
+    connecting: Option<Pin<Box<F::Future>>>,
+    stream: Option<F::Stream>,
+}
+
+impl<F> DeferredConnectIO<F>
+where
+    F: ConnectOnce,
+{
+    /// Creates new [`DeferredConnectIO`](crate::deferred::DeferredConnectIO)
+    /// getting its stream from `factory`.
+    pub fn new(factory: F) -> Self {
+        DeferredConnectIO {
+            factory: Some(factory),
+            connecting: None,
+            stream: None,
+        }
+    }
+
+    /// Returns whether the stream has been established.
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Provides access to the stream, if it has been established.
+    pub fn get_ref(&self) -> Option<&F::Stream> {
+        self.stream.as_ref()
+    }
+
+    /// Provides `mut` access to the stream, if it has been established.
+    pub fn get_mut(&mut self) -> Option<&mut F::Stream> {
+        self.stream.as_mut()
+    }
+
+    /// Deconstructs `DeferredConnectIO` into the stream, if it has been
+    /// established.
+    pub fn into_inner(self) -> Option<F::Stream> {
+        self.stream
+    }
+
+    fn poll_stream(&mut self, cx: &mut Context<'_>) -> Poll<Result<Pin<&mut F::Stream>>>
+    where
+        F::Stream: Unpin,
+    {
+        if self.stream.is_none() {
+            if self.connecting.is_none() {
+                match self.factory.take() {
+                    Some(factory) => self.connecting = Some(Box::pin(factory.connect())),
+                    None => {
+                        return Poll::Ready(Err(Error::new(
+                            ErrorKind::NotConnected,
+                            "DeferredConnectIO failed to connect",
+                        )))
+                    }
+                }
+            }
+            let connecting = self.connecting.as_mut().unwrap();
+            match connecting.as_mut().poll(cx) {
+                Poll::Ready(result) => {
+                    self.connecting = None;
+                    match result {
+                        Ok(stream) => self.stream = Some(stream),
+                        Err(err) => return Poll::Ready(Err(err)),
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(Pin::new(self.stream.as_mut().unwrap())))
+    }
+}
+
+impl<F> fmt::Debug for DeferredConnectIO<F>
+where
+    F: ConnectOnce,
+    F::Stream: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredConnectIO")
+            .field("connecting", &self.connecting.is_some())
+            .field("stream", &self.stream)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> AsyncRead for DeferredConnectIO<F>
+where
+    F: ConnectOnce + Unpin,
+    F::Stream: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        match self.get_mut().poll_stream(cx) {
+            Poll::Ready(Ok(stream)) => stream.poll_read(cx, buf),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        match self.get_mut().poll_stream(cx) {
+            Poll::Ready(Ok(stream)) => stream.poll_read_vectored(cx, bufs),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<F> AsyncWrite for DeferredConnectIO<F>
+where
+    F: ConnectOnce + Unpin,
+    F::Stream: AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        match self.get_mut().poll_stream(cx) {
+            Poll::Ready(Ok(stream)) => stream.poll_write(cx, buf),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        match self.get_mut().poll_stream(cx) {
+            Poll::Ready(Ok(stream)) => stream.poll_write_vectored(cx, bufs),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.get_mut().stream.as_mut() {
+            Some(stream) => Pin::new(stream).poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.get_mut().stream.as_mut() {
+            Some(stream) => Pin::new(stream).poll_close(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Connected pairs of in-memory streams.
+
+use crate::MergeIO;
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_sink::Sink;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// One end of a pair of connected in-memory streams, created by
+/// [`duplex`](crate::duplex()).
+pub type DuplexStream = MergeIO<DuplexReader, DuplexWriter>;
+
+/// Reads the chunks of data sent by the peer [`DuplexWriter`].
+#[derive(Debug)]
+pub struct DuplexReader {
+    rx: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+/// Sends chunks of data to the peer [`DuplexReader`].
+///
+/// Closing the writer makes the peer reader report EOF once it has read all
+/// the data sent before.
+#[derive(Debug)]
+pub struct DuplexWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+}
+
+/// Creates a pair of connected in-memory streams: bytes written to one of them
+/// can be read from the other one.
+///
+/// Each direction buffers up to `capacity` writes; further writes return
+/// `Pending` until the peer reads some data.
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    let (a_tx, a_rx) = mpsc::channel(capacity);
+    let (b_tx, b_rx) = mpsc::channel(capacity);
+    (
+        MergeIO::new(DuplexReader::new(b_rx), DuplexWriter { tx: a_tx }),
+        MergeIO::new(DuplexReader::new(a_rx), DuplexWriter { tx: b_tx }),
+    )
+}
+
+impl DuplexReader {
+    fn new(rx: mpsc::Receiver<Vec<u8>>) -> Self {
+        DuplexReader {
+            rx,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl AsyncRead for DuplexReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        while self.pos == self.chunk.len() {
+            match Pin::new(&mut self.rx).poll_next(cx) {
+                Poll::Ready(Some(chunk)) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Poll::Ready(None) => return Poll::Ready(Ok(0)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Poll::Ready(Ok(n))
+    }
+}
+
+fn disconnected() -> Error {
+    Error::new(ErrorKind::BrokenPipe, "duplex peer has been dropped")
+}
+
+impl AsyncWrite for DuplexWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        match Pin::new(&mut self.tx).poll_ready(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(_)) => return Poll::Ready(Err(disconnected())),
+            Poll::Pending => return Poll::Pending,
+        }
+        match Pin::new(&mut self.tx).start_send(buf.to_vec()) {
+            Ok(()) => Poll::Ready(Ok(buf.len())),
+            Err(_) => Poll::Ready(Err(disconnected())),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.tx.close_channel();
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,615 @@
+//! Fault-injection wrappers for resilience testing.
+//!
+//! Decisions are driven by a small seeded generator, so a given seed always
+//! produces the same sequence of faults.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A xorshift64* generator; not suitable for anything but tests.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Xorshift gets stuck at zero.
+        Rng(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in `0.0..1.0`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a number in `1..=max`, `max` being at least 1.
+    fn between_one_and(&mut self, max: usize) -> usize {
+        1 + (self.next_u64() % max as u64) as usize
+    }
+}
+
+/// Makes writes accept a random, non-zero number of bytes at most
+/// `max_fraction` of the buffer passed in, forcing callers to handle partial
+/// writes.
+#[pin_project]
+#[derive(Debug)]
+pub struct PartialWriteIO<T> {
+    #[pin]
+    inner: T,
+    rng: Rng,
+    max_fraction: f64,
+}
+
+impl<T> PartialWriteIO<T> {
+    /// Creates new [`PartialWriteIO`](crate::fault::PartialWriteIO).
+    ///
+    /// `max_fraction` is clamped to `0.0..=1.0`; at least one byte is always
+    /// offered to the inner writer.
+    pub fn new(inner: T, rng_seed: u64, max_fraction: f64) -> Self {
+        PartialWriteIO {
+            inner,
+            rng: Rng::new(rng_seed),
+            max_fraction: max_fraction.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `PartialWriteIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for PartialWriteIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for PartialWriteIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        if buf.is_empty() {
+            return this.inner.poll_write(cx, buf);
+        }
+        let max = ((buf.len() as f64 * *this.max_fraction) as usize).max(1);
+        let len = this.rng.between_one_and(max);
+        this.inner.poll_write(cx, &buf[..len])
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Makes reads fill a random, non-zero part of the buffer passed in, forcing
+/// callers to loop until they have all the data they need.
+///
+/// The inner reader is simply offered a shorter buffer, so no data is held
+/// back between reads.
+#[pin_project]
+#[derive(Debug)]
+pub struct PartialReadIO<T> {
+    #[pin]
+    inner: T,
+    rng: Rng,
+}
+
+impl<T> PartialReadIO<T> {
+    /// Creates new [`PartialReadIO`](crate::fault::PartialReadIO).
+    pub fn new(inner: T, rng_seed: u64) -> Self {
+        PartialReadIO {
+            inner,
+            rng: Rng::new(rng_seed),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `PartialReadIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for PartialReadIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if buf.is_empty() {
+            return this.inner.poll_read(cx, buf);
+        }
+        let len = this.rng.between_one_and(buf.len());
+        this.inner.poll_read(cx, &mut buf[..len])
+    }
+}
+
+impl<T> AsyncWrite for PartialReadIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Fails reads and writes with a given error kind once a configured number of
+/// bytes has been transferred in the respective direction.
+///
+/// Calls up to the threshold succeed normally. The call crossing it passes
+/// the bytes up to the threshold on to the inner I/O object and then fails,
+/// and every call after that fails without touching the inner I/O object. A
+/// threshold of `None` never fails.
+#[pin_project]
+#[derive(Debug)]
+pub struct ErrorAfterIO<T> {
+    #[pin]
+    inner: T,
+    read_remaining: Option<u64>,
+    write_remaining: Option<u64>,
+    kind: ErrorKind,
+}
+
+impl<T> ErrorAfterIO<T> {
+    /// Creates new [`ErrorAfterIO`](crate::fault::ErrorAfterIO).
+    pub fn new(
+        inner: T,
+        read_fail_after: Option<u64>,
+        write_fail_after: Option<u64>,
+        kind: ErrorKind,
+    ) -> Self {
+        ErrorAfterIO {
+            inner,
+            read_remaining: read_fail_after,
+            write_remaining: write_fail_after,
+            kind,
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ErrorAfterIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Returns how much of a `len` byte buffer may be passed on, and whether the
+/// call crosses the threshold.
+fn allowance(remaining: Option<u64>, len: usize) -> (usize, bool) {
+    match remaining {
+        Some(remaining) if len as u64 > remaining => (remaining as usize, true),
+        _ => (len, false),
+    }
+}
+
+/// Counts the bytes transferred by `poll`, failing a call crossing the
+/// threshold once it has reached it.
+fn count(
+    remaining: &mut Option<u64>,
+    crosses: bool,
+    kind: ErrorKind,
+    poll: Poll<Result<usize>>,
+) -> Poll<Result<usize>> {
+    if let (Some(remaining), Poll::Ready(Ok(n))) = (remaining, &poll) {
+        *remaining -= *n as u64;
+        if crosses && *remaining == 0 {
+            return Poll::Ready(Err(Error::new(kind, "injected error")));
+        }
+    }
+    poll
+}
+
+impl<T> AsyncRead for ErrorAfterIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (len, crosses) = allowance(*this.read_remaining, buf.len());
+        if crosses && len == 0 {
+            return Poll::Ready(Err(Error::new(*this.kind, "injected error")));
+        }
+        let poll = this.inner.poll_read(cx, &mut buf[..len]);
+        count(this.read_remaining, crosses, *this.kind, poll)
+    }
+}
+
+impl<T> AsyncWrite for ErrorAfterIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (len, crosses) = allowance(*this.write_remaining, buf.len());
+        if crosses && len == 0 {
+            return Poll::Ready(Err(Error::new(*this.kind, "injected error")));
+        }
+        let poll = this.inner.poll_write(cx, &buf[..len]);
+        count(this.write_remaining, crosses, *this.kind, poll)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Limits every write to a window that starts small and grows with the data
+/// written, simulating TCP slow start.
+///
+/// Each write offers the inner writer at most `window` bytes, and the window
+/// grows by the number of bytes actually written, up to `max_window`. It
+/// thus doubles with every write using it in full, while short writes grow
+/// it only by as much as they wrote.
+#[pin_project]
+#[derive(Debug)]
+pub struct SlowStartWriteIO<T> {
+    #[pin]
+    inner: T,
+    window: usize,
+    max_window: usize,
+}
+
+impl<T> SlowStartWriteIO<T> {
+    /// Creates new [`SlowStartWriteIO`](crate::fault::SlowStartWriteIO).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `initial_window` is `0` or greater than `max_window`.
+    pub fn new(inner: T, initial_window: usize, max_window: usize) -> Self {
+        assert!(initial_window > 0, "initial_window must be positive");
+        assert!(
+            initial_window <= max_window,
+            "initial_window exceeds max_window"
+        );
+        SlowStartWriteIO {
+            inner,
+            window: initial_window,
+            max_window,
+        }
+    }
+
+    /// Returns the current window.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the size the window grows up to.
+    pub fn max_window(&self) -> usize {
+        self.max_window
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `SlowStartWriteIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for SlowStartWriteIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for SlowStartWriteIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let len = buf.len().min(*this.window);
+        let poll = this.inner.poll_write(cx, &buf[..len]);
+        if let Poll::Ready(Ok(n)) = poll {
+            *this.window = this.window.saturating_add(n).min(*this.max_window);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Picks the bits to flip in a stream of data, each one independently with
+/// the given probability.
+///
+/// Rather than rolling for every bit, the gap to the next flipped bit is
+/// drawn from the matching geometric distribution.
+#[derive(Debug, Clone)]
+struct BitFlipper {
+    rng: Rng,
+    rate: f64,
+    until_flip: Option<u64>,
+}
+
+impl BitFlipper {
+    fn draw_gap(&mut self) -> u64 {
+        if self.rate >= 1.0 {
+            return 0;
+        }
+        let uniform = 1.0 - self.rng.next_f64();
+        (uniform.ln() / (1.0 - self.rate).ln()) as u64
+    }
+
+    /// Calls `flip` with the index of every bit to flip in the next `len`
+    /// bytes of the stream.
+    fn walk(&mut self, len: usize, mut flip: impl FnMut(usize)) {
+        if self.rate <= 0.0 {
+            return;
+        }
+        let bits = len as u64 * 8;
+        let mut pos = 0;
+        loop {
+            let gap = match self.until_flip {
+                Some(gap) => gap,
+                None => self.draw_gap(),
+            };
+            if gap >= bits - pos {
+                self.until_flip = Some(gap - (bits - pos));
+                return;
+            }
+            pos += gap;
+            flip(pos as usize);
+            pos += 1;
+            self.until_flip = None;
+        }
+    }
+
+    fn corrupt(&mut self, data: &mut [u8]) {
+        self.walk(data.len(), |bit| data[bit / 8] ^= 0x80 >> (bit % 8));
+    }
+}
+
+/// Flips random bits in the data going through in both directions and,
+/// optionally, cuts reads and writes short.
+///
+/// Every bit is flipped with probability `bit_error_rate`. With
+/// [`with_drop_probability`](crate::fault::CorruptingIO::with_drop_probability),
+/// each read may also end the stream early by returning `Ok(0)` and each
+/// write may fail with
+/// [`ErrorKind::BrokenPipe`](std::io::ErrorKind::BrokenPipe), without
+/// touching the inner I/O object. Written data is corrupted in a copy, so
+/// the caller's buffer is left alone.
+#[pin_project]
+#[derive(Debug)]
+pub struct CorruptingIO<T> {
+    #[pin]
+    inner: T,
+    read_flipper: BitFlipper,
+    write_flipper: BitFlipper,
+    drop_rng: Rng,
+    drop_probability: f64,
+    write_buf: Vec<u8>,
+}
+
+impl<T> CorruptingIO<T> {
+    /// Creates new [`CorruptingIO`](crate::fault::CorruptingIO).
+    ///
+    /// `bit_error_rate` is clamped to `0.0..=1.0`.
+    pub fn new(inner: T, bit_error_rate: f64, seed: u64) -> Self {
+        let rate = bit_error_rate.clamp(0.0, 1.0);
+        let flipper = |seed| BitFlipper {
+            rng: Rng::new(seed),
+            rate,
+            until_flip: None,
+        };
+        CorruptingIO {
+            inner,
+            read_flipper: flipper(seed),
+            write_flipper: flipper(seed.rotate_left(21)),
+            drop_rng: Rng::new(seed.rotate_left(42)),
+            drop_probability: 0.0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Sets the probability of a read ending the stream early or a write
+    /// failing, clamped to `0.0..=1.0`.
+    pub fn with_drop_probability(mut self, drop_probability: f64) -> Self {
+        self.drop_probability = drop_probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the probability of a bit being flipped.
+    pub fn bit_error_rate(&self) -> f64 {
+        self.read_flipper.rate
+    }
+
+    /// Returns the probability of a read or write being dropped.
+    pub fn drop_probability(&self) -> f64 {
+        self.drop_probability
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CorruptingIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn dropped(rng: &mut Rng, probability: f64) -> bool {
+    probability > 0.0 && rng.next_f64() < probability
+}
+
+impl<T> AsyncRead for CorruptingIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if !buf.is_empty() && dropped(this.drop_rng, *this.drop_probability) {
+            return Poll::Ready(Ok(0));
+        }
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.read_flipper.corrupt(&mut buf[..n]);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for CorruptingIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        if !buf.is_empty() && dropped(this.drop_rng, *this.drop_probability) {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::BrokenPipe,
+                "injected broken pipe",
+            )));
+        }
+        this.write_buf.clear();
+        this.write_buf.extend_from_slice(buf);
+        // Corrupt with a copy of the state and only advance the real one
+        // past the bytes actually written, so a retried write gets the same
+        // corruption as if it had been accepted in one go.
+        this.write_flipper.clone().corrupt(this.write_buf);
+        let poll = this.inner.poll_write(cx, this.write_buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.write_flipper.walk(n, |_| {});
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,241 @@
+//! Window-based flow control.
+//!
+//! Data is sent as frames made of a zero byte, a big-endian `u32` length and
+//! the payload. Acknowledgements are a one byte followed by a big-endian
+//! `u32` count of bytes the receiver has consumed.
+
+use crate::util::{invalid_data, poll_drain, start_drain, FrameReader, MAX_PAYLOAD};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const FRAME_DATA: u8 = 0;
+const FRAME_ACK: u8 = 1;
+const HEADER_LEN: usize = 5;
+
+fn frame_value(header: &[u8]) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&header[1..HEADER_LEN]);
+    u32::from_be_bytes(value)
+}
+
+fn payload_len(header: &[u8]) -> Result<usize> {
+    match header[0] {
+        // Acknowledgements carry their count in the header.
+        FRAME_ACK => Ok(0),
+        FRAME_DATA => Ok(frame_value(header) as usize),
+        _ => Err(invalid_data("unknown frame type")),
+    }
+}
+
+/// Limits the amount of data in flight to a window the receiver replenishes
+/// with acknowledgements, so a slow reader can't make buffers pile up in
+/// between.
+///
+/// Both ends of the stream have to be wrapped with the same window. Writes
+/// return `Pending` once the window is exhausted, reading from the inner I/O
+/// object in the meantime to pick up acknowledgements; data received while
+/// waiting is kept for the next read. The receiving side acknowledges
+/// consumed data once half a window has been read, and keeps retrying to send
+/// the acknowledgement on subsequent reads if the inner I/O object doesn't
+/// accept it right away.
+///
+/// Frames and acknowledgements are buffered until the inner I/O object
+/// accepts them, so [`poll_flush`](futures::io::AsyncWrite::poll_flush) has
+/// to be called to make sure everything is sent.
+#[pin_project]
+#[derive(Debug)]
+pub struct WindowedFlowControlIO<T> {
+    #[pin]
+    inner: T,
+    initial_window: u32,
+    send_window: u32,
+    unacked: u32,
+    frames: FrameReader,
+    incoming: VecDeque<u8>,
+    write_buf: Vec<u8>,
+}
+
+impl<T> WindowedFlowControlIO<T> {
+    /// Creates new
+    /// [`WindowedFlowControlIO`](crate::flow_control::WindowedFlowControlIO)
+    /// allowing up to `initial_window` unacknowledged bytes in flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `initial_window` is zero.
+    pub fn new(inner: T, initial_window: u32) -> Self {
+        assert!(initial_window > 0, "window must not be zero");
+        WindowedFlowControlIO {
+            inner,
+            initial_window,
+            send_window: initial_window,
+            unacked: 0,
+            frames: FrameReader::default(),
+            incoming: VecDeque::new(),
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Returns the window the stream was created with.
+    pub fn initial_window(&self) -> u32 {
+        self.initial_window
+    }
+
+    /// Returns how many more bytes can be sent before waiting for an
+    /// acknowledgement.
+    pub fn send_window(&self) -> u32 {
+        self.send_window
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `WindowedFlowControlIO` into the inner I/O object,
+    /// dropping any received but unread data and any frames not yet written.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> WindowedFlowControlIO<T>
+where
+    T: AsyncRead,
+{
+    /// Reads a single frame, queueing its data or applying its
+    /// acknowledgement. Returns `false` on a clean EOF between frames.
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        let this = self.project();
+        let poll = this
+            .frames
+            .poll_frame(this.inner, cx, HEADER_LEN, MAX_PAYLOAD, payload_len);
+        let frame = match poll {
+            Poll::Ready(Ok(Some(frame))) => frame,
+            Poll::Ready(Ok(None)) => return Poll::Ready(Ok(false)),
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        };
+        if frame[0] == FRAME_ACK {
+            *this.send_window = this.send_window.saturating_add(frame_value(frame));
+        } else {
+            this.incoming.extend(&frame[HEADER_LEN..]);
+        }
+        Poll::Ready(Ok(true))
+    }
+}
+
+impl<T> AsyncRead for WindowedFlowControlIO<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        {
+            // Send along acknowledgements left over from earlier reads, so a
+            // receiver that never writes doesn't stall the sender. A pending
+            // write doesn't hold up reading; it is retried on the next read.
+            let this = self.as_mut().project();
+            if let Poll::Ready(Err(err)) = poll_drain(this.inner, cx, this.write_buf) {
+                return Poll::Ready(Err(err));
+            }
+        }
+        while self.incoming.is_empty() && !buf.is_empty() {
+            match self.as_mut().poll_frame(cx) {
+                Poll::Ready(Ok(true)) => {}
+                Poll::Ready(Ok(false)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let this = self.project();
+        let n = buf.len().min(this.incoming.len());
+        for (dst, src) in buf.iter_mut().zip(this.incoming.drain(..n)) {
+            *dst = src;
+        }
+        *this.unacked += n as u32;
+        if *this.unacked >= (*this.initial_window / 2).max(1) {
+            this.write_buf.push(FRAME_ACK);
+            this.write_buf
+                .extend_from_slice(&this.unacked.to_be_bytes());
+            *this.unacked = 0;
+            // Whatever part of the acknowledgement isn't sent now is retried
+            // at the start of the next read.
+            start_drain(this.inner, cx, this.write_buf);
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<T> AsyncWrite for WindowedFlowControlIO<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        {
+            let this = self.as_mut().project();
+            match poll_drain(this.inner, cx, this.write_buf) {
+                Poll::Ready(Ok(())) => {}
+                poll => return poll.map_ok(|()| 0),
+            }
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        while self.send_window == 0 {
+            match self.as_mut().poll_frame(cx) {
+                Poll::Ready(Ok(true)) => {}
+                Poll::Ready(Ok(false)) => {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::BrokenPipe,
+                        "stream ended while waiting for an acknowledgement",
+                    )))
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let this = self.project();
+        let n = buf.len().min(*this.send_window as usize).min(MAX_PAYLOAD);
+        this.write_buf.push(FRAME_DATA);
+        this.write_buf.extend_from_slice(&(n as u32).to_be_bytes());
+        this.write_buf.extend_from_slice(&buf[..n]);
+        *this.send_window -= n as u32;
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Length-prefixed framing.
+//!
+//! Every frame is sent as a big-endian payload length followed by the
+//! payload itself. [`PacketIO`](crate::framing::PacketIO) lets the width of
+//! the length be chosen, [`FramedIO`](crate::framing::FramedIO) builds on it
+//! with a `u32` length and adds a polling interface.
+
+use crate::util::{poll_drain, FrameReader};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::future::poll_fn;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reads and writes length-prefixed frames over an I/O object.
+///
+/// This is a [`PacketIO`](crate::framing::PacketIO) with a
+/// [`LengthPrefix::U32`](crate::framing::LengthPrefix::U32) length that can
+/// also be driven by polling. Partially received frames are kept across
+/// reads, so a [`read_frame`](crate::framing::FramedIO::read_frame) future
+/// can be dropped and a later call picks up where it stopped.
+#[derive(Debug)]
+pub struct FramedIO<T> {
+    packets: PacketIO<T>,
+}
+
+impl<T> FramedIO<T> {
+    /// Creates new [`FramedIO`](crate::framing::FramedIO) accepting frames of
+    /// at most `max_frame_size` bytes of payload in both directions. The
+    /// limit is capped at `u32::MAX`.
+    pub fn new(inner: T, max_frame_size: usize) -> Self {
+        FramedIO {
+            packets: PacketIO::new(inner, LengthPrefix::U32, max_frame_size),
+        }
+    }
+
+    /// Returns the maximum payload size of a frame.
+    pub fn max_frame_size(&self) -> usize {
+        self.packets.max_packet_size()
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        self.packets.get_ref()
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.packets.get_mut()
+    }
+
+    /// Deconstructs `FramedIO` into the inner I/O object, dropping any
+    /// partially read or written frame.
+    pub fn into_inner(self) -> T {
+        self.packets.into_inner()
+    }
+}
+
+impl<T> FramedIO<T>
+where
+    T: AsyncRead + Unpin,
+{
+    /// Polls for the next frame and returns its payload.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+    /// if the frame exceeds the maximum size, skipping it, and with
+    /// [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the
+    /// stream ends before a frame is complete, including before its first
+    /// byte.
+    pub fn poll_read_frame(&mut self, cx: &mut Context<'_>) -> Poll<Result<Vec<u8>>> {
+        self.packets.poll_recv_packet(cx)
+    }
+
+    /// Reads the next frame and returns its payload.
+    ///
+    /// See [`poll_read_frame`](crate::framing::FramedIO::poll_read_frame) for
+    /// the possible errors.
+    pub async fn read_frame(&mut self) -> Result<Vec<u8>> {
+        poll_fn(|cx| self.poll_read_frame(cx)).await
+    }
+}
+
+impl<T> FramedIO<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    /// Queues a frame carrying `data` to be written by
+    /// [`poll_write_frames`](crate::framing::FramedIO::poll_write_frames).
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput)
+    /// if `data` exceeds the maximum frame size.
+    pub fn start_frame(&mut self, data: &[u8]) -> Result<()> {
+        self.packets.start_packet(data)
+    }
+
+    /// Writes all the queued frames and flushes the inner I/O object.
+    pub fn poll_write_frames(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.packets.poll_send_packets(cx)
+    }
+
+    /// Writes a frame carrying `data` and flushes the inner I/O object.
+    ///
+    /// See [`start_frame`](crate::framing::FramedIO::start_frame) for the
+    /// possible errors.
+    pub async fn write_frame(&mut self, data: &[u8]) -> Result<()> {
+        self.start_frame(data)?;
+        poll_fn(|cx| self.poll_write_frames(cx)).await
+    }
+}
+
+/// The width of the big-endian length prefix of a
+/// [`PacketIO`](crate::framing::PacketIO) packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// A single byte, for packets of up to 255 bytes.
+    U8,
+    /// A `u16`, for packets of up to 65535 bytes.
+    U16,
+    /// A `u32`.
+    U32,
+}
+
+impl LengthPrefix {
+    fn len(self) -> usize {
+        match self {
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16 => 2,
+            LengthPrefix::U32 => 4,
+        }
+    }
+
+    fn max(self) -> usize {
+        match self {
+            LengthPrefix::U8 => u8::MAX as usize,
+            LengthPrefix::U16 => u16::MAX as usize,
+            LengthPrefix::U32 => u32::MAX as usize,
+        }
+    }
+
+    fn encode(self, len: usize, buf: &mut Vec<u8>) {
+        match self {
+            LengthPrefix::U8 => buf.push(len as u8),
+            LengthPrefix::U16 => buf.extend_from_slice(&(len as u16).to_be_bytes()),
+            LengthPrefix::U32 => buf.extend_from_slice(&(len as u32).to_be_bytes()),
+        }
+    }
+
+    fn decode(self, header: &[u8]) -> usize {
+        header
+            .iter()
+            .fold(0usize, |len, &byte| (len << 8) | byte as usize)
+    }
+}
+
+/// Sends and receives length-prefixed packets over an I/O object, with the
+/// width of the length prefix chosen by a
+/// [`LengthPrefix`](crate::framing::LengthPrefix).
+///
+/// Partially received packets are kept across calls, so a
+/// [`recv_packet`](crate::framing::PacketIO::recv_packet) future can be
+/// dropped and a later call picks up where it stopped. Packets exceeding the
+/// maximum size are skipped: their payload is discarded before the next
+/// packet is read, so the stream stays usable after the error.
+#[derive(Debug)]
+pub struct PacketIO<T> {
+    inner: T,
+    prefix: LengthPrefix,
+    max_packet_size: usize,
+    frames: FrameReader,
+    write_buf: Vec<u8>,
+}
+
+impl<T> PacketIO<T> {
+    /// Creates new [`PacketIO`](crate::framing::PacketIO) accepting packets
+    /// of at most `max_packet_size` bytes in both directions. The limit is
+    /// capped at what `prefix` can express.
+    pub fn new(inner: T, prefix: LengthPrefix, max_packet_size: usize) -> Self {
+        PacketIO {
+            inner,
+            prefix,
+            max_packet_size: max_packet_size.min(prefix.max()),
+            frames: FrameReader::default(),
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Returns the width of the length prefix.
+    pub fn prefix(&self) -> LengthPrefix {
+        self.prefix
+    }
+
+    /// Returns the maximum packet size.
+    pub fn max_packet_size(&self) -> usize {
+        self.max_packet_size
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `PacketIO` into the inner I/O object, dropping any
+    /// partially received or sent packet.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> PacketIO<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_recv_packet(&mut self, cx: &mut Context<'_>) -> Poll<Result<Vec<u8>>> {
+        let prefix = self.prefix;
+        match self.frames.poll_frame(
+            Pin::new(&mut self.inner),
+            cx,
+            prefix.len(),
+            self.max_packet_size,
+            |header| Ok(prefix.decode(header)),
+        ) {
+            Poll::Ready(Ok(Some(packet))) => Poll::Ready(Ok(packet[prefix.len()..].to_vec())),
+            Poll::Ready(Ok(None)) => Poll::Ready(Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "stream ended before a complete packet",
+            ))),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Receives the next packet.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+    /// if the packet exceeds the maximum size, skipping it, and with
+    /// [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the
+    /// stream ends before a packet is complete, including before its first
+    /// byte.
+    pub async fn recv_packet(&mut self) -> Result<Vec<u8>> {
+        poll_fn(|cx| self.poll_recv_packet(cx)).await
+    }
+}
+
+impl<T> PacketIO<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    fn start_packet(&mut self, data: &[u8]) -> Result<()> {
+        if data.len() > self.max_packet_size {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "packet exceeds the maximum packet size",
+            ));
+        }
+        self.prefix.encode(data.len(), &mut self.write_buf);
+        self.write_buf.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn poll_send_packets(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match poll_drain(Pin::new(&mut self.inner), cx, &mut self.write_buf) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.inner).poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    /// Sends a packet carrying `data` and flushes the inner I/O object.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput)
+    /// if `data` exceeds the maximum packet size. If the future is dropped
+    /// before completing, the rest of the packet is sent by the next call.
+    pub async fn send_packet(&mut self, data: &[u8]) -> Result<()> {
+        self.start_packet(data)?;
+        poll_fn(|cx| self.poll_send_packets(cx)).await
+    }
+}
@@ -0,0 +1,178 @@
+//! `xxd`-style dumps of the traffic.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{self, Result, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const DEFAULT_LINE_WIDTH: usize = 16;
+
+/// Writes a hex dump of all the data read and written to `output`.
+///
+/// Each line holds the direction (`<` for data read, `>` for data written),
+/// the offset within that direction's stream, the bytes in hex, and their
+/// ASCII representation, with non-printable bytes shown as `.`. The dump is
+/// written synchronously as operations complete, and failures to write it
+/// are ignored.
+#[pin_project]
+#[derive(Debug)]
+pub struct HexDumpIO<T, W2> {
+    #[pin]
+    inner: T,
+    output: W2,
+    line_width: usize,
+    read_offset: u64,
+    write_offset: u64,
+}
+
+impl<T, W2> HexDumpIO<T, W2> {
+    /// Creates new [`HexDumpIO`](crate::hexdump::HexDumpIO) dumping to
+    /// `output` with 16 bytes per line.
+    pub fn new(inner: T, output: W2) -> Self {
+        HexDumpIO {
+            inner,
+            output,
+            line_width: DEFAULT_LINE_WIDTH,
+            read_offset: 0,
+            write_offset: 0,
+        }
+    }
+
+    /// Sets the number of bytes shown per line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_width` is neither 16 nor 32.
+    pub fn with_line_width(mut self, line_width: usize) -> Self {
+        assert!(
+            line_width == 16 || line_width == 32,
+            "line_width must be 16 or 32"
+        );
+        self.line_width = line_width;
+        self
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Provides access to the dump output.
+    pub fn output(&self) -> &W2 {
+        &self.output
+    }
+
+    /// Deconstructs `HexDumpIO` into the inner I/O object and the dump
+    /// output.
+    pub fn into_inner(self) -> (T, W2) {
+        (self.inner, self.output)
+    }
+}
+
+impl<T> HexDumpIO<T, io::Stderr> {
+    /// Creates new [`HexDumpIO`](crate::hexdump::HexDumpIO) dumping to the
+    /// standard error.
+    pub fn stderr(inner: T) -> Self {
+        Self::new(inner, io::stderr())
+    }
+}
+
+fn dump(
+    output: &mut impl Write,
+    prefix: char,
+    offset: u64,
+    line_width: usize,
+    data: &[u8],
+) -> Result<()> {
+    for (i, line) in data.chunks(line_width).enumerate() {
+        let mut hex = String::with_capacity(line_width * 5 / 2);
+        for (j, byte) in line.iter().enumerate() {
+            if j > 0 && j % 2 == 0 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x}", byte));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b == b' ' || b.is_ascii_graphic() {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(
+            output,
+            "{} {:08x}: {:<width$}  {}",
+            prefix,
+            offset + (i * line_width) as u64,
+            hex,
+            ascii,
+            width = line_width * 5 / 2 - 1,
+        )?;
+    }
+    Ok(())
+}
+
+impl<T, W2> AsyncRead for HexDumpIO<T, W2>
+where
+    T: AsyncRead,
+    W2: Write,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            let _ = dump(
+                this.output,
+                '<',
+                *this.read_offset,
+                *this.line_width,
+                &buf[..n],
+            );
+            *this.read_offset += n as u64;
+        }
+        poll
+    }
+}
+
+impl<T, W2> AsyncWrite for HexDumpIO<T, W2>
+where
+    T: AsyncWrite,
+    W2: Write,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            let _ = dump(
+                this.output,
+                '>',
+                *this.write_offset,
+                *this.line_width,
+                &buf[..n],
+            );
+            *this.write_offset += n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
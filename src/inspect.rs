@@ -0,0 +1,195 @@
+//! Side-effect hooks observing the data passing through an I/O object.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::fmt;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::ops::Deref;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A hook invoked with the bytes transferred by a successful poll.
+///
+/// Implemented for every `FnMut(&[u8])` and for
+/// [`NoopHook`](crate::inspect::NoopHook).
+pub trait InspectHook {
+    /// Observes `data`.
+    fn inspect(&mut self, data: &[u8]);
+}
+
+impl<F> InspectHook for F
+where
+    F: FnMut(&[u8]),
+{
+    fn inspect(&mut self, data: &[u8]) {
+        self(data)
+    }
+}
+
+/// An [`InspectHook`](crate::inspect::InspectHook) that does nothing, used
+/// for the direction that isn't inspected.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopHook;
+
+impl InspectHook for NoopHook {
+    fn inspect(&mut self, _data: &[u8]) {}
+}
+
+/// Calls `FR` with every chunk of data read and `FW` with every chunk of data
+/// written, created by
+/// [`MergeIO::inspect_read`](crate::MergeIO::inspect_read) and
+/// [`MergeIO::inspect_write`](crate::MergeIO::inspect_write).
+///
+/// Hooks are only invoked when a poll returns `Ready(Ok(n))` with `n > 0`.
+#[pin_project]
+pub struct InspectIO<T, FR, FW> {
+    #[pin]
+    inner: T,
+    on_read: FR,
+    on_write: FW,
+}
+
+impl<T, FR, FW> InspectIO<T, FR, FW> {
+    /// Creates new [`InspectIO`](crate::inspect::InspectIO) with the given
+    /// hooks.
+    pub fn new(inner: T, on_read: FR, on_write: FW) -> Self {
+        InspectIO {
+            inner,
+            on_read,
+            on_write,
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `InspectIO` into the inner I/O object, dropping the
+    /// hooks.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, FW> InspectIO<T, NoopHook, FW> {
+    /// Sets the hook called with every chunk of data read.
+    pub fn inspect_read<F>(self, f: F) -> InspectIO<T, F, FW>
+    where
+        F: FnMut(&[u8]),
+    {
+        InspectIO::new(self.inner, f, self.on_write)
+    }
+}
+
+impl<T, FR> InspectIO<T, FR, NoopHook> {
+    /// Sets the hook called with every chunk of data written.
+    pub fn inspect_write<F>(self, f: F) -> InspectIO<T, FR, F>
+    where
+        F: FnMut(&[u8]),
+    {
+        InspectIO::new(self.inner, self.on_read, f)
+    }
+}
+
+impl<T: fmt::Debug, FR, FW> fmt::Debug for InspectIO<T, FR, FW> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectIO")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+fn inspect_slices<S, H>(slices: &[S], mut n: usize, hook: &mut H)
+where
+    S: Deref<Target = [u8]>,
+    H: InspectHook,
+{
+    for slice in slices {
+        if n == 0 {
+            break;
+        }
+        let len = slice.len().min(n);
+        if len > 0 {
+            hook.inspect(&slice[..len]);
+        }
+        n -= len;
+    }
+}
+
+impl<T, FR, FW> AsyncRead for InspectIO<T, FR, FW>
+where
+    T: AsyncRead,
+    FR: InspectHook,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            if n > 0 {
+                this.on_read.inspect(&buf[..n]);
+            }
+        }
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            inspect_slices(bufs, n, this.on_read);
+        }
+        poll
+    }
+}
+
+impl<T, FR, FW> AsyncWrite for InspectIO<T, FR, FW>
+where
+    T: AsyncWrite,
+    FW: InspectHook,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            if n > 0 {
+                this.on_write.inspect(&buf[..n]);
+            }
+        }
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            inspect_slices(bufs, n, this.on_write);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
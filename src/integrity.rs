@@ -0,0 +1,182 @@
+//! CRC32-checked streams.
+//!
+//! Every write is sent as a frame made of a big-endian `u32` payload length,
+//! the payload, and a big-endian CRC32 of the payload. The length prefix is
+//! what lets the reading side find where the payload ends and the checksum
+//! starts.
+
+use crate::util::{invalid_data, poll_drain, start_drain, FrameReader, MAX_PAYLOAD};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const HEADER_LEN: usize = 4;
+const CRC_LEN: usize = 4;
+const DEFAULT_MAX_FRAME_SIZE: usize = MAX_PAYLOAD;
+
+/// Appends a CRC32 checksum to every write and verifies it on every read.
+///
+/// Reads fail with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+/// if a checksum doesn't match or a frame announces a payload larger than the
+/// maximum frame size, which defaults to 64 KiB; either way the frame is
+/// skipped, so reading can go on with the next one. Writes larger than the
+/// maximum are split into several frames. A frame's payload is only handed
+/// out once the whole frame has arrived and passed the check.
+///
+/// Written frames are buffered until the inner I/O object accepts them, so
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) has to be called to
+/// make sure everything is sent.
+#[pin_project]
+#[derive(Debug)]
+pub struct CrcCheckedIO<T> {
+    #[pin]
+    inner: T,
+    max_frame_size: usize,
+    frames: FrameReader,
+    data: Vec<u8>,
+    data_pos: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<T> CrcCheckedIO<T> {
+    /// Creates new [`CrcCheckedIO`](crate::integrity::CrcCheckedIO).
+    pub fn new(inner: T) -> Self {
+        CrcCheckedIO {
+            inner,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            frames: FrameReader::default(),
+            data: Vec::new(),
+            data_pos: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Sets the maximum payload size of a frame, in both directions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_frame_size` is zero or doesn't fit in a `u32`.
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        assert!(
+            max_frame_size > 0 && max_frame_size <= u32::MAX as usize,
+            "max_frame_size must be in 1..=u32::MAX"
+        );
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Returns the maximum payload size of a frame.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `CrcCheckedIO` into the inner I/O object, dropping any
+    /// partially read frame and any frames not yet written.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for CrcCheckedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        loop {
+            if *this.data_pos < this.data.len() {
+                let available = &this.data[*this.data_pos..];
+                let n = buf.len().min(available.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                *this.data_pos += n;
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let frame = match this.frames.poll_frame(
+                this.inner.as_mut(),
+                cx,
+                HEADER_LEN,
+                *this.max_frame_size + CRC_LEN,
+                |header| {
+                    let mut len = [0u8; HEADER_LEN];
+                    len.copy_from_slice(header);
+                    Ok(u32::from_be_bytes(len) as usize + CRC_LEN)
+                },
+            ) {
+                Poll::Ready(Ok(Some(frame))) => frame,
+                Poll::Ready(Ok(None)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            let data_end = frame.len() - CRC_LEN;
+            let mut crc = [0u8; CRC_LEN];
+            crc.copy_from_slice(&frame[data_end..]);
+            if u32::from_be_bytes(crc) != crc32fast::hash(&frame[HEADER_LEN..data_end]) {
+                return Poll::Ready(Err(invalid_data("frame checksum mismatch")));
+            }
+            this.data.clear();
+            this.data.extend_from_slice(&frame[HEADER_LEN..data_end]);
+            *this.data_pos = 0;
+        }
+    }
+}
+
+impl<T> AsyncWrite for CrcCheckedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let data = &buf[..buf.len().min(*this.max_frame_size)];
+        this.write_buf
+            .extend_from_slice(&(data.len() as u32).to_be_bytes());
+        this.write_buf.extend_from_slice(data);
+        this.write_buf
+            .extend_from_slice(&crc32fast::hash(data).to_be_bytes());
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Periodic heartbeats keeping idle connections alive.
+//!
+//! Idle time is tracked with Tokio timers, so a Tokio runtime has to be
+//! running.
+
+use crate::util::poll_drain;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Instant, Sleep};
+
+/// Writes a heartbeat payload whenever nothing has been written for the
+/// configured interval, and drops heartbeats received from the peer.
+///
+/// The heartbeat goes out from within reads and writes, so the stream has to
+/// be polled, for instance by a task waiting for incoming data, for it to be
+/// sent. A heartbeat that the inner I/O object only partially accepted is
+/// finished before any further write, so it is never interleaved with
+/// regular data.
+///
+/// On the read side, every occurrence of the payload in the incoming data is
+/// removed, so it should be a sequence that regular data never contains.
+/// Bytes that might start a heartbeat are held back until it is clear they
+/// don't, or until EOF.
+#[pin_project]
+#[derive(Debug)]
+pub struct KeepaliveIO<T> {
+    #[pin]
+    inner: T,
+    interval: Duration,
+    payload: Vec<u8>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    heartbeat: Vec<u8>,
+    heartbeat_flush: bool,
+    closed: bool,
+    held: Vec<u8>,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+}
+
+impl<T> KeepaliveIO<T> {
+    /// Creates new [`KeepaliveIO`](crate::keepalive::KeepaliveIO) sending
+    /// `payload` after every `interval` without writes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `payload` is empty.
+    pub fn new(inner: T, interval: Duration, payload: Vec<u8>) -> Self {
+        assert!(!payload.is_empty(), "heartbeat payload must not be empty");
+        KeepaliveIO {
+            inner,
+            interval,
+            payload,
+            sleep: None,
+            heartbeat: Vec::new(),
+            heartbeat_flush: false,
+            closed: false,
+            held: Vec::new(),
+            decoded: Vec::new(),
+            decoded_pos: 0,
+        }
+    }
+
+    /// Returns the heartbeat interval.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the heartbeat payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `KeepaliveIO` into the inner I/O object, dropping any
+    /// partially sent heartbeat and any held back incoming bytes.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Moves `data` into `decoded`, dropping every occurrence of `payload` and
+/// keeping a possible start of one in `held`.
+fn filter(payload: &[u8], held: &mut Vec<u8>, decoded: &mut Vec<u8>, data: &[u8]) {
+    for &byte in data {
+        held.push(byte);
+        while !payload.starts_with(held) {
+            decoded.push(held.remove(0));
+        }
+        if held.len() == payload.len() {
+            held.clear();
+        }
+    }
+}
+
+impl<T> KeepaliveIO<T>
+where
+    T: AsyncWrite,
+{
+    /// Starts a heartbeat if the interval has elapsed and pushes any started
+    /// one to the inner I/O object.
+    fn poll_heartbeat(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if *this.closed {
+            return Poll::Ready(Ok(()));
+        }
+        if this.heartbeat.is_empty() && !*this.heartbeat_flush {
+            let interval = *this.interval;
+            let timer = this.sleep.get_or_insert_with(|| Box::pin(sleep(interval)));
+            if timer.as_mut().poll(cx).is_pending() {
+                return Poll::Ready(Ok(()));
+            }
+            this.heartbeat.extend_from_slice(this.payload);
+            *this.heartbeat_flush = true;
+            timer.as_mut().reset(Instant::now() + interval);
+            // Register for the next deadline.
+            let _ = timer.as_mut().poll(cx);
+        }
+        match poll_drain(this.inner.as_mut(), cx, this.heartbeat) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll,
+        }
+        match this.inner.poll_flush(cx) {
+            Poll::Ready(Ok(())) => {
+                *this.heartbeat_flush = false;
+                Poll::Ready(Ok(()))
+            }
+            poll => poll,
+        }
+    }
+
+    fn restart_timer(self: Pin<&mut Self>) {
+        let this = self.project();
+        if let Some(timer) = this.sleep {
+            timer.as_mut().reset(Instant::now() + *this.interval);
+        }
+    }
+}
+
+impl<T> AsyncRead for KeepaliveIO<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if let Poll::Ready(Err(err)) = self.as_mut().poll_heartbeat(cx) {
+            return Poll::Ready(Err(err));
+        }
+        let mut this = self.project();
+        loop {
+            if *this.decoded_pos < this.decoded.len() {
+                let available = &this.decoded[*this.decoded_pos..];
+                let n = buf.len().min(available.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                *this.decoded_pos += n;
+                if *this.decoded_pos == this.decoded.len() {
+                    this.decoded.clear();
+                    *this.decoded_pos = 0;
+                }
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            match this.inner.as_mut().poll_read(cx, buf) {
+                Poll::Ready(Ok(0)) if this.held.is_empty() => return Poll::Ready(Ok(0)),
+                Poll::Ready(Ok(0)) => this.decoded.append(this.held),
+                Poll::Ready(Ok(n)) => filter(this.payload, this.held, this.decoded, &buf[..n]),
+                poll => return poll,
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for KeepaliveIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        match self.as_mut().poll_heartbeat(cx) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        let poll = self.as_mut().project().inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            if n > 0 {
+                self.restart_timer();
+            }
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.as_mut().poll_heartbeat(cx) {
+            Poll::Ready(Ok(())) => self.project().inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.as_mut().poll_heartbeat(cx) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll,
+        }
+        let this = self.project();
+        *this.closed = true;
+        this.inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,239 @@
+//! Artificial latency for simulating slow networks.
+//!
+//! Delays are Tokio timers, so a Tokio runtime has to be running.
+
+use crate::fault::Rng;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Sleep};
+
+/// Holds back a single operation until its delay has passed.
+///
+/// The timer is started by the first poll of an operation, and is reset once
+/// the operation completes so the next one waits again.
+#[derive(Debug, Default)]
+struct Delay {
+    sleep: Option<Pin<Box<Sleep>>>,
+    elapsed: bool,
+}
+
+impl Delay {
+    fn poll_op<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        latency: impl FnOnce() -> Duration,
+        op: impl FnOnce(&mut Context<'_>) -> Poll<Result<T>>,
+    ) -> Poll<Result<T>> {
+        if !self.elapsed {
+            let sleep = self.sleep.get_or_insert_with(|| Box::pin(sleep(latency())));
+            match sleep.as_mut().poll(cx) {
+                Poll::Ready(()) => {
+                    self.sleep = None;
+                    self.elapsed = true;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let poll = op(cx);
+        if poll.is_ready() {
+            self.elapsed = false;
+        }
+        poll
+    }
+}
+
+/// Delays every read and write by a fixed duration before passing it on.
+///
+/// The delay applies per call rather than per byte, so large and small
+/// transfers wait equally long. Flushes and closes aren't delayed.
+#[pin_project]
+#[derive(Debug)]
+pub struct LatencyIO<T> {
+    #[pin]
+    inner: T,
+    read_latency: Duration,
+    write_latency: Duration,
+    read_delay: Delay,
+    write_delay: Delay,
+}
+
+impl<T> LatencyIO<T> {
+    /// Creates new [`LatencyIO`](crate::latency::LatencyIO).
+    pub fn new(inner: T, read_latency: Duration, write_latency: Duration) -> Self {
+        LatencyIO {
+            inner,
+            read_latency,
+            write_latency,
+            read_delay: Delay::default(),
+            write_delay: Delay::default(),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `LatencyIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for LatencyIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, latency) = (this.inner, *this.read_latency);
+        this.read_delay
+            .poll_op(cx, || latency, |cx| inner.poll_read(cx, buf))
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, latency) = (this.inner, *this.read_latency);
+        this.read_delay
+            .poll_op(cx, || latency, |cx| inner.poll_read_vectored(cx, bufs))
+    }
+}
+
+impl<T> AsyncWrite for LatencyIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, latency) = (this.inner, *this.write_latency);
+        this.write_delay
+            .poll_op(cx, || latency, |cx| inner.poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, latency) = (this.inner, *this.write_latency);
+        this.write_delay
+            .poll_op(cx, || latency, |cx| inner.poll_write_vectored(cx, bufs))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Like [`LatencyIO`](crate::latency::LatencyIO), but the delay of every
+/// read and write is picked uniformly from `min..=max` by a generator seeded
+/// with `rng_seed`.
+#[pin_project]
+#[derive(Debug)]
+pub struct JitteredLatencyIO<T> {
+    #[pin]
+    inner: T,
+    min: Duration,
+    max: Duration,
+    rng: Rng,
+    read_delay: Delay,
+    write_delay: Delay,
+}
+
+impl<T> JitteredLatencyIO<T> {
+    /// Creates new [`JitteredLatencyIO`](crate::latency::JitteredLatencyIO).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn new(inner: T, min: Duration, max: Duration, rng_seed: u64) -> Self {
+        assert!(min <= max, "min latency exceeds max latency");
+        JitteredLatencyIO {
+            inner,
+            min,
+            max,
+            rng: Rng::new(rng_seed),
+            read_delay: Delay::default(),
+            write_delay: Delay::default(),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `JitteredLatencyIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn jitter(rng: &mut Rng, min: Duration, max: Duration) -> Duration {
+    let span = (max - min).as_nanos().min(u64::MAX as u128 - 1) as u64;
+    min + Duration::from_nanos(rng.next_u64() % (span + 1))
+}
+
+impl<T> AsyncRead for JitteredLatencyIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, rng, min, max) = (this.inner, this.rng, *this.min, *this.max);
+        this.read_delay
+            .poll_op(cx, || jitter(rng, min, max), |cx| inner.poll_read(cx, buf))
+    }
+}
+
+impl<T> AsyncWrite for JitteredLatencyIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let (inner, rng, min, max) = (this.inner, this.rng, *this.min, *this.max);
+        this.write_delay
+            .poll_op(cx, || jitter(rng, min, max), |cx| inner.poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,243 @@
+//! Composable middleware stacks.
+//!
+//! An [`IoLayer`](crate::layer::IoLayer) wraps an I/O object into another
+//! one, and a [`LayeredIOBuilder`](crate::layer::LayeredIOBuilder) applies
+//! layers one after another, similar to Tower's `ServiceBuilder`:
+//!
+//! ```
+//! use merge_io::layer::{LayeredIOBuilder, LogLayer};
+//! use merge_io::MergeIO;
+//! use futures::io::Cursor;
+//!
+//! let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+//! let stream = LayeredIOBuilder::new(stream)
+//!     .layer(LogLayer::new(std::io::stderr()))
+//!     .build();
+//! ```
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Wraps an I/O object of type `I` into another I/O object.
+///
+/// The inner type is a type parameter rather than an associated type, so one
+/// layer value can wrap any I/O object it supports and the builder can infer
+/// the types without annotations.
+pub trait IoLayer<I> {
+    /// The wrapped I/O object.
+    type Output: AsyncRead + AsyncWrite;
+
+    /// Wraps `inner`.
+    fn wrap(self, inner: I) -> Self::Output;
+}
+
+/// Applies [`IoLayer`](crate::layer::IoLayer)s to an I/O object.
+///
+/// Each call to [`layer`](crate::layer::LayeredIOBuilder::layer) wraps the
+/// current object, so the first layer added ends up innermost.
+#[derive(Debug)]
+pub struct LayeredIOBuilder<T> {
+    inner: T,
+}
+
+impl<T> LayeredIOBuilder<T> {
+    /// Creates new [`LayeredIOBuilder`](crate::layer::LayeredIOBuilder)
+    /// starting from `inner`.
+    pub fn new(inner: T) -> Self {
+        LayeredIOBuilder { inner }
+    }
+
+    /// Wraps the current I/O object with `layer`.
+    pub fn layer<L>(self, layer: L) -> LayeredIOBuilder<L::Output>
+    where
+        L: IoLayer<T>,
+    {
+        LayeredIOBuilder {
+            inner: layer.wrap(self.inner),
+        }
+    }
+
+    /// Returns the fully wrapped I/O object.
+    pub fn build(self) -> T {
+        self.inner
+    }
+}
+
+/// A layer wrapping I/O objects into [`LogIO`](crate::layer::LogIO).
+#[derive(Debug, Clone)]
+pub struct LogLayer<L> {
+    sink: L,
+}
+
+impl<L> LogLayer<L> {
+    /// Creates new [`LogLayer`](crate::layer::LogLayer) logging to `sink`.
+    pub fn new(sink: L) -> Self {
+        LogLayer { sink }
+    }
+}
+
+impl<I, L> IoLayer<I> for LogLayer<L>
+where
+    I: AsyncRead + AsyncWrite,
+    L: Write,
+{
+    type Output = LogIO<I, L>;
+
+    fn wrap(self, inner: I) -> Self::Output {
+        LogIO::new(inner, self.sink)
+    }
+}
+
+/// Writes a line to `sink` describing the result of every completed
+/// operation on the inner I/O object.
+///
+/// Failures to write to the sink are ignored.
+#[pin_project]
+#[derive(Debug)]
+pub struct LogIO<T, L> {
+    #[pin]
+    inner: T,
+    sink: L,
+}
+
+impl<T, L> LogIO<T, L> {
+    /// Creates new [`LogIO`](crate::layer::LogIO) logging to `sink`.
+    pub fn new(inner: T, sink: L) -> Self {
+        LogIO { inner, sink }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Provides access to the log sink.
+    pub fn sink(&self) -> &L {
+        &self.sink
+    }
+
+    /// Deconstructs `LogIO` into the inner I/O object and the log sink.
+    pub fn into_inner(self) -> (T, L) {
+        (self.inner, self.sink)
+    }
+}
+
+fn log_bytes(sink: &mut impl Write, op: &str, poll: &Poll<Result<usize>>) {
+    let _ = match poll {
+        Poll::Ready(Ok(n)) => writeln!(sink, "{}: {} bytes", op, n),
+        Poll::Ready(Err(err)) => writeln!(sink, "{}: error: {}", op, err),
+        Poll::Pending => return,
+    };
+}
+
+fn log_unit(sink: &mut impl Write, op: &str, poll: &Poll<Result<()>>) {
+    let _ = match poll {
+        Poll::Ready(Ok(())) => writeln!(sink, "{}: ok", op),
+        Poll::Ready(Err(err)) => writeln!(sink, "{}: error: {}", op, err),
+        Poll::Pending => return,
+    };
+}
+
+impl<T, L> AsyncRead for LogIO<T, L>
+where
+    T: AsyncRead,
+    L: Write,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        log_bytes(this.sink, "read", &poll);
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        log_bytes(this.sink, "read", &poll);
+        poll
+    }
+}
+
+impl<T, L> AsyncWrite for LogIO<T, L>
+where
+    T: AsyncWrite,
+    L: Write,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        log_bytes(this.sink, "write", &poll);
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        log_bytes(this.sink, "write", &poll);
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_flush(cx);
+        log_unit(this.sink, "flush", &poll);
+        poll
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        log_unit(this.sink, "close", &poll);
+        poll
+    }
+}
+
+/// A layer wrapping I/O objects into
+/// [`RateLimitedIO`](crate::rate_limit::RateLimitedIO), with the same rate
+/// in both directions.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitLayer {
+    bytes_per_sec: u64,
+}
+
+#[cfg(feature = "tokio")]
+impl RateLimitLayer {
+    /// Creates new [`RateLimitLayer`](crate::layer::RateLimitLayer) limiting
+    /// reads and writes to `bytes_per_sec` each.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        RateLimitLayer { bytes_per_sec }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<I> IoLayer<I> for RateLimitLayer
+where
+    I: AsyncRead + AsyncWrite,
+{
+    type Output = crate::rate_limit::RateLimitedIO<I>;
+
+    fn wrap(self, inner: I) -> Self::Output {
+        crate::rate_limit::RateLimitedIO::new(inner, self.bytes_per_sec, self.bytes_per_sec)
+    }
+}
@@ -36,32 +36,157 @@
 //! # })
 //! # }
 //! ```
+//!
+//! # Layout
+//!
+//! `MergeIO` holds nothing but its two halves, so it is exactly as large as
+//! `R` and `W` together, and a `MergeIO` of two zero-sized halves is a
+//! zero-sized type itself. This is checked at compile time by the test
+//! suite. The wrappers do add state of their own; for instance,
+//! [`counted::CountedMergeIO`] keeps two `u64` counters next to the
+//! `MergeIO` it wraps.
+//!
+//! # Cargo features
+//!
+//! - `tokio` — implements [`tokio::io::AsyncRead`] and
+//!   [`tokio::io::AsyncWrite`] for [`MergeIO`] when the halves implement them,
+//!   and enables the wrappers that need a timer, such as
+//!   [`timeout::ReadTimeoutIO`], as well as
+//!   [`MergeIO::from_tcp_stream`].
+//! - `compat` — enables [`MergeIO::into_compat`] for use with code written
+//!   against futures 0.1 and `tokio-io` 0.1.
+//! - `crc32` — enables the [`integrity`] module with CRC32-checked
+//!   streams.
+//! - `deflate` — enables the [`compression`] module with zlib compression
+//!   wrappers for either direction.
+//! - `lz4` — enables the [`lz4`] module with transparent LZ4 compression.
+//! - `metrics` — enables the [`metered`] module reporting traffic to a
+//!   `metrics` recorder.
+//! - `serde` — enables the [`serde_frame`] module with typed messages over
+//!   frames; `serde_json` and `ciborium` add its JSON and CBOR codecs.
+//! - `test-utils` — enables the [`test_utils`] module with scripted mock
+//!   readers and writers.
+//! - `tracing` — enables the [`traced`] module emitting `tracing` events
+//!   for every poll, and [`MergeIO::traced`].
+//!
+//! # `no_std`
+//!
+//! The crate requires `std`. The [`AsyncRead`](futures::io::AsyncRead) and
+//! [`AsyncWrite`](futures::io::AsyncWrite) traits are defined by `futures-io`
+//! in terms of [`std::io::Error`] and are only available with its `std`
+//! feature, and `futures-core` offers no `no_std` replacement for them.
 
 #![warn(missing_debug_implementations, rust_2018_idioms, missing_docs)]
 
-use futures_io::{AsyncRead, AsyncWrite};
-use std::io::{IoSlice, IoSliceMut, Result};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite};
+use pin_project::pin_project;
+use std::future::poll_fn;
+use std::io::{IoSlice, IoSliceMut, Result, SeekFrom};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+pub mod abort;
+#[cfg(feature = "tokio")]
+pub mod async_drop;
+pub mod base64;
+pub mod bounded;
+pub mod buffered;
+pub mod capacity;
+pub mod chain;
+pub mod chunked;
+pub mod circuit_breaker;
+#[cfg(feature = "deflate")]
+pub mod compression;
+pub mod conditional;
+pub mod counted;
+pub mod deferred;
+pub mod duplex;
+pub mod fault;
+pub mod flow_control;
+pub mod framing;
+pub mod hexdump;
+pub mod inspect;
+#[cfg(feature = "crc32")]
+pub mod integrity;
+#[cfg(feature = "tokio")]
+pub mod keepalive;
+#[cfg(feature = "tokio")]
+pub mod latency;
+pub mod layer;
+pub mod line;
+#[cfg(feature = "lz4")]
+pub mod lz4;
+pub mod map_err;
+#[cfg(feature = "metrics")]
+pub mod metered;
+pub mod mux;
+pub mod null;
+pub mod peek;
+pub mod pool;
+pub mod proxy;
+#[cfg(feature = "tokio")]
+pub mod rate_limit;
+pub mod ready;
+#[cfg(feature = "tokio")]
+pub mod reconnect;
+pub mod retry;
+pub mod select;
+pub mod sequenced;
+#[cfg(feature = "serde")]
+pub mod serde_frame;
+pub mod shared;
+pub mod shutdown;
+pub mod split;
+pub mod spy;
+pub mod stats;
+pub mod tagged;
+pub mod tee;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "tokio")]
+pub mod timeout;
+#[cfg(feature = "tracing")]
+pub mod traced;
+pub mod unread;
+pub mod xor;
+
+mod util;
+
+pub use crate::duplex::duplex;
+pub use crate::proxy::{bridge, copy_bidirectional};
+
+use bounded::{BoundedReadIO, BoundedWriteIO};
+use futures_util::io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
+use inspect::{InspectIO, NoopHook};
+use line::LineReaderIO;
+use map_err::{MappedReadErrIO, MappedWriteErrIO};
+#[cfg(feature = "tokio")]
+use rate_limit::{BudgetedIO, IoBudget};
+use split::{PinnedReadHalf, PinnedWriteHalf, ReadHalf, WriteHalf};
+use stats::StatsIO;
+#[cfg(feature = "tokio")]
+use timeout::{ReadTimeoutIO, WriteTimeoutIO};
+#[cfg(feature = "tracing")]
+use traced::TracedIO;
+
 /// Merged I/O, delegates reads and writes to the provided
 /// [`AsyncRead`](futures::io::AsyncRead) (`R`) and
 /// [`AsyncWrite`](futures::io::AsyncWrite) (`W`).
-#[derive(Debug)]
-pub struct MergeIO<R, W>
-where
-    R: AsyncRead + Unpin,
-    W: AsyncWrite + Unpin,
-{
+///
+/// Neither `R` nor `W` is required to be [`Unpin`]: the halves are
+/// pin-projected, so `MergeIO` is [`Unpin`] exactly when both halves are.
+/// The same goes for [`Send`] and [`Sync`], so merging the owned halves of a
+/// TCP stream yields a value that can be moved to another thread.
+#[pin_project]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MergeIO<R, W> {
+    #[pin]
     reader: R,
+    #[pin]
     writer: W,
 }
 
-impl<R, W> MergeIO<R, W>
-where
-    R: AsyncRead + Unpin,
-    W: AsyncWrite + Unpin,
-{
+impl<R, W> MergeIO<R, W> {
     /// Creates new [`MergeIO`](crate::MergeIO), that reads to `reader` and
     /// writes to `writer`.
     pub fn new(reader: R, writer: W) -> Self {
@@ -88,23 +213,506 @@ where
         &mut self.writer
     }
 
+    /// Provides pinned `mut` access to `reader`.
+    pub fn reader_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().reader
+    }
+
+    /// Provides pinned `mut` access to `writer`.
+    pub fn writer_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer
+    }
+
     /// Deconstructs `MergeIO` into the `reader` and `writer`.
     pub fn into_inner(self) -> (R, W) {
         (self.reader, self.writer)
     }
+
+    /// Deconstructs `MergeIO` into the `reader` and `writer`, unless
+    /// `writer` still holds data that hasn't been written out, in which
+    /// case `self` is handed back with a
+    /// [`WriteZero`](std::io::ErrorKind::WriteZero) error.
+    ///
+    /// `writer` has to implement [`HasPendingBytes`](crate::HasPendingBytes);
+    /// writers that don't buffer anything can do so with an empty `impl`.
+    pub fn try_into_inner(self) -> std::result::Result<(R, W), (Self, std::io::Error)>
+    where
+        W: HasPendingBytes,
+    {
+        match self.writer.pending_bytes() {
+            0 => Ok(self.into_inner()),
+            n => Err((
+                self,
+                std::io::Error::new(
+                    std::io::ErrorKind::WriteZero,
+                    format!("writer has {} unflushed bytes", n),
+                ),
+            )),
+        }
+    }
+
+    /// Moves `MergeIO` to the heap and pins it there.
+    pub fn into_pinned(self) -> Pin<Box<Self>> {
+        Box::pin(self)
+    }
+
+    /// Moves `MergeIO` to the heap, erasing the types of the halves, so
+    /// streams built from different halves can be stored together.
+    pub fn into_boxed_dyn(self) -> Pin<Box<dyn AsyncReadWrite + Send + Unpin + 'static>>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Box::pin(self)
+    }
+
+    /// Applies `f` to `reader`, keeping `writer` as is.
+    pub fn map_reader<R2, F>(self, f: F) -> MergeIO<R2, W>
+    where
+        F: FnOnce(R) -> R2,
+    {
+        MergeIO::new(f(self.reader), self.writer)
+    }
+
+    /// Applies `f` to `writer`, keeping `reader` as is.
+    pub fn map_writer<W2, F>(self, f: F) -> MergeIO<R, W2>
+    where
+        F: FnOnce(W) -> W2,
+    {
+        MergeIO::new(self.reader, f(self.writer))
+    }
+
+    /// Replaces `reader` with `new_reader`, dropping the old one.
+    pub fn with_reader<R2>(self, new_reader: R2) -> MergeIO<R2, W> {
+        MergeIO::new(new_reader, self.writer)
+    }
+
+    /// Replaces `writer` with `new_writer`, dropping the old one.
+    pub fn with_writer<W2>(self, new_writer: W2) -> MergeIO<R, W2> {
+        MergeIO::new(self.reader, new_writer)
+    }
+
+    /// Exchanges the roles of the halves, reading from the former `writer`
+    /// and writing to the former `reader`.
+    ///
+    /// The bounds make sure the result is usable as a stream, so halves
+    /// passed to [`new`](crate::MergeIO::new) in the wrong order are caught
+    /// where they're swapped.
+    pub fn swap(self) -> MergeIO<W, R>
+    where
+        W: AsyncRead + Unpin,
+        R: AsyncWrite + Unpin,
+    {
+        MergeIO::new(self.writer, self.reader)
+    }
+
+    /// Splits `MergeIO` into independently owned
+    /// [`ReadHalf`](crate::split::ReadHalf) and
+    /// [`WriteHalf`](crate::split::WriteHalf).
+    ///
+    /// The halves can be put back together with
+    /// [`ReadHalf::reunite`](crate::split::ReadHalf::reunite), which checks
+    /// that they belong together.
+    pub fn into_split(self) -> (ReadHalf<R>, WriteHalf<W>) {
+        let id = split::next_split_id();
+        (
+            ReadHalf::new(self.reader, id),
+            WriteHalf::new(self.writer, id),
+        )
+    }
+
+    /// Splits a pinned `MergeIO` into a
+    /// [`PinnedReadHalf`](crate::split::PinnedReadHalf) and a
+    /// [`PinnedWriteHalf`](crate::split::PinnedWriteHalf) borrowing its
+    /// halves.
+    ///
+    /// Unlike [`into_split`](crate::MergeIO::into_split), this works for
+    /// halves that aren't [`Unpin`], at the cost of the split halves being
+    /// tied to the borrow, like with
+    /// [`tokio::io::split`](https://docs.rs/tokio/1/tokio/io/fn.split.html).
+    pub fn pin_split(self: Pin<&mut Self>) -> (PinnedReadHalf<'_, R>, PinnedWriteHalf<'_, W>) {
+        let this = self.project();
+        (
+            PinnedReadHalf::new(this.reader),
+            PinnedWriteHalf::new(this.writer),
+        )
+    }
+
+    /// Polls `reader` for readiness with a zero-length read, so a buffer
+    /// only has to be allocated once data is available.
+    ///
+    /// This is only as precise as `reader`'s handling of empty reads: many
+    /// readers return `Ok(0)` for them right away, which is reported as
+    /// ready.
+    pub fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>>
+    where
+        R: AsyncRead,
+    {
+        ready::probe_read(self.project().reader, cx)
+    }
+
+    /// Polls `writer` for readiness with a zero-length write.
+    ///
+    /// This is only as precise as `writer`'s handling of empty writes: many
+    /// writers return `Ok(0)` for them right away, which is reported as
+    /// ready.
+    pub fn poll_write_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>>
+    where
+        W: AsyncWrite,
+    {
+        ready::probe_write(self.project().writer, cx)
+    }
+
+    /// Seeks only `reader`, leaving `writer` at its current position.
+    pub async fn seek_reader(&mut self, pos: SeekFrom) -> Result<u64>
+    where
+        R: AsyncSeek + Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut self.reader).poll_seek(cx, pos)).await
+    }
+
+    /// Seeks only `writer`, leaving `reader` at its current position.
+    pub async fn seek_writer(&mut self, pos: SeekFrom) -> Result<u64>
+    where
+        W: AsyncSeek + Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut self.writer).poll_seek(cx, pos)).await
+    }
+
+    /// Reads exactly `buf.len()` bytes from `reader`, retrying partial
+    /// reads, like [`read_exact`](futures::io::AsyncReadExt::read_exact).
+    pub async fn read_exact_into(&mut self, buf: &mut [u8]) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        AsyncReadExt::read_exact(&mut self.reader, buf).await
+    }
+
+    /// Writes all of `buf` to `writer`, retrying partial writes, like
+    /// [`write_all`](futures::io::AsyncWriteExt::write_all).
+    pub async fn write_all_from(&mut self, buf: &[u8]) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        AsyncWriteExt::write_all(&mut self.writer, buf).await
+    }
+
+    /// Copies everything from `reader` into `dest` until EOF, returning the
+    /// number of bytes copied.
+    pub async fn pipe_into<W2>(&mut self, dest: &mut W2) -> Result<u64>
+    where
+        R: AsyncRead + Unpin,
+        W2: AsyncWrite + Unpin,
+    {
+        futures_util::io::copy(&mut self.reader, dest).await
+    }
+
+    /// Copies everything from `src` into `writer` until EOF, returning the
+    /// number of bytes copied.
+    pub async fn pipe_from<R2>(&mut self, src: &mut R2) -> Result<u64>
+    where
+        R2: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        futures_util::io::copy(src, &mut self.writer).await
+    }
+
+    /// Reconstructs `MergeIO` from the halves returned by
+    /// [`into_split`](crate::MergeIO::into_split).
+    ///
+    /// The halves don't have to come from the same split; use
+    /// [`ReadHalf::reunite`](crate::split::ReadHalf::reunite) to make sure
+    /// they do.
+    pub fn unsplit(read: ReadHalf<R>, write: WriteHalf<W>) -> Self {
+        MergeIO::new(read.into_inner(), write.into_inner())
+    }
+
+    /// Wraps `MergeIO` into a [`StatsIO`](crate::stats::StatsIO) that
+    /// collects I/O metrics.
+    pub fn with_stats(self) -> StatsIO<Self> {
+        StatsIO::new(self)
+    }
+
+    /// Wraps `MergeIO` into a [`TracedIO`](crate::traced::TracedIO) that
+    /// emits `tracing` events under `name`.
+    #[cfg(feature = "tracing")]
+    pub fn traced(self, name: &'static str) -> TracedIO<Self> {
+        TracedIO::new(self, name)
+    }
+
+    /// Applies `f` to every error returned by the reading half.
+    pub fn map_read_err<F>(self, f: F) -> MappedReadErrIO<R, W, F>
+    where
+        F: Fn(std::io::Error) -> std::io::Error,
+    {
+        MappedReadErrIO::new(self, f)
+    }
+
+    /// Applies `f` to every error returned by the writing half, including
+    /// flush and close errors.
+    pub fn map_write_err<F>(self, f: F) -> MappedWriteErrIO<R, W, F>
+    where
+        F: Fn(std::io::Error) -> std::io::Error,
+    {
+        MappedWriteErrIO::new(self, f)
+    }
+
+    /// Calls `f` with every chunk of data read.
+    pub fn inspect_read<F>(self, f: F) -> InspectIO<Self, F, NoopHook>
+    where
+        F: FnMut(&[u8]),
+    {
+        InspectIO::new(self, f, NoopHook)
+    }
+
+    /// Calls `f` with every chunk of data written.
+    pub fn inspect_write<F>(self, f: F) -> InspectIO<Self, NoopHook, F>
+    where
+        F: FnMut(&[u8]),
+    {
+        InspectIO::new(self, NoopHook, f)
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`BoundedReadIO`](crate::bounded::BoundedReadIO) that reports EOF
+    /// after `n` bytes have been read.
+    pub fn limit_read(self, n: u64) -> BoundedReadIO<Self> {
+        BoundedReadIO::new(self, n)
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`BoundedWriteIO`](crate::bounded::BoundedWriteIO) that fails writes
+    /// after `n` bytes have been written.
+    pub fn limit_write(self, n: u64) -> BoundedWriteIO<Self> {
+        BoundedWriteIO::new(self, n)
+    }
+
+    /// Wraps `reader` into a [`BufReader`](futures::io::BufReader) with the
+    /// given `capacity`.
+    pub fn buf_read(self, capacity: usize) -> MergeIO<BufReader<R>, W>
+    where
+        R: AsyncRead,
+    {
+        MergeIO::new(BufReader::with_capacity(capacity, self.reader), self.writer)
+    }
+
+    /// Wraps `writer` into a [`BufWriter`](futures::io::BufWriter) with the
+    /// given `capacity`.
+    pub fn buf_write(self, capacity: usize) -> MergeIO<R, BufWriter<W>>
+    where
+        W: AsyncWrite,
+    {
+        MergeIO::new(self.reader, BufWriter::with_capacity(capacity, self.writer))
+    }
+
+    /// Wraps `reader` into a [`BufReader`](futures::io::BufReader) and
+    /// returns a [`LineReaderIO`](crate::line::LineReaderIO) over the result.
+    pub fn with_line_reader(self) -> LineReaderIO<R, W>
+    where
+        R: AsyncRead,
+    {
+        LineReaderIO::new(MergeIO::new(BufReader::new(self.reader), self.writer))
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`Compat`](futures_util::compat::Compat) implementing the futures 0.1
+    /// and `tokio-io` 0.1 I/O traits.
+    #[cfg(feature = "compat")]
+    pub fn into_compat(self) -> futures_util::compat::Compat<Self> {
+        futures_util::compat::Compat::new(self)
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`BudgetedIO`](crate::rate_limit::BudgetedIO) drawing from the shared
+    /// `budget`.
+    #[cfg(feature = "tokio")]
+    pub fn with_budget(self, budget: std::sync::Arc<IoBudget>) -> BudgetedIO<Self> {
+        BudgetedIO::new(self, budget)
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`ReadTimeoutIO`](crate::timeout::ReadTimeoutIO) that fails reads
+    /// pending for longer than `timeout`.
+    #[cfg(feature = "tokio")]
+    pub fn read_timeout(self, timeout: std::time::Duration) -> ReadTimeoutIO<Self> {
+        ReadTimeoutIO::new(self, timeout)
+    }
+
+    /// Wraps `MergeIO` into a
+    /// [`WriteTimeoutIO`](crate::timeout::WriteTimeoutIO) that fails writes
+    /// pending for longer than `timeout`.
+    #[cfg(feature = "tokio")]
+    pub fn write_timeout(self, timeout: std::time::Duration) -> WriteTimeoutIO<Self> {
+        WriteTimeoutIO::new(self, timeout)
+    }
 }
 
-impl<R, W> AsyncRead for MergeIO<R, W>
+/// Type-erased [`MergeIO`](crate::MergeIO), useful when the concrete halves
+/// are only known at runtime.
+pub type DynMergeIO<'a> =
+    MergeIO<Box<dyn AsyncRead + Unpin + Send + 'a>, Box<dyn AsyncWrite + Unpin + Send + 'a>>;
+
+impl<'a> DynMergeIO<'a> {
+    /// Creates new [`DynMergeIO`](crate::DynMergeIO) by boxing `reader` and
+    /// `writer`.
+    pub fn boxed(
+        reader: impl AsyncRead + Unpin + Send + 'a,
+        writer: impl AsyncWrite + Unpin + Send + 'a,
+    ) -> Self {
+        MergeIO::new(Box::new(reader), Box::new(writer))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl MergeIO<tokio::net::tcp::OwnedReadHalf, tokio::net::tcp::OwnedWriteHalf> {
+    /// Creates new [`MergeIO`](crate::MergeIO) from the owned halves of
+    /// `stream`, see [`TcpStream::into_split`](tokio::net::TcpStream::into_split).
+    pub fn from_tcp_stream(stream: tokio::net::TcpStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        MergeIO::new(reader, writer)
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl MergeIO<tokio::net::unix::OwnedReadHalf, tokio::net::unix::OwnedWriteHalf> {
+    /// Creates new [`MergeIO`](crate::MergeIO) from the owned halves of
+    /// `stream`, see [`UnixStream::into_split`](tokio::net::UnixStream::into_split).
+    pub fn from_unix_stream(stream: tokio::net::UnixStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        MergeIO::new(reader, writer)
+    }
+}
+
+/// Combines [`AsyncRead`](futures::io::AsyncRead) and
+/// [`AsyncWrite`](futures::io::AsyncWrite), so both can be used through a
+/// single trait object, see
+/// [`MergeIO::into_boxed_dyn`](crate::MergeIO::into_boxed_dyn).
+///
+/// Implemented for everything implementing both.
+pub trait AsyncReadWrite: AsyncRead + AsyncWrite {}
+
+impl<T> AsyncReadWrite for T where T: AsyncRead + AsyncWrite + ?Sized {}
+
+/// Writers that can report how much data they hold without having written
+/// it out, checked by
+/// [`MergeIO::try_into_inner`](crate::MergeIO::try_into_inner).
+///
+/// Writers without a buffer of their own report zero, which is what the
+/// default implementation does, so for them an empty `impl` is enough.
+pub trait HasPendingBytes {
+    /// Returns the number of bytes accepted but not yet written out.
+    fn pending_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl<W> HasPendingBytes for BufWriter<W> {
+    fn pending_bytes(&self) -> usize {
+        self.buffer().len()
+    }
+}
+
+impl HasPendingBytes for Vec<u8> {}
+
+impl<T> HasPendingBytes for futures_util::io::Cursor<T> {}
+
+impl HasPendingBytes for futures_util::io::Sink {}
+
+impl<W> HasPendingBytes for &mut W
+where
+    W: HasPendingBytes + ?Sized,
+{
+    fn pending_bytes(&self) -> usize {
+        (**self).pending_bytes()
+    }
+}
+
+impl<W> HasPendingBytes for Box<W>
+where
+    W: HasPendingBytes + ?Sized,
+{
+    fn pending_bytes(&self) -> usize {
+        (**self).pending_bytes()
+    }
+}
+
+/// Conversion into [`MergeIO`](crate::MergeIO).
+///
+/// Accept `impl IntoMergeIO` to take anything that can be turned into a
+/// merged stream: a `(reader, writer)` tuple, a `MergeIO` itself, or, with
+/// the `tokio` feature, a [`TcpStream`](tokio::net::TcpStream), whose halves
+/// are adapted to the `futures` I/O traits.
+pub trait IntoMergeIO {
+    /// The reading half.
+    type Reader: AsyncRead + Unpin;
+    /// The writing half.
+    type Writer: AsyncWrite + Unpin;
+
+    /// Converts `self` into [`MergeIO`](crate::MergeIO).
+    fn into_merge_io(self) -> MergeIO<Self::Reader, Self::Writer>;
+}
+
+impl<R, W> IntoMergeIO for MergeIO<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    type Reader = R;
+    type Writer = W;
+
+    fn into_merge_io(self) -> MergeIO<R, W> {
+        self
+    }
+}
+
+impl<R, W> IntoMergeIO for (R, W)
 where
     R: AsyncRead + Unpin,
     W: AsyncWrite + Unpin,
+{
+    type Reader = R;
+    type Writer = W;
+
+    fn into_merge_io(self) -> MergeIO<R, W> {
+        MergeIO::from(self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl IntoMergeIO for tokio::net::TcpStream {
+    type Reader = tokio_util::compat::Compat<tokio::net::tcp::OwnedReadHalf>;
+    type Writer = tokio_util::compat::Compat<tokio::net::tcp::OwnedWriteHalf>;
+
+    fn into_merge_io(self) -> MergeIO<Self::Reader, Self::Writer> {
+        use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+        let (reader, writer) = self.into_split();
+        MergeIO::new(reader.compat(), writer.compat_write())
+    }
+}
+
+impl<R, W> From<(R, W)> for MergeIO<R, W> {
+    fn from((reader, writer): (R, W)) -> Self {
+        MergeIO::new(reader, writer)
+    }
+}
+
+impl<R, W> From<MergeIO<R, W>> for (R, W) {
+    fn from(merged: MergeIO<R, W>) -> Self {
+        merged.into_inner()
+    }
+}
+
+impl<R, W> AsyncRead for MergeIO<R, W>
+where
+    R: AsyncRead,
 {
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<Result<usize>> {
-        AsyncRead::poll_read(Pin::new(&mut self.get_mut().reader), cx, buf)
+        self.project().reader.poll_read(cx, buf)
     }
 
     fn poll_read_vectored(
@@ -112,17 +720,60 @@ where
         cx: &mut Context<'_>,
         bufs: &mut [IoSliceMut<'_>],
     ) -> Poll<Result<usize>> {
-        AsyncRead::poll_read_vectored(Pin::new(&mut self.get_mut().reader), cx, bufs)
+        self.project().reader.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<R, W> AsyncBufRead for MergeIO<R, W>
+where
+    R: AsyncBufRead,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        self.project().reader.poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.project().reader.consume(amt)
+    }
+}
+
+/// Seeks both `reader` and `writer`, in that order, and reports the position
+/// of `reader`.
+///
+/// The same [`SeekFrom`](std::io::SeekFrom) is applied to both halves, so
+/// relative seeks move them by the same amount rather than to the same
+/// position. The implementation keeps no state of its own: if `writer`
+/// returns `Pending` after `reader` has been seeked, the next poll seeks
+/// `reader` again, so a relative seek can move `reader` more than once. Use
+/// [`SeekFrom::Start`](std::io::SeekFrom) or halves that seek without
+/// blocking when that matters. Use
+/// [`seek_reader`](crate::MergeIO::seek_reader) and
+/// [`seek_writer`](crate::MergeIO::seek_writer) to seek only one of the
+/// halves.
+impl<R, W> AsyncSeek for MergeIO<R, W>
+where
+    R: AsyncSeek,
+    W: AsyncSeek,
+{
+    fn poll_seek(self: Pin<&mut Self>, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let this = self.project();
+        let position = match this.reader.poll_seek(cx, pos) {
+            Poll::Ready(Ok(position)) => position,
+            poll => return poll,
+        };
+        match this.writer.poll_seek(cx, pos) {
+            Poll::Ready(Ok(_)) => Poll::Ready(Ok(position)),
+            poll => poll,
+        }
     }
 }
 
 impl<R, W> AsyncWrite for MergeIO<R, W>
 where
-    R: AsyncRead + Unpin,
-    W: AsyncWrite + Unpin,
+    W: AsyncWrite,
 {
     fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
-        AsyncWrite::poll_write(Pin::new(&mut self.get_mut().writer), cx, buf)
+        self.project().writer.poll_write(cx, buf)
     }
 
     fn poll_write_vectored(
@@ -130,14 +781,155 @@ where
         cx: &mut Context<'_>,
         bufs: &[IoSlice<'_>],
     ) -> Poll<Result<usize>> {
-        AsyncWrite::poll_write_vectored(Pin::new(&mut self.get_mut().writer), cx, bufs)
+        self.project().writer.poll_write_vectored(cx, bufs)
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        AsyncWrite::poll_flush(Pin::new(&mut self.get_mut().writer), cx)
+        self.project().writer.poll_flush(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        AsyncWrite::poll_close(Pin::new(&mut self.get_mut().writer), cx)
+        self.project().writer.poll_close(cx)
+    }
+}
+
+/// Yields the items of `reader`, for when it is a message stream rather than
+/// a byte stream.
+impl<R, W> futures_core::Stream for MergeIO<R, W>
+where
+    R: futures_core::Stream,
+{
+    type Item = R::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().reader.poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.reader.size_hint()
+    }
+}
+
+/// Forwards items to `writer`, for when it is a message sink rather than a
+/// byte stream.
+impl<R, W, Item> futures_sink::Sink<Item> for MergeIO<R, W>
+where
+    W: futures_sink::Sink<Item>,
+{
+    type Error = W::Error;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::result::Result<(), Self::Error>> {
+        self.project().writer.poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Item) -> std::result::Result<(), Self::Error> {
+        self.project().writer.start_send(item)
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::result::Result<(), Self::Error>> {
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::result::Result<(), Self::Error>> {
+        self.project().writer.poll_close(cx)
+    }
+}
+
+impl<R, W> std::io::Read for MergeIO<R, W>
+where
+    R: std::io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.reader.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize> {
+        self.reader.read_vectored(bufs)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        self.reader.read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> Result<usize> {
+        self.reader.read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.reader.read_exact(buf)
+    }
+}
+
+impl<R, W> std::io::Write for MergeIO<R, W>
+where
+    W: std::io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize> {
+        self.writer.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.writer.write_all(buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R, W> tokio::io::AsyncRead for MergeIO<R, W>
+where
+    R: tokio::io::AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        self.project().reader.poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R, W> tokio::io::AsyncWrite for MergeIO<R, W>
+where
+    W: tokio::io::AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().writer.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().writer.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.writer.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_shutdown(cx)
     }
 }
@@ -0,0 +1,129 @@
+//! Line-oriented reading without giving up the writing half.
+
+use crate::MergeIO;
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use futures_util::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reads lines from a buffered reading half while still allowing writes.
+///
+/// All reads, including the raw [`AsyncRead`](futures::io::AsyncRead) ones,
+/// go through the [`BufReader`](futures::io::BufReader), so no buffered data
+/// is lost when mixing [`read_line`](crate::line::LineReaderIO::read_line)
+/// with other reads.
+#[pin_project]
+#[derive(Debug)]
+pub struct LineReaderIO<R, W> {
+    #[pin]
+    inner: MergeIO<BufReader<R>, W>,
+}
+
+impl<R, W> LineReaderIO<R, W> {
+    /// Creates new [`LineReaderIO`](crate::line::LineReaderIO).
+    pub fn new(inner: MergeIO<BufReader<R>, W>) -> Self {
+        LineReaderIO { inner }
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<BufReader<R>, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<BufReader<R>, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `LineReaderIO` into the inner [`MergeIO`](crate::MergeIO).
+    pub fn into_inner(self) -> MergeIO<BufReader<R>, W> {
+        self.inner
+    }
+}
+
+impl<R, W> LineReaderIO<R, W>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Reads the next line, including the trailing newline if there is one.
+    ///
+    /// Returns an empty string at EOF. See
+    /// [`AsyncBufReadExt::read_line`](futures::io::AsyncBufReadExt::read_line)
+    /// for the details.
+    pub async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        self.inner.reader_mut().read_line(&mut line).await?;
+        Ok(line)
+    }
+}
+
+impl<R, W> LineReaderIO<R, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Writes all of `data` to the writing half.
+    pub async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.inner.writer_mut().write_all(data).await
+    }
+}
+
+impl<R, W> AsyncRead for LineReaderIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<R, W> AsyncBufRead for LineReaderIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        self.project().inner.poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.project().inner.consume(amt)
+    }
+}
+
+impl<R, W> AsyncWrite for LineReaderIO<R, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
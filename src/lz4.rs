@@ -0,0 +1,169 @@
+//! Transparent LZ4 compression.
+//!
+//! Every write is sent as a frame made of a big-endian `u32` length followed
+//! by an LZ4 block with its uncompressed size prepended, as produced by
+//! [`lz4_flex::compress_prepend_size`].
+
+use crate::util::{invalid_data, poll_drain, start_drain, FrameReader};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const HEADER_LEN: usize = 4;
+const SIZE_LEN: usize = 4;
+/// The largest amount of uncompressed data put into a single frame.
+const MAX_CHUNK: usize = 64 * 1024;
+
+fn max_frame_len() -> usize {
+    SIZE_LEN + lz4_flex::block::get_maximum_output_size(MAX_CHUNK)
+}
+
+/// Compresses writes and decompresses reads with LZ4.
+///
+/// Writes are split into chunks of up to 64 KiB, each compressed into its own
+/// frame. Frames are buffered until the inner I/O object accepts them, so
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) has to be called to
+/// make sure everything is sent. Reads fail with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) on corrupt
+/// input.
+#[pin_project]
+#[derive(Debug)]
+pub struct LZ4IO<T> {
+    #[pin]
+    inner: T,
+    frames: FrameReader,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<T> LZ4IO<T> {
+    /// Creates new [`LZ4IO`](crate::lz4::LZ4IO).
+    pub fn new(inner: T) -> Self {
+        LZ4IO {
+            inner,
+            frames: FrameReader::default(),
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `LZ4IO` into the inner I/O object, dropping any
+    /// partially read frame and any frames not yet written.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn decode(frame: &[u8]) -> Result<Vec<u8>> {
+    if frame.len() < SIZE_LEN {
+        return Err(invalid_data("LZ4 frame too short"));
+    }
+    let mut size = [0u8; SIZE_LEN];
+    size.copy_from_slice(&frame[..SIZE_LEN]);
+    let size = u32::from_le_bytes(size) as usize;
+    if size > MAX_CHUNK {
+        return Err(invalid_data("LZ4 frame too large"));
+    }
+    lz4_flex::decompress(&frame[SIZE_LEN..], size).map_err(invalid_data)
+}
+
+impl<T> AsyncRead for LZ4IO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        loop {
+            if *this.decoded_pos < this.decoded.len() {
+                let available = &this.decoded[*this.decoded_pos..];
+                let n = buf.len().min(available.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                *this.decoded_pos += n;
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let frame = match this.frames.poll_frame(
+                this.inner.as_mut(),
+                cx,
+                HEADER_LEN,
+                max_frame_len(),
+                |header| {
+                    let mut len = [0u8; HEADER_LEN];
+                    len.copy_from_slice(header);
+                    Ok(u32::from_be_bytes(len) as usize)
+                },
+            ) {
+                Poll::Ready(Ok(Some(frame))) => frame,
+                Poll::Ready(Ok(None)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            *this.decoded = match decode(&frame[HEADER_LEN..]) {
+                Ok(decoded) => decoded,
+                Err(err) => return Poll::Ready(Err(err)),
+            };
+            *this.decoded_pos = 0;
+        }
+    }
+}
+
+impl<T> AsyncWrite for LZ4IO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let data = &buf[..buf.len().min(MAX_CHUNK)];
+        let compressed = lz4_flex::compress_prepend_size(data);
+        this.write_buf
+            .extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        this.write_buf.extend_from_slice(&compressed);
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_close(cx),
+            poll => poll,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Error-mapping combinators for [`MergeIO`](crate::MergeIO).
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::fmt;
+use std::io::{Error, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Applies `f` to the errors of the reading half of a
+/// [`MergeIO`](crate::MergeIO), created by
+/// [`MergeIO::map_read_err`](crate::MergeIO::map_read_err).
+#[pin_project]
+pub struct MappedReadErrIO<R, W, F> {
+    #[pin]
+    inner: MergeIO<R, W>,
+    f: F,
+}
+
+/// Applies `f` to the errors of the writing half of a
+/// [`MergeIO`](crate::MergeIO), created by
+/// [`MergeIO::map_write_err`](crate::MergeIO::map_write_err).
+#[pin_project]
+pub struct MappedWriteErrIO<R, W, F> {
+    #[pin]
+    inner: MergeIO<R, W>,
+    f: F,
+}
+
+fn map_poll<T, F>(poll: Poll<Result<T>>, f: &F) -> Poll<Result<T>>
+where
+    F: Fn(Error) -> Error,
+{
+    match poll {
+        Poll::Ready(Err(err)) => Poll::Ready(Err(f(err))),
+        poll => poll,
+    }
+}
+
+impl<R, W, F> MappedReadErrIO<R, W, F> {
+    pub(crate) fn new(inner: MergeIO<R, W>, f: F) -> Self {
+        MappedReadErrIO { inner, f }
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `MappedReadErrIO` into the inner
+    /// [`MergeIO`](crate::MergeIO), dropping the mapping function.
+    pub fn into_inner(self) -> MergeIO<R, W> {
+        self.inner
+    }
+}
+
+impl<R, W, F> MappedWriteErrIO<R, W, F> {
+    pub(crate) fn new(inner: MergeIO<R, W>, f: F) -> Self {
+        MappedWriteErrIO { inner, f }
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `MappedWriteErrIO` into the inner
+    /// [`MergeIO`](crate::MergeIO), dropping the mapping function.
+    pub fn into_inner(self) -> MergeIO<R, W> {
+        self.inner
+    }
+}
+
+impl<R: fmt::Debug, W: fmt::Debug, F> fmt::Debug for MappedReadErrIO<R, W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedReadErrIO")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: fmt::Debug, W: fmt::Debug, F> fmt::Debug for MappedWriteErrIO<R, W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedWriteErrIO")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R, W, F> AsyncRead for MappedReadErrIO<R, W, F>
+where
+    R: AsyncRead,
+    F: Fn(Error) -> Error,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        map_poll(this.inner.poll_read(cx, buf), this.f)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        map_poll(this.inner.poll_read_vectored(cx, bufs), this.f)
+    }
+}
+
+impl<R, W, F> AsyncWrite for MappedReadErrIO<R, W, F>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+impl<R, W, F> AsyncRead for MappedWriteErrIO<R, W, F>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<R, W, F> AsyncWrite for MappedWriteErrIO<R, W, F>
+where
+    W: AsyncWrite,
+    F: Fn(Error) -> Error,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        map_poll(this.inner.poll_write(cx, buf), this.f)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        map_poll(this.inner.poll_write_vectored(cx, bufs), this.f)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        map_poll(this.inner.poll_flush(cx), this.f)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        map_poll(this.inner.poll_close(cx), this.f)
+    }
+}
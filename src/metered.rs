@@ -0,0 +1,169 @@
+//! [`metrics`](https://docs.rs/metrics) integration.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use metrics::{counter, histogram, Counter, Histogram};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Clone)]
+struct DirectionMetrics {
+    bytes: Counter,
+    errors: Counter,
+    poll_bytes: Option<Histogram>,
+}
+
+impl DirectionMetrics {
+    fn new(prefix: &str, direction: &'static str, bytes: &str) -> Self {
+        DirectionMetrics {
+            bytes: counter!(format!("{}.{}", prefix, bytes), "direction" => direction),
+            errors: counter!(format!("{}.errors", prefix), "direction" => direction),
+            poll_bytes: None,
+        }
+    }
+
+    fn record<T>(&self, poll: &Poll<Result<T>>, bytes: impl FnOnce(&T) -> Option<usize>) {
+        match poll {
+            Poll::Ready(Ok(value)) => {
+                if let Some(n) = bytes(value) {
+                    self.bytes.increment(n as u64);
+                    if let Some(poll_bytes) = &self.poll_bytes {
+                        poll_bytes.record(n as f64);
+                    }
+                }
+            }
+            Poll::Ready(Err(_)) => self.errors.increment(1),
+            Poll::Pending => {}
+        }
+    }
+}
+
+/// Reports the traffic of an I/O object to the installed `metrics`
+/// recorder.
+///
+/// Completed reads and writes add to the `{prefix}.bytes_read` and
+/// `{prefix}.bytes_written` counters, and every failed poll adds one to
+/// `{prefix}.errors`; all of them carry a `direction` label of `"read"` or
+/// `"write"`, with failed flushes and closes counting as writes.
+/// [`with_histograms`](crate::metered::MeteredIO::with_histograms)
+/// additionally records the size of every read and write in a
+/// `{prefix}.poll_bytes` histogram.
+///
+/// The metrics are registered once, when the `MeteredIO` is created, so the
+/// recorder has to be installed before that.
+#[pin_project]
+#[derive(Debug)]
+pub struct MeteredIO<T> {
+    #[pin]
+    inner: T,
+    prefix: String,
+    read: DirectionMetrics,
+    write: DirectionMetrics,
+}
+
+impl<T> MeteredIO<T> {
+    /// Creates new [`MeteredIO`](crate::metered::MeteredIO) reporting metrics
+    /// named after `prefix`.
+    pub fn new(inner: T, prefix: &str) -> Self {
+        MeteredIO {
+            inner,
+            prefix: prefix.to_owned(),
+            read: DirectionMetrics::new(prefix, "read", "bytes_read"),
+            write: DirectionMetrics::new(prefix, "write", "bytes_written"),
+        }
+    }
+
+    /// Also records the number of bytes of every completed read and write in
+    /// the `{prefix}.poll_bytes` histogram.
+    pub fn with_histograms(mut self) -> Self {
+        let name = format!("{}.poll_bytes", self.prefix);
+        self.read.poll_bytes = Some(histogram!(name.clone(), "direction" => "read"));
+        self.write.poll_bytes = Some(histogram!(name, "direction" => "write"));
+        self
+    }
+
+    /// Returns the prefix of the metric names.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `MeteredIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for MeteredIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        this.read.record(&poll, |&n| Some(n));
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        this.read.record(&poll, |&n| Some(n));
+        poll
+    }
+}
+
+impl<T> AsyncWrite for MeteredIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        this.write.record(&poll, |&n| Some(n));
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        this.write.record(&poll, |&n| Some(n));
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_flush(cx);
+        this.write.record(&poll, |()| None);
+        poll
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        this.write.record(&poll, |()| None);
+        poll
+    }
+}
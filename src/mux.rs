@@ -0,0 +1,356 @@
+//! Multiplexing logical channels over a single stream.
+//!
+//! Every frame is a one-byte type, a big-endian `u32` channel id and a
+//! big-endian `u32` payload length, followed by the payload. A data frame
+//! carries bytes for a channel, a FIN frame has no payload and marks the end
+//! of the channel's data in that direction.
+
+use crate::util::{invalid_data, poll_drain, FrameReader, MAX_PAYLOAD};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+const FRAME_DATA: u8 = 0;
+const FRAME_FIN: u8 = 1;
+const HEADER_LEN: usize = 9;
+
+#[derive(Debug, Default)]
+struct Channel {
+    buf: VecDeque<u8>,
+    fin_received: bool,
+    fin_sent: bool,
+    reader: Option<Waker>,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    io: T,
+    channels: HashMap<u32, Channel>,
+    frames: FrameReader,
+    eof: bool,
+    read_error: Option<Error>,
+    write_buf: Vec<u8>,
+    writers: Vec<Waker>,
+    driver: Option<Waker>,
+}
+
+fn encode_frame(buf: &mut Vec<u8>, kind: u8, id: u32, payload: &[u8]) {
+    buf.push(kind);
+    buf.extend_from_slice(&id.to_be_bytes());
+    buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buf.extend_from_slice(payload);
+}
+
+impl<T> State<T> {
+    fn wake_readers(&mut self) {
+        for channel in self.channels.values_mut() {
+            if let Some(waker) = channel.reader.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn queue_fin(&mut self, id: u32) {
+        let channel = self.channels.entry(id).or_default();
+        if !channel.fin_sent {
+            channel.fin_sent = true;
+            encode_frame(&mut self.write_buf, FRAME_FIN, id, &[]);
+        }
+    }
+
+    fn read_error(&self) -> Option<Error> {
+        self.read_error
+            .as_ref()
+            .map(|err| Error::new(err.kind(), err.to_string()))
+    }
+}
+
+/// Hands a complete frame, header included, to its channel.
+fn dispatch(channels: &mut HashMap<u32, Channel>, frame: &[u8]) -> Result<()> {
+    let id = u32::from_be_bytes([frame[1], frame[2], frame[3], frame[4]]);
+    let channel = channels.entry(id).or_default();
+    match frame[0] {
+        FRAME_DATA => channel.buf.extend(&frame[HEADER_LEN..]),
+        FRAME_FIN => channel.fin_received = true,
+        _ => return Err(invalid_data("unknown frame type")),
+    }
+    if let Some(waker) = channel.reader.take() {
+        waker.wake();
+    }
+    Ok(())
+}
+
+impl<T> State<T>
+where
+    T: AsyncRead + Unpin,
+{
+    /// Reads a single frame and hands it to its channel. Returns `false` on a
+    /// clean EOF between frames.
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        let frame = match self.frames.poll_frame(
+            Pin::new(&mut self.io),
+            cx,
+            HEADER_LEN,
+            MAX_PAYLOAD,
+            |header| Ok(u32::from_be_bytes([header[5], header[6], header[7], header[8]]) as usize),
+        ) {
+            Poll::Ready(Ok(frame)) => frame,
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        };
+        match frame {
+            Some(frame) => Poll::Ready(dispatch(&mut self.channels, frame).map(|()| true)),
+            None => Poll::Ready(Ok(false)),
+        }
+    }
+
+    /// Demultiplexes incoming frames until the stream has nothing more to
+    /// offer, ends or fails.
+    fn poll_demux(&mut self, cx: &mut Context<'_>) {
+        while !self.eof && self.read_error.is_none() {
+            match self.poll_frame(cx) {
+                Poll::Ready(Ok(true)) => {}
+                Poll::Ready(Ok(false)) => {
+                    self.eof = true;
+                    self.wake_readers();
+                }
+                Poll::Ready(Err(err)) => {
+                    self.read_error = Some(err);
+                    self.wake_readers();
+                }
+                Poll::Pending => return,
+            }
+        }
+    }
+}
+
+impl<T> State<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let poll = poll_drain(Pin::new(&mut self.io), cx, &mut self.write_buf);
+        if poll.is_pending() {
+            if !self.writers.iter().any(|w| w.will_wake(cx.waker())) {
+                self.writers.push(cx.waker().clone());
+            }
+        } else {
+            for waker in self.writers.drain(..) {
+                waker.wake();
+            }
+        }
+        poll
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.poll_drain(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.io).poll_flush(cx),
+            poll => poll,
+        }
+    }
+}
+
+fn lock<T>(state: &Mutex<State<T>>) -> MutexGuard<'_, State<T>> {
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Multiplexes independent logical channels over a single stream, such as a
+/// [`MergeIO`](crate::MergeIO).
+///
+/// Channels are opened with
+/// [`open_channel`](crate::mux::MuxIO::open_channel) on both ends using the
+/// same id. Incoming frames are demultiplexed into per-channel buffers
+/// whenever any channel is read, so data for a channel nobody is reading is
+/// kept in memory until it is.
+///
+/// `MuxIO` is itself a future driving the stream: polling it writes out
+/// queued frames and demultiplexes incoming data until the stream ends. It
+/// is cheap to clone, so a clone can be spawned as a background task while
+/// the original keeps opening channels.
+#[derive(Debug)]
+pub struct MuxIO<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Clone for MuxIO<T> {
+    fn clone(&self) -> Self {
+        MuxIO {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<T> MuxIO<T> {
+    /// Creates new [`MuxIO`](crate::mux::MuxIO) over `stream`.
+    pub fn new(stream: T) -> Self {
+        MuxIO {
+            state: Arc::new(Mutex::new(State {
+                io: stream,
+                channels: HashMap::new(),
+                frames: FrameReader::default(),
+                eof: false,
+                read_error: None,
+                write_buf: Vec::new(),
+                writers: Vec::new(),
+                driver: None,
+            })),
+        }
+    }
+
+    /// Returns a handle to the logical channel `id`.
+    ///
+    /// Data the peer sent on the channel before it was opened is not lost.
+    /// Opening the same id again returns another handle to the same channel.
+    pub fn open_channel(&self, id: u32) -> ChannelHandle<T> {
+        lock(&self.state).channels.entry(id).or_default();
+        ChannelHandle {
+            id,
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Sends a FIN for the channel `id`, after which the peer reads EOF from
+    /// it once all the data sent before is consumed.
+    ///
+    /// The FIN is queued and written out the next time a channel or the
+    /// `MuxIO` itself is polled. Writing to the channel afterwards fails with
+    /// [`ErrorKind::BrokenPipe`](std::io::ErrorKind::BrokenPipe).
+    pub fn close_channel(&self, id: u32) {
+        let mut state = lock(&self.state);
+        state.queue_fin(id);
+        if let Some(waker) = state.driver.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for MuxIO<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    type Output = Result<()>;
+
+    /// Drives the stream, resolving once it ends.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = lock(&self.state);
+        state.driver = Some(cx.waker().clone());
+        if let Poll::Ready(Err(err)) = state.poll_flush(cx) {
+            return Poll::Ready(Err(err));
+        }
+        state.poll_demux(cx);
+        if let Some(err) = state.read_error() {
+            return Poll::Ready(Err(err));
+        }
+        if state.eof {
+            return Poll::Ready(Ok(()));
+        }
+        Poll::Pending
+    }
+}
+
+/// A logical channel of a [`MuxIO`](crate::mux::MuxIO).
+///
+/// Writes are split into frames of up to 64 KiB, which are buffered until
+/// the stream accepts them, so
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) has to be called to
+/// make sure everything is sent. Closing the handle sends a FIN for the
+/// channel, but leaves the stream and the other channels open.
+#[derive(Debug)]
+pub struct ChannelHandle<T> {
+    id: u32,
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> ChannelHandle<T> {
+    /// Returns the id of the channel.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl<T> AsyncRead for ChannelHandle<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let mut guard = lock(&self.state);
+        let state = &mut *guard;
+        if state.channels.entry(self.id).or_default().buf.is_empty() {
+            state.poll_demux(cx);
+        }
+
+        let channel = state.channels.entry(self.id).or_default();
+        if !channel.buf.is_empty() {
+            let n = buf.len().min(channel.buf.len());
+            for (dst, src) in buf.iter_mut().zip(channel.buf.drain(..n)) {
+                *dst = src;
+            }
+            // This task might have been the one the stream was going to
+            // wake, so let the other readers take over driving it.
+            state.wake_readers();
+            return Poll::Ready(Ok(n));
+        }
+        if channel.fin_received {
+            return Poll::Ready(Ok(0));
+        }
+        channel.reader = Some(cx.waker().clone());
+        if let Some(err) = state.read_error() {
+            return Poll::Ready(Err(err));
+        }
+        if state.eof {
+            return Poll::Ready(Ok(0));
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> AsyncWrite for ChannelHandle<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut state = lock(&self.state);
+        if state.channels.entry(self.id).or_default().fin_sent {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::BrokenPipe,
+                "channel already closed",
+            )));
+        }
+        match state.poll_drain(cx) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let data = &buf[..buf.len().min(MAX_PAYLOAD)];
+        encode_frame(&mut state.write_buf, FRAME_DATA, self.id, data);
+        // Every channel drains the shared buffer before queueing more, so the
+        // rest of this frame and any write error surface on the next write or
+        // flush of any channel.
+        let _ = state.poll_drain(cx);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        lock(&self.state).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut state = lock(&self.state);
+        state.queue_fin(self.id);
+        state.poll_flush(cx)
+    }
+}
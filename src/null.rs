@@ -0,0 +1,77 @@
+//! I/O objects discarding writes and reading nothing or zeros.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io::{IoSlice, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Discards all writes and reads a fixed or endless number of zero bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullIO {
+    zeros: Option<u64>,
+}
+
+impl NullIO {
+    /// Creates new [`NullIO`](crate::null::NullIO) reporting EOF right away.
+    pub fn sink() -> Self {
+        NullIO { zeros: Some(0) }
+    }
+
+    /// Creates new [`NullIO`](crate::null::NullIO) reading `count` zero
+    /// bytes before EOF.
+    pub fn zeros(count: u64) -> Self {
+        NullIO { zeros: Some(count) }
+    }
+
+    /// Creates new [`NullIO`](crate::null::NullIO) that never reaches EOF.
+    pub fn infinite_zeros() -> Self {
+        NullIO { zeros: None }
+    }
+
+    /// Returns the number of zero bytes left to read, or `None` if there's
+    /// no end to them.
+    pub fn remaining(&self) -> Option<u64> {
+        self.zeros
+    }
+}
+
+impl AsyncRead for NullIO {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let n = match self.zeros {
+            Some(ref mut zeros) => {
+                let n = (buf.len() as u64).min(*zeros) as usize;
+                *zeros -= n as u64;
+                n
+            }
+            None => buf.len(),
+        };
+        buf[..n].iter_mut().for_each(|b| *b = 0);
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncWrite for NullIO {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        Poll::Ready(Ok(bufs.iter().map(|buf| buf.len()).sum()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,141 @@
+//! Looking at upcoming data without consuming it.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::io::{IoSlice, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Allows peeking at the data to be read, keeping the peeked bytes in a
+/// buffer that subsequent reads return first.
+#[pin_project]
+#[derive(Debug)]
+pub struct PeekIO<T> {
+    #[pin]
+    inner: T,
+    peeked: VecDeque<u8>,
+}
+
+impl<T> PeekIO<T> {
+    /// Creates new [`PeekIO`](crate::peek::PeekIO).
+    pub fn new(inner: T) -> Self {
+        PeekIO {
+            inner,
+            peeked: VecDeque::new(),
+        }
+    }
+
+    /// Returns the bytes peeked at but not yet read.
+    pub fn peeked(&self) -> &VecDeque<u8> {
+        &self.peeked
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `PeekIO` into the inner I/O object, dropping the peeked
+    /// bytes.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> PeekIO<T>
+where
+    T: AsyncRead,
+{
+    /// Fills `buf` with upcoming data without consuming it and returns the
+    /// number of bytes copied.
+    ///
+    /// If fewer than `buf.len()` bytes have been peeked at so far, a single
+    /// read from the inner I/O object is attempted to get more. Already
+    /// peeked bytes are returned instead of waiting if that read is pending,
+    /// and fewer bytes than requested are returned at EOF.
+    pub fn poll_peek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let peeked = this.peeked;
+        if peeked.len() < buf.len() {
+            let mut chunk = vec![0; buf.len() - peeked.len()];
+            match this.inner.poll_read(cx, &mut chunk) {
+                Poll::Ready(Ok(n)) => peeked.extend(&chunk[..n]),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending if peeked.is_empty() => return Poll::Pending,
+                Poll::Pending => {}
+            }
+        }
+        let n = buf.len().min(peeked.len());
+        for (dst, src) in buf.iter_mut().zip(peeked.iter()) {
+            *dst = *src;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    /// Fills `buf` with upcoming data without consuming it.
+    ///
+    /// See [`poll_peek`](crate::peek::PeekIO::poll_peek) for the details.
+    pub async fn peek(&mut self, buf: &mut [u8]) -> Result<usize>
+    where
+        Self: Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut *self).poll_peek(cx, buf)).await
+    }
+}
+
+impl<T> AsyncRead for PeekIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if this.peeked.is_empty() {
+            return this.inner.poll_read(cx, buf);
+        }
+        let n = buf.len().min(this.peeked.len());
+        for (dst, src) in buf.iter_mut().zip(this.peeked.drain(..n)) {
+            *dst = src;
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<T> AsyncWrite for PeekIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
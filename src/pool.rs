@@ -0,0 +1,323 @@
+//! Reusing connections across tasks.
+
+use crate::MergeIO;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::io::Result;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Poll, Waker};
+
+/// Creates the connections of a [`Pool`](crate::pool::Pool).
+///
+/// Implemented for every `Fn() -> Fut` where `Fut` resolves to a
+/// `Result<MergeIO<R, W>>`.
+pub trait Connect {
+    /// The reader of the connections created.
+    type Reader;
+    /// The writer of the connections created.
+    type Writer;
+    /// The future resolving to a connection.
+    type Future: Future<Output = Result<MergeIO<Self::Reader, Self::Writer>>>;
+
+    /// Starts creating a connection.
+    fn connect(&self) -> Self::Future;
+}
+
+impl<F, Fut, R, W> Connect for F
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<MergeIO<R, W>>>,
+{
+    type Reader = R;
+    type Writer = W;
+    type Future = Fut;
+
+    fn connect(&self) -> Self::Future {
+        self()
+    }
+}
+
+#[derive(Debug)]
+struct State<R, W> {
+    idle: VecDeque<MergeIO<R, W>>,
+    /// Open connections, whether idle, acquired or still connecting.
+    size: usize,
+    max_idle: usize,
+    waiters: Vec<Waker>,
+}
+
+impl<R, W> State<R, W> {
+    fn wake_waiters(&mut self) {
+        for waker in self.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+type Shared<R, W> = Arc<Mutex<State<R, W>>>;
+
+fn lock<R, W>(state: &Shared<R, W>) -> MutexGuard<'_, State<R, W>> {
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A pool of [`MergeIO`](crate::MergeIO) connections created by a
+/// [`Connect`](crate::pool::Connect) factory.
+///
+/// [`acquire`](crate::pool::Pool::acquire) hands out an idle connection if
+/// there is one, creates a new one while fewer than `max_size` are open, and
+/// otherwise waits for one to be released. Connections go back to the pool
+/// when their [`PoolGuard`](crate::pool::PoolGuard) is dropped, unless
+/// `max_idle` connections are idle already, in which case they are dropped,
+/// closing them. Every `acquire` first tops the pool up to `min_size` open
+/// connections; [`fill`](crate::pool::Pool::fill) does the same ahead of the
+/// first one.
+///
+/// By default `min_size` is `0` and `max_size` and `max_idle` are unlimited.
+pub struct Pool<F: Connect> {
+    factory: F,
+    min_size: usize,
+    max_size: usize,
+    state: Shared<F::Reader, F::Writer>,
+}
+
+impl<F> Pool<F>
+where
+    F: Connect,
+{
+    /// Creates new empty [`Pool`](crate::pool::Pool) getting its connections
+    /// from `factory`.
+    pub fn new(factory: F) -> Self {
+        Pool {
+            factory,
+            min_size: 0,
+            max_size: usize::MAX,
+            state: Arc::new(Mutex::new(State {
+                idle: VecDeque::new(),
+                size: 0,
+                max_idle: usize::MAX,
+                waiters: Vec::new(),
+            })),
+        }
+    }
+
+    /// Sets the number of connections kept open, created by the next
+    /// [`acquire`](crate::pool::Pool::acquire) or
+    /// [`fill`](crate::pool::Pool::fill).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_size` exceeds the maximum size.
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        assert!(min_size <= self.max_size, "min_size exceeds max_size");
+        self.min_size = min_size;
+        self
+    }
+
+    /// Sets the maximum number of open connections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0` or less than the minimum size.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        assert!(max_size > 0, "max_size must be positive");
+        assert!(max_size >= self.min_size, "max_size is less than min_size");
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept.
+    pub fn with_max_idle(self, max_idle: usize) -> Self {
+        lock(&self.state).max_idle = max_idle;
+        self
+    }
+
+    /// Returns the number of connections kept open.
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    /// Returns the maximum number of open connections.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Returns the maximum number of idle connections kept.
+    pub fn max_idle(&self) -> usize {
+        lock(&self.state).max_idle
+    }
+
+    /// Returns the number of open connections, including acquired ones.
+    pub fn size(&self) -> usize {
+        lock(&self.state).size
+    }
+
+    /// Returns the number of idle connections.
+    pub fn idle_count(&self) -> usize {
+        lock(&self.state).idle.len()
+    }
+
+    /// Creates connections until at least `min_size` are open, adding them to
+    /// the idle ones.
+    ///
+    /// Stops at the first connection that fails to be created and returns its
+    /// error.
+    pub async fn fill(&self) -> Result<()> {
+        loop {
+            {
+                let mut state = lock(&self.state);
+                if state.size >= self.min_size {
+                    return Ok(());
+                }
+                state.size += 1;
+            }
+            let stream = self.connect().await?;
+            let mut state = lock(&self.state);
+            state.idle.push_back(stream);
+            state.wake_waiters();
+        }
+    }
+
+    /// Returns a connection from the pool, creating one if none is idle and
+    /// fewer than `max_size` are open, or waiting for one to be released
+    /// otherwise.
+    ///
+    /// Connections missing to reach `min_size` are created first, like
+    /// [`fill`](crate::pool::Pool::fill) does. Fails if creating any of the
+    /// connections does.
+    pub async fn acquire(&self) -> Result<PoolGuard<F::Reader, F::Writer>> {
+        self.fill().await?;
+        let idle = poll_fn(|cx| {
+            let mut state = lock(&self.state);
+            if let Some(stream) = state.idle.pop_front() {
+                return Poll::Ready(Some(stream));
+            }
+            if state.size < self.max_size {
+                state.size += 1;
+                return Poll::Ready(None);
+            }
+            state.waiters.push(cx.waker().clone());
+            Poll::Pending
+        })
+        .await;
+        let stream = match idle {
+            Some(stream) => stream,
+            None => self.connect().await?,
+        };
+        Ok(PoolGuard {
+            stream: Some(stream),
+            state: Arc::clone(&self.state),
+        })
+    }
+
+    /// Creates a connection already counted in the size, uncounting it if
+    /// that fails or the future is dropped.
+    async fn connect(&self) -> Result<MergeIO<F::Reader, F::Writer>> {
+        let mut slot = Slot {
+            state: &self.state,
+            reserved: true,
+        };
+        let stream = self.factory.connect().await?;
+        slot.reserved = false;
+        Ok(stream)
+    }
+}
+
+/// A counted connection that has yet to be created.
+struct Slot<'a, R, W> {
+    state: &'a Shared<R, W>,
+    reserved: bool,
+}
+
+impl<R, W> Drop for Slot<'_, R, W> {
+    fn drop(&mut self) {
+        if self.reserved {
+            let mut state = lock(self.state);
+            state.size -= 1;
+            state.wake_waiters();
+        }
+    }
+}
+
+impl<F> fmt::Debug for Pool<F>
+where
+    F: Connect,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = lock(&self.state);
+        f.debug_struct("Pool")
+            .field("min_size", &self.min_size)
+            .field("max_size", &self.max_size)
+            .field("max_idle", &state.max_idle)
+            .field("size", &state.size)
+            .field("idle", &state.idle.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A connection acquired from a [`Pool`](crate::pool::Pool), returned to it
+/// when dropped.
+pub struct PoolGuard<R, W> {
+    stream: Option<MergeIO<R, W>>,
+    state: Shared<R, W>,
+}
+
+impl<R, W> PoolGuard<R, W> {
+    /// Takes the connection out of the pool for good, freeing its slot.
+    ///
+    /// Use this for connections that broke and shouldn't be reused.
+    pub fn detach(mut self) -> MergeIO<R, W> {
+        let stream = self.stream.take().unwrap();
+        let mut state = lock(&self.state);
+        state.size -= 1;
+        state.wake_waiters();
+        stream
+    }
+}
+
+impl<R, W> Deref for PoolGuard<R, W> {
+    type Target = MergeIO<R, W>;
+
+    fn deref(&self) -> &Self::Target {
+        self.stream.as_ref().unwrap()
+    }
+}
+
+impl<R, W> DerefMut for PoolGuard<R, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.stream.as_mut().unwrap()
+    }
+}
+
+impl<R, W> Drop for PoolGuard<R, W> {
+    fn drop(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            let evicted = {
+                let mut state = lock(&self.state);
+                state.wake_waiters();
+                if state.idle.len() < state.max_idle {
+                    state.idle.push_back(stream);
+                    None
+                } else {
+                    state.size -= 1;
+                    Some(stream)
+                }
+            };
+            // Close the connection outside of the lock.
+            drop(evicted);
+        }
+    }
+}
+
+impl<R, W> fmt::Debug for PoolGuard<R, W>
+where
+    R: fmt::Debug,
+    W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolGuard")
+            .field("stream", &self.stream)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,165 @@
+//! Proxying data between I/O streams.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::future::try_join;
+use futures_util::io::{copy, AsyncWriteExt};
+use std::future::poll_fn;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const BUF_SIZE: usize = 8 * 1024;
+
+async fn copy_and_close<R, W>(reader: R, writer: &mut W) -> Result<u64>
+where
+    R: AsyncRead,
+    W: AsyncWrite + Unpin,
+{
+    let n = copy(reader, writer).await?;
+    writer.close().await?;
+    Ok(n)
+}
+
+/// Copies data from `a`'s reader to `b`'s writer and from `b`'s reader to
+/// `a`'s writer concurrently, returning the number of bytes copied in each
+/// direction as `(a_to_b, b_to_a)`.
+///
+/// When a direction reaches EOF, the writer it was copying to is flushed and
+/// closed, and the other direction keeps going until it reaches EOF as well.
+/// The first error in either direction is returned right away.
+pub async fn bridge<R1, W1, R2, W2>(
+    a: &mut MergeIO<R1, W1>,
+    b: &mut MergeIO<R2, W2>,
+) -> Result<(u64, u64)>
+where
+    R1: AsyncRead + Unpin,
+    W1: AsyncWrite + Unpin,
+    R2: AsyncRead + Unpin,
+    W2: AsyncWrite + Unpin,
+{
+    try_join(
+        copy_and_close(&mut a.reader, &mut b.writer),
+        copy_and_close(&mut b.reader, &mut a.writer),
+    )
+    .await
+}
+
+/// One direction of a
+/// [`copy_bidirectional`](crate::proxy::copy_bidirectional).
+#[derive(Debug)]
+struct CopyBuffer {
+    buf: Box<[u8]>,
+    pos: usize,
+    cap: usize,
+    amt: u64,
+    read_done: bool,
+    flushed: bool,
+    done: bool,
+}
+
+impl CopyBuffer {
+    fn new() -> Self {
+        CopyBuffer {
+            buf: vec![0; BUF_SIZE].into_boxed_slice(),
+            pos: 0,
+            cap: 0,
+            amt: 0,
+            read_done: false,
+            flushed: false,
+            done: false,
+        }
+    }
+
+    /// Copies from `reader` to `writer` until `reader` reaches EOF, then
+    /// flushes and closes `writer`.
+    fn poll_copy<R, W>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut reader: Pin<&mut R>,
+        mut writer: Pin<&mut W>,
+    ) -> Poll<Result<u64>>
+    where
+        R: AsyncRead + ?Sized,
+        W: AsyncWrite + ?Sized,
+    {
+        loop {
+            if self.done {
+                return Poll::Ready(Ok(self.amt));
+            }
+            if self.pos == self.cap && !self.read_done {
+                match reader.as_mut().poll_read(cx, &mut self.buf) {
+                    Poll::Ready(Ok(0)) => self.read_done = true,
+                    Poll::Ready(Ok(n)) => {
+                        self.pos = 0;
+                        self.cap = n;
+                    }
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            if self.pos < self.cap {
+                match writer
+                    .as_mut()
+                    .poll_write(cx, &self.buf[self.pos..self.cap])
+                {
+                    Poll::Ready(Ok(0)) => {
+                        return Poll::Ready(Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "write zero byte into writer",
+                        )))
+                    }
+                    Poll::Ready(Ok(n)) => {
+                        self.pos += n;
+                        self.amt += n as u64;
+                    }
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                }
+                continue;
+            }
+            if !self.flushed {
+                match writer.as_mut().poll_flush(cx) {
+                    Poll::Ready(Ok(())) => self.flushed = true,
+                    poll => return poll.map_ok(|()| 0),
+                }
+            }
+            match writer.as_mut().poll_close(cx) {
+                Poll::Ready(Ok(())) => self.done = true,
+                poll => return poll.map_ok(|()| 0),
+            }
+        }
+    }
+}
+
+/// Copies data from `stream_a` to `stream_b` and from `stream_b` to
+/// `stream_a` concurrently, returning the number of bytes copied in each
+/// direction as `(a_to_b, b_to_a)`.
+///
+/// Unlike [`bridge`](crate::proxy::bridge), this works with any pair of
+/// streams, not just [`MergeIO`](crate::MergeIO)s: both directions are driven
+/// from a single task, each with a buffer of its own. When a direction
+/// reaches EOF, the stream it was writing to is flushed and closed, and the
+/// other direction keeps going until it reaches EOF as well. The first error
+/// in either direction is returned right away.
+pub async fn copy_bidirectional<A, B>(stream_a: &mut A, stream_b: &mut B) -> Result<(u64, u64)>
+where
+    A: AsyncRead + AsyncWrite + Unpin + ?Sized,
+    B: AsyncRead + AsyncWrite + Unpin + ?Sized,
+{
+    let mut a_to_b = CopyBuffer::new();
+    let mut b_to_a = CopyBuffer::new();
+    poll_fn(|cx| {
+        let a_to_b = a_to_b.poll_copy(cx, Pin::new(&mut *stream_a), Pin::new(&mut *stream_b));
+        if let Poll::Ready(Err(err)) = a_to_b {
+            return Poll::Ready(Err(err));
+        }
+        let b_to_a = b_to_a.poll_copy(cx, Pin::new(&mut *stream_b), Pin::new(&mut *stream_a));
+        match (a_to_b, b_to_a) {
+            (_, Poll::Ready(Err(err))) => Poll::Ready(Err(err)),
+            (Poll::Ready(Ok(a_to_b)), Poll::Ready(Ok(b_to_a))) => Poll::Ready(Ok((a_to_b, b_to_a))),
+            _ => Poll::Pending,
+        }
+    })
+    .await
+}
@@ -0,0 +1,469 @@
+//! Bandwidth throttling based on token buckets.
+//!
+//! The wrappers rely on the Tokio timer and have to be polled within a Tokio
+//! runtime.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io::{IoSlice, Result};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep_until, Instant, Sleep};
+
+/// The state of a token bucket refilled lazily from the elapsed time, where
+/// one token allows transferring one byte.
+#[derive(Debug)]
+struct Tokens {
+    rate: u64,
+    burst: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Tokens {
+    fn new(rate: u64, burst: u64) -> Self {
+        let burst = burst.max(1);
+        Tokens {
+            rate,
+            burst,
+            tokens: burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn set_burst(&mut self, burst: u64) {
+        self.burst = burst.max(1);
+        self.tokens = self.tokens.min(self.burst as f64);
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Returns how many of the `wanted` bytes may be transferred now, or the
+    /// instant at which all of them, up to a full burst, may be.
+    ///
+    /// Waiting for the whole request rather than a single byte keeps the
+    /// number of timer wake-ups proportional to the number of bursts.
+    fn try_acquire(&mut self, wanted: usize) -> std::result::Result<usize, Instant> {
+        if self.rate == 0 || wanted == 0 {
+            return Ok(wanted);
+        }
+        self.refill();
+        if self.tokens >= 1.0 {
+            return Ok((self.tokens as u64).min(wanted as u64) as usize);
+        }
+        let target = (wanted as u64).min(self.burst) as f64;
+        let wait = Duration::from_secs_f64((target - self.tokens) / self.rate as f64);
+        Err(self.last_refill + wait)
+    }
+
+    fn consume(&mut self, n: usize) {
+        if self.rate != 0 {
+            self.tokens -= n as f64;
+        }
+    }
+}
+
+/// Calls `acquire` until it grants some bytes, sleeping until the instants
+/// it returns in between.
+fn poll_acquire_with(
+    sleep: &mut Option<Pin<Box<Sleep>>>,
+    cx: &mut Context<'_>,
+    mut acquire: impl FnMut() -> std::result::Result<usize, Instant>,
+) -> Poll<usize> {
+    loop {
+        match acquire() {
+            Ok(allowed) => {
+                *sleep = None;
+                return Poll::Ready(allowed);
+            }
+            Err(deadline) => {
+                let sleep = sleep.get_or_insert_with(|| Box::pin(sleep_until(deadline)));
+                sleep.as_mut().reset(deadline);
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// A token bucket refilled lazily from the elapsed time, where one token
+/// allows transferring one byte.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    tokens: Tokens,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket; a `rate` of zero disables limiting.
+    pub(crate) fn new(rate: u64, burst: u64) -> Self {
+        TokenBucket {
+            tokens: Tokens::new(rate, burst),
+            sleep: None,
+        }
+    }
+
+    pub(crate) fn set_burst(&mut self, burst: u64) {
+        self.tokens.set_burst(burst);
+    }
+
+    /// Returns how many of the `wanted` bytes may be transferred now, or
+    /// schedules a wake-up for when all of them, up to a full burst, may be.
+    pub(crate) fn poll_acquire(&mut self, cx: &mut Context<'_>, wanted: usize) -> Poll<usize> {
+        let tokens = &mut self.tokens;
+        poll_acquire_with(&mut self.sleep, cx, || tokens.try_acquire(wanted))
+    }
+
+    /// Takes the tokens for `n` transferred bytes out of the bucket.
+    pub(crate) fn consume(&mut self, n: usize) {
+        self.tokens.consume(n);
+    }
+}
+
+/// Limits the throughput of reads and writes independently.
+///
+/// Each direction gets a token bucket that holds up to a second worth of
+/// bytes by default; use
+/// [`with_read_burst`](crate::rate_limit::RateLimitedIO::with_read_burst) and
+/// [`with_write_burst`](crate::rate_limit::RateLimitedIO::with_write_burst) to
+/// change that. A rate of zero disables limiting in that direction.
+#[pin_project]
+#[derive(Debug)]
+pub struct RateLimitedIO<T> {
+    #[pin]
+    inner: T,
+    read_bucket: TokenBucket,
+    write_bucket: TokenBucket,
+}
+
+impl<T> RateLimitedIO<T> {
+    /// Creates new [`RateLimitedIO`](crate::rate_limit::RateLimitedIO)
+    /// limiting reads to `read_bps` and writes to `write_bps` bytes per
+    /// second.
+    pub fn new(inner: T, read_bps: u64, write_bps: u64) -> Self {
+        RateLimitedIO {
+            inner,
+            read_bucket: TokenBucket::new(read_bps, read_bps),
+            write_bucket: TokenBucket::new(write_bps, write_bps),
+        }
+    }
+
+    /// Sets how many bytes can be read in a single burst.
+    pub fn with_read_burst(mut self, burst: u64) -> Self {
+        self.read_bucket.set_burst(burst);
+        self
+    }
+
+    /// Sets how many bytes can be written in a single burst.
+    pub fn with_write_burst(mut self, burst: u64) -> Self {
+        self.write_bucket.set_burst(burst);
+        self
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `RateLimitedIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for RateLimitedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let allowed = match this.read_bucket.poll_acquire(cx, buf.len()) {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+        let poll = this.inner.poll_read(cx, &mut buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.read_bucket.consume(n);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for RateLimitedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let allowed = match this.write_bucket.poll_acquire(cx, buf.len()) {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+        let poll = this.inner.poll_write(cx, &buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.write_bucket.consume(n);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Limits the throughput of reads only, simulating a slow upstream, while
+/// writes pass through untouched.
+///
+/// Like [`RateLimitedIO`](crate::rate_limit::RateLimitedIO), this uses a
+/// token bucket holding up to a second worth of bytes by default; use
+/// [`with_burst`](crate::rate_limit::ThrottledReadIO::with_burst) to change
+/// that. A rate of zero disables limiting.
+#[pin_project]
+#[derive(Debug)]
+pub struct ThrottledReadIO<T> {
+    #[pin]
+    inner: T,
+    bytes_per_sec: u64,
+    bucket: TokenBucket,
+}
+
+impl<T> ThrottledReadIO<T> {
+    /// Creates new [`ThrottledReadIO`](crate::rate_limit::ThrottledReadIO)
+    /// returning at most `bytes_per_sec` bytes per second from reads.
+    pub fn new(inner: T, bytes_per_sec: u64) -> Self {
+        ThrottledReadIO {
+            inner,
+            bytes_per_sec,
+            bucket: TokenBucket::new(bytes_per_sec, bytes_per_sec),
+        }
+    }
+
+    /// Sets how many bytes can be read in a single burst.
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.bucket.set_burst(burst);
+        self
+    }
+
+    /// Returns the configured rate.
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ThrottledReadIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for ThrottledReadIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let allowed = match this.bucket.poll_acquire(cx, buf.len()) {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+        let poll = this.inner.poll_read(cx, &mut buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.bucket.consume(n);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for ThrottledReadIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// A token bucket shared by any number of
+/// [`BudgetedIO`](crate::rate_limit::BudgetedIO) streams, limiting their
+/// combined throughput in both directions.
+///
+/// Refills happen lazily: a stream that finds the budget exhausted sleeps
+/// until enough time has passed for a refill, so every waiting stream wakes
+/// up on its own without any background task. Streams draw from the budget
+/// concurrently, so it can briefly be overdrawn; the deficit is paid back
+/// before anyone can transfer again.
+#[derive(Debug)]
+pub struct IoBudget {
+    tokens: Mutex<Tokens>,
+}
+
+impl IoBudget {
+    /// Creates new [`IoBudget`](crate::rate_limit::IoBudget) allowing
+    /// `total_bytes_per_sec` bytes per second and bursts of up to `burst`
+    /// bytes. A rate of zero disables limiting.
+    pub fn new(total_bytes_per_sec: u64, burst: u64) -> Arc<Self> {
+        Arc::new(IoBudget {
+            tokens: Mutex::new(Tokens::new(total_bytes_per_sec, burst)),
+        })
+    }
+
+    fn try_acquire(&self, wanted: usize) -> std::result::Result<usize, Instant> {
+        self.tokens.lock().unwrap().try_acquire(wanted)
+    }
+
+    fn consume(&self, n: usize) {
+        self.tokens.lock().unwrap().consume(n);
+    }
+}
+
+/// Draws from a shared [`IoBudget`](crate::rate_limit::IoBudget) before each
+/// read or write.
+///
+/// Reads and writes wait for the budget separately, so both can be pending at
+/// once, for example when the halves of a split stream are driven by
+/// different tasks.
+#[pin_project]
+#[derive(Debug)]
+pub struct BudgetedIO<T> {
+    #[pin]
+    inner: T,
+    budget: Arc<IoBudget>,
+    read_sleep: Option<Pin<Box<Sleep>>>,
+    write_sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<T> BudgetedIO<T> {
+    /// Creates new [`BudgetedIO`](crate::rate_limit::BudgetedIO) drawing
+    /// from `budget`.
+    pub fn new(inner: T, budget: Arc<IoBudget>) -> Self {
+        BudgetedIO {
+            inner,
+            budget,
+            read_sleep: None,
+            write_sleep: None,
+        }
+    }
+
+    /// Returns the shared budget.
+    pub fn budget(&self) -> &Arc<IoBudget> {
+        &self.budget
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `BudgetedIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for BudgetedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let budget = &**this.budget;
+        let allowed = match poll_acquire_with(this.read_sleep, cx, || budget.try_acquire(buf.len()))
+        {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+        let poll = this.inner.poll_read(cx, &mut buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            budget.consume(n);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for BudgetedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let budget = &**this.budget;
+        let allowed =
+            match poll_acquire_with(this.write_sleep, cx, || budget.try_acquire(buf.len())) {
+                Poll::Ready(allowed) => allowed,
+                Poll::Pending => return Poll::Pending,
+            };
+        let poll = this.inner.poll_write(cx, &buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            budget.consume(n);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,55 @@
+//! Probing readiness separately from transferring data.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A reader that can be polled for readiness without committing a buffer.
+pub trait AsyncReadReady {
+    /// Returns `Ready(Ok(()))` once data is available to read, or an error
+    /// the next read would return, and `Pending` otherwise.
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>>;
+}
+
+/// A writer that can be polled for readiness without committing a buffer.
+pub trait AsyncWriteReady {
+    /// Returns `Ready(Ok(()))` once the writer can accept data, or an error
+    /// the next write would return, and `Pending` otherwise.
+    fn poll_write_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>>;
+}
+
+/// Probes `reader` with a zero-length read.
+pub(crate) fn probe_read<R>(reader: Pin<&mut R>, cx: &mut Context<'_>) -> Poll<Result<()>>
+where
+    R: AsyncRead + ?Sized,
+{
+    reader.poll_read(cx, &mut []).map_ok(|_| ())
+}
+
+/// Probes `writer` with a zero-length write.
+pub(crate) fn probe_write<W>(writer: Pin<&mut W>, cx: &mut Context<'_>) -> Poll<Result<()>>
+where
+    W: AsyncWrite + ?Sized,
+{
+    writer.poll_write(cx, &[]).map_ok(|_| ())
+}
+
+impl<R, W> AsyncReadReady for MergeIO<R, W>
+where
+    R: AsyncRead,
+{
+    fn poll_read_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        MergeIO::poll_read_ready(self, cx)
+    }
+}
+
+impl<R, W> AsyncWriteReady for MergeIO<R, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        MergeIO::poll_write_ready(self, cx)
+    }
+}
@@ -0,0 +1,282 @@
+//! Re-establishing connections on I/O errors.
+//!
+//! Backoff delays are waited out with Tokio timers, so a Tokio runtime has to
+//! be running.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::fmt;
+use std::future::Future;
+use std::io::{Error, IoSlice, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Sleep};
+
+/// Creates fresh streams for [`ReconnectIO`](crate::reconnect::ReconnectIO).
+///
+/// Implemented for every `Fn() -> Fut` where `Fut` resolves to an
+/// `io::Result` of a stream.
+pub trait StreamFactory {
+    /// The stream created.
+    type Stream;
+    /// The future resolving to a new stream.
+    type Future: Future<Output = Result<Self::Stream>>;
+
+    /// Starts creating a new stream.
+    fn connect(&self) -> Self::Future;
+}
+
+impl<F, Fut, S> StreamFactory for F
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<S>>,
+{
+    type Stream = S;
+    type Future = Fut;
+
+    fn connect(&self) -> Self::Future {
+        self()
+    }
+}
+
+/// Delays between consecutive reconnection attempts, doubling from
+/// `initial` up to `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    initial: Duration,
+    max: Duration,
+}
+
+impl ExponentialBackoff {
+    /// Creates new [`ExponentialBackoff`](crate::reconnect::ExponentialBackoff).
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        ExponentialBackoff { initial, max }
+    }
+
+    /// Returns the delay before the reconnection attempt with the given
+    /// zero-based index.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        2u32.checked_pow(attempt)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+#[derive(Debug)]
+enum Failure {
+    Retry,
+    GiveUp(Error),
+}
+
+struct Connection<F: StreamFactory> {
+    stream: Option<F::Stream>,
+    connecting: Option<Pin<Box<F::Future>>>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    failures: usize,
+    max_attempts: usize,
+    backoff: ExponentialBackoff,
+}
+
+impl<F> Connection<F>
+where
+    F: StreamFactory,
+    F::Stream: Unpin,
+{
+    fn fail(&mut self, err: Error) -> Failure {
+        self.stream = None;
+        self.connecting = None;
+        if self.failures >= self.max_attempts {
+            self.failures = 0;
+            return Failure::GiveUp(err);
+        }
+        let attempt = self.failures.min(u32::MAX as usize) as u32;
+        self.sleep = Some(Box::pin(sleep(self.backoff.delay(attempt))));
+        self.failures += 1;
+        Failure::Retry
+    }
+
+    fn poll_connect(&mut self, factory: &F, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            if let Some(sleep) = self.sleep.as_mut() {
+                match sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => self.sleep = None,
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            let connecting = self
+                .connecting
+                .get_or_insert_with(|| Box::pin(factory.connect()));
+            match connecting.as_mut().poll(cx) {
+                Poll::Ready(Ok(stream)) => {
+                    self.connecting = None;
+                    self.stream = Some(stream);
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(err)) => {
+                    if let Failure::GiveUp(err) = self.fail(err) {
+                        return Poll::Ready(Err(err));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn poll_op<T>(
+        &mut self,
+        factory: &F,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(Pin<&mut F::Stream>, &mut Context<'_>) -> Poll<Result<T>>,
+    ) -> Poll<Result<T>> {
+        loop {
+            if let Some(stream) = self.stream.as_mut() {
+                match op(Pin::new(stream), cx) {
+                    Poll::Ready(Err(err)) => {
+                        if let Failure::GiveUp(err) = self.fail(err) {
+                            return Poll::Ready(Err(err));
+                        }
+                    }
+                    Poll::Ready(Ok(val)) => {
+                        self.failures = 0;
+                        return Poll::Ready(Ok(val));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            match self.poll_connect(factory, cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A stream that is replaced with a fresh one from a
+/// [`StreamFactory`](crate::reconnect::StreamFactory) when an operation
+/// fails, after which the operation is retried on the new stream.
+///
+/// The first stream is created by the first operation. Up to `max_attempts`
+/// reconnections are made in a row, waiting for the
+/// [`ExponentialBackoff`](crate::reconnect::ExponentialBackoff) delay before
+/// each; a failure to connect counts as an attempt too. When they're
+/// exhausted the last error is returned, and the next operation starts
+/// over. The attempt count is also reset by every successful operation.
+///
+/// Data buffered by a stream that failed is lost, so this is only suitable
+/// for protocols that tolerate it. Closing doesn't reconnect and succeeds
+/// right away if there's no stream.
+#[pin_project]
+pub struct ReconnectIO<F: StreamFactory> {
+    factory: F,
+    conn: Connection<F>,
+}
+
+impl<F> ReconnectIO<F>
+where
+    F: StreamFactory,
+{
+    /// Creates new [`ReconnectIO`](crate::reconnect::ReconnectIO) getting
+    /// streams from `factory`.
+    pub fn new(factory: F, max_attempts: usize, backoff: ExponentialBackoff) -> Self {
+        ReconnectIO {
+            factory,
+            conn: Connection {
+                stream: None,
+                connecting: None,
+                sleep: None,
+                failures: 0,
+                max_attempts,
+                backoff,
+            },
+        }
+    }
+
+    /// Returns whether there's a stream currently in use.
+    pub fn is_connected(&self) -> bool {
+        self.conn.stream.is_some()
+    }
+
+    /// Provides access to the stream currently in use, if any.
+    pub fn get_ref(&self) -> Option<&F::Stream> {
+        self.conn.stream.as_ref()
+    }
+
+    /// Provides `mut` access to the stream currently in use, if any.
+    pub fn get_mut(&mut self) -> Option<&mut F::Stream> {
+        self.conn.stream.as_mut()
+    }
+
+    /// Deconstructs `ReconnectIO` into the stream currently in use, if any.
+    pub fn into_inner(self) -> Option<F::Stream> {
+        self.conn.stream
+    }
+}
+
+impl<F> fmt::Debug for ReconnectIO<F>
+where
+    F: StreamFactory,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectIO")
+            .field("connected", &self.conn.stream.is_some())
+            .field("failures", &self.conn.failures)
+            .field("max_attempts", &self.conn.max_attempts)
+            .field("backoff", &self.conn.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> AsyncRead for ReconnectIO<F>
+where
+    F: StreamFactory,
+    F::Stream: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        this.conn
+            .poll_op(this.factory, cx, |stream, cx| stream.poll_read(cx, buf))
+    }
+}
+
+impl<F> AsyncWrite for ReconnectIO<F>
+where
+    F: StreamFactory,
+    F::Stream: AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        this.conn
+            .poll_op(this.factory, cx, |stream, cx| stream.poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        this.conn.poll_op(this.factory, cx, |stream, cx| {
+            stream.poll_write_vectored(cx, bufs)
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        this.conn
+            .poll_op(this.factory, cx, |stream, cx| stream.poll_flush(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        match this.conn.stream.as_mut() {
+            Some(stream) => Pin::new(stream).poll_close(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
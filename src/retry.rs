@@ -0,0 +1,148 @@
+//! Retrying operations that fail with transient errors.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Retries operations failing with
+/// [`ErrorKind::WouldBlock`](std::io::ErrorKind::WouldBlock) or
+/// [`ErrorKind::Interrupted`](std::io::ErrorKind::Interrupted) on the same
+/// I/O object, right away and up to `max_retries` times in a row, before
+/// returning the error.
+///
+/// The retry count is shared by all operations and reset by every
+/// successful one.
+#[pin_project]
+#[derive(Debug)]
+pub struct RetryIO<T> {
+    #[pin]
+    inner: T,
+    max_retries: u32,
+    retries: u32,
+}
+
+impl<T> RetryIO<T> {
+    /// Creates new [`RetryIO`](crate::retry::RetryIO).
+    pub fn new(inner: T, max_retries: u32) -> Self {
+        RetryIO {
+            inner,
+            max_retries,
+            retries: 0,
+        }
+    }
+
+    /// Returns the number of retries made since the last successful
+    /// operation.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `RetryIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn poll_retry<U>(
+    retries: &mut u32,
+    max_retries: u32,
+    mut op: impl FnMut() -> Poll<Result<U>>,
+) -> Poll<Result<U>> {
+    loop {
+        match op() {
+            Poll::Ready(Err(err))
+                if *retries < max_retries
+                    && matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) =>
+            {
+                *retries += 1;
+            }
+            Poll::Ready(result) => {
+                *retries = 0;
+                return Poll::Ready(result);
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+impl<T> AsyncRead for RetryIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_read(cx, buf)
+        })
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_read_vectored(cx, bufs)
+        })
+    }
+}
+
+impl<T> AsyncWrite for RetryIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_write(cx, buf)
+        })
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_write_vectored(cx, bufs)
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_flush(cx)
+        })
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let mut inner = this.inner;
+        poll_retry(this.retries, *this.max_retries, || {
+            inner.as_mut().poll_close(cx)
+        })
+    }
+}
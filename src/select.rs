@@ -0,0 +1,40 @@
+//! Reading from several streams in a single task.
+
+use futures_io::AsyncRead;
+use std::future::poll_fn;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::Poll;
+
+/// The stream a [`select_read`](crate::select::select_read) read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WhichStream {
+    /// The first stream.
+    A,
+    /// The second stream.
+    B,
+}
+
+/// Reads into `buf` from whichever of `a` and `b` is ready first, returning
+/// the number of bytes read and the stream they came from.
+///
+/// `a` is always polled first, so if both are ready at the same time the
+/// data comes from `a`, and a stream that keeps being ready starves the
+/// other. EOF counts as ready, returning `0` along with the stream that
+/// ended, and so does an error, which is returned as is. Nothing is read
+/// from the stream that wasn't picked.
+pub async fn select_read<A, B>(a: &mut A, b: &mut B, buf: &mut [u8]) -> Result<(usize, WhichStream)>
+where
+    A: AsyncRead + Unpin + ?Sized,
+    B: AsyncRead + Unpin + ?Sized,
+{
+    poll_fn(|cx| {
+        if let Poll::Ready(res) = Pin::new(&mut *a).poll_read(cx, buf) {
+            return Poll::Ready(res.map(|n| (n, WhichStream::A)));
+        }
+        Pin::new(&mut *b)
+            .poll_read(cx, buf)
+            .map_ok(|n| (n, WhichStream::B))
+    })
+    .await
+}
@@ -0,0 +1,186 @@
+//! Sequence-numbered frames for detecting lost or reordered writes.
+//!
+//! Every write is sent as a frame made of a big-endian `u64` sequence
+//! number, a big-endian `u32` payload length and the payload itself.
+
+use crate::util::{invalid_data, poll_drain, start_drain, FrameReader, MAX_PAYLOAD};
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const SEQ_LEN: usize = 8;
+const HEADER_LEN: usize = SEQ_LEN + 4;
+
+/// Numbers every write and checks the numbers of incoming frames.
+///
+/// Each write of up to 64 KiB becomes one frame carrying the next sequence
+/// number, starting from zero. Reads strip the framing and fail with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) when a frame
+/// doesn't carry the expected number; reading then continues after the
+/// number received, so only the first frame after a gap is reported. Frames
+/// with a payload over 64 KiB fail the same way and are skipped. Closing
+/// resets both counters.
+///
+/// Frames are buffered until the inner I/O object accepts them, so
+/// [`poll_flush`](futures::io::AsyncWrite::poll_flush) has to be called to
+/// make sure everything is sent.
+#[pin_project]
+#[derive(Debug)]
+pub struct SequencedIO<T> {
+    #[pin]
+    inner: T,
+    next_read_seq: u64,
+    next_write_seq: u64,
+    frames: FrameReader,
+    data: Vec<u8>,
+    data_pos: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<T> SequencedIO<T> {
+    /// Creates new [`SequencedIO`](crate::sequenced::SequencedIO) with both
+    /// counters at zero.
+    pub fn new(inner: T) -> Self {
+        SequencedIO {
+            inner,
+            next_read_seq: 0,
+            next_write_seq: 0,
+            frames: FrameReader::default(),
+            data: Vec::new(),
+            data_pos: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Returns the sequence number expected on the next incoming frame.
+    pub fn next_read_seq(&self) -> u64 {
+        self.next_read_seq
+    }
+
+    /// Returns the sequence number the next write is sent with.
+    pub fn next_write_seq(&self) -> u64 {
+        self.next_write_seq
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `SequencedIO` into the inner I/O object, dropping any
+    /// received but unread data and any frames not yet written.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for SequencedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        loop {
+            if *this.data_pos < this.data.len() {
+                let available = &this.data[*this.data_pos..];
+                let n = buf.len().min(available.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                *this.data_pos += n;
+                return Poll::Ready(Ok(n));
+            }
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let frame = match this.frames.poll_frame(
+                this.inner.as_mut(),
+                cx,
+                HEADER_LEN,
+                MAX_PAYLOAD,
+                |header| {
+                    let mut len = [0u8; 4];
+                    len.copy_from_slice(&header[SEQ_LEN..]);
+                    Ok(u32::from_be_bytes(len) as usize)
+                },
+            ) {
+                Poll::Ready(Ok(Some(frame))) => frame,
+                Poll::Ready(Ok(None)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            let mut seq = [0u8; SEQ_LEN];
+            seq.copy_from_slice(&frame[..SEQ_LEN]);
+            let seq = u64::from_be_bytes(seq);
+            this.data.clear();
+            this.data.extend_from_slice(&frame[HEADER_LEN..]);
+            *this.data_pos = 0;
+            let expected = *this.next_read_seq;
+            *this.next_read_seq = seq.wrapping_add(1);
+            if seq != expected {
+                return Poll::Ready(Err(invalid_data(format!(
+                    "expected sequence number {}, got {}",
+                    expected, seq
+                ))));
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for SequencedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll.map_ok(|()| 0),
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let data = &buf[..buf.len().min(MAX_PAYLOAD)];
+        this.write_buf
+            .extend_from_slice(&this.next_write_seq.to_be_bytes());
+        this.write_buf
+            .extend_from_slice(&(data.len() as u32).to_be_bytes());
+        this.write_buf.extend_from_slice(data);
+        *this.next_write_seq = this.next_write_seq.wrapping_add(1);
+        start_drain(this.inner, cx, this.write_buf);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => this.inner.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        match poll_drain(this.inner.as_mut(), cx, this.write_buf) {
+            Poll::Ready(Ok(())) => {}
+            poll => return poll,
+        }
+        let poll = this.inner.poll_close(cx);
+        if poll.is_ready() {
+            *this.next_read_seq = 0;
+            *this.next_write_seq = 0;
+        }
+        poll
+    }
+}
@@ -0,0 +1,153 @@
+//! Typed messages over length-prefixed frames.
+//!
+//! [`SerdeFrameIO`](crate::serde_frame::SerdeFrameIO) serializes every
+//! message into a frame of a [`FramedIO`](crate::framing::FramedIO) with a
+//! [`Codec`](crate::serde_frame::Codec):
+//!
+//! ```
+//! # #[cfg(feature = "serde_json")]
+//! # futures::executor::block_on(async {
+//! use merge_io::framing::FramedIO;
+//! use merge_io::serde_frame::{JsonCodec, SerdeFrameIO};
+//!
+//! let (a, b) = merge_io::duplex(1024);
+//! let mut a = SerdeFrameIO::<_, JsonCodec>::new(FramedIO::new(a, 1024));
+//! let mut b = SerdeFrameIO::<_, JsonCodec>::new(FramedIO::new(b, 1024));
+//!
+//! a.send(&("hello", 42)).await.unwrap();
+//! let msg: (String, u32) = b.recv().await.unwrap();
+//! assert_eq!(msg, ("hello".to_owned(), 42));
+//! # });
+//! ```
+
+use crate::framing::FramedIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Result;
+#[cfg(any(feature = "serde_json", feature = "ciborium"))]
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+
+/// A serialization format for the messages of a
+/// [`SerdeFrameIO`](crate::serde_frame::SerdeFrameIO).
+pub trait Codec {
+    /// Serializes `msg` into the payload of a frame.
+    fn encode<S: Serialize>(msg: &S) -> Result<Vec<u8>>;
+
+    /// Deserializes a message from the payload of a frame.
+    fn decode<D: DeserializeOwned>(bytes: &[u8]) -> Result<D>;
+}
+
+/// Encodes messages as JSON.
+#[cfg(feature = "serde_json")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "serde_json")]
+impl Codec for JsonCodec {
+    fn encode<S: Serialize>(msg: &S) -> Result<Vec<u8>> {
+        serde_json::to_vec(msg).map_err(|err| Error::new(ErrorKind::InvalidInput, err))
+    }
+
+    fn decode<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
+        serde_json::from_slice(bytes).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+/// Encodes messages as CBOR.
+#[cfg(feature = "ciborium")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "ciborium")]
+impl Codec for CborCodec {
+    fn encode<S: Serialize>(msg: &S) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(msg, &mut bytes)
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err.to_string()))?;
+        Ok(bytes)
+    }
+
+    fn decode<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
+        ciborium::from_reader(bytes)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
+/// Sends and receives messages serialized with `C`, one per frame of a
+/// [`FramedIO`](crate::framing::FramedIO).
+///
+/// Messages that fail to serialize, or serialize to more than the maximum
+/// frame size, fail with
+/// [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput); frames that
+/// exceed the maximum frame size or fail to deserialize fail with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) and are
+/// skipped, so the next message can still be received.
+pub struct SerdeFrameIO<T, C> {
+    framed: FramedIO<T>,
+    codec: PhantomData<fn() -> C>,
+}
+
+impl<T, C> SerdeFrameIO<T, C> {
+    /// Creates new [`SerdeFrameIO`](crate::serde_frame::SerdeFrameIO).
+    pub fn new(framed: FramedIO<T>) -> Self {
+        SerdeFrameIO {
+            framed,
+            codec: PhantomData,
+        }
+    }
+
+    /// Provides access to the inner [`FramedIO`](crate::framing::FramedIO).
+    pub fn get_ref(&self) -> &FramedIO<T> {
+        &self.framed
+    }
+
+    /// Provides `mut` access to the inner
+    /// [`FramedIO`](crate::framing::FramedIO).
+    pub fn get_mut(&mut self) -> &mut FramedIO<T> {
+        &mut self.framed
+    }
+
+    /// Deconstructs `SerdeFrameIO` into the inner
+    /// [`FramedIO`](crate::framing::FramedIO).
+    pub fn into_inner(self) -> FramedIO<T> {
+        self.framed
+    }
+}
+
+impl<T, C> SerdeFrameIO<T, C>
+where
+    T: AsyncWrite + Unpin,
+    C: Codec,
+{
+    /// Serializes `msg` and writes it as a single frame.
+    pub async fn send<S: Serialize>(&mut self, msg: &S) -> Result<()> {
+        let bytes = C::encode(msg)?;
+        self.framed.write_frame(&bytes).await
+    }
+}
+
+impl<T, C> SerdeFrameIO<T, C>
+where
+    T: AsyncRead + Unpin,
+    C: Codec,
+{
+    /// Reads the next frame and deserializes it into a message.
+    pub async fn recv<D: for<'de> Deserialize<'de>>(&mut self) -> Result<D> {
+        let bytes = self.framed.read_frame().await?;
+        C::decode(&bytes)
+    }
+}
+
+impl<T, C> fmt::Debug for SerdeFrameIO<T, C>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SerdeFrameIO")
+            .field("framed", &self.framed)
+            .finish()
+    }
+}
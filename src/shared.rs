@@ -0,0 +1,168 @@
+//! Sharing a single [`MergeIO`](crate::MergeIO) between tasks.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io::{Error, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug)]
+struct Shared<R, W> {
+    io: Mutex<MergeIO<R, W>>,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+/// A cloneable handle to a [`MergeIO`](crate::MergeIO) shared by several
+/// tasks.
+///
+/// Every operation locks the underlying `MergeIO` for the duration of a
+/// single poll. If another handle holds the lock, the task is woken once it
+/// is released instead of blocking the thread. Each poll is atomic with
+/// respect to the other handles, so data passed to a single
+/// [`poll_write`](futures::io::AsyncWrite::poll_write) is never interleaved
+/// with another task's writes, but a `write_all` spanning several polls can
+/// be.
+///
+/// Many readers and writers keep only the waker of the last task that polled
+/// them, so when several tasks wait on the same direction at once only one of
+/// them might be woken. Sharing works best with one task per direction, or
+/// with halves that never return `Pending`.
+#[derive(Debug)]
+pub struct SharedMergeIO<R, W> {
+    shared: Arc<Shared<R, W>>,
+}
+
+impl<R, W> Clone for SharedMergeIO<R, W> {
+    fn clone(&self) -> Self {
+        SharedMergeIO {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<R, W> SharedMergeIO<R, W> {
+    /// Creates new [`SharedMergeIO`](crate::shared::SharedMergeIO) owning
+    /// `inner`.
+    pub fn new(inner: MergeIO<R, W>) -> Self {
+        SharedMergeIO {
+            shared: Arc::new(Shared {
+                io: Mutex::new(inner),
+                waiters: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Returns the `MergeIO` if this is the only handle left, and the handle
+    /// itself otherwise.
+    pub fn try_unwrap(self) -> std::result::Result<MergeIO<R, W>, Self> {
+        match Arc::try_unwrap(self.shared) {
+            Ok(shared) => Ok(shared
+                .io
+                .into_inner()
+                .unwrap_or_else(|err| err.into_inner())),
+            Err(shared) => Err(SharedMergeIO { shared }),
+        }
+    }
+
+    fn try_lock(&self) -> Result<Option<MutexGuard<'_, MergeIO<R, W>>>> {
+        match self.shared.io.try_lock() {
+            Ok(guard) => Ok(Some(guard)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Poisoned(_)) => {
+                Err(Error::other("shared MergeIO poisoned by a panicking task"))
+            }
+        }
+    }
+
+    fn poll_locked<T>(
+        &self,
+        cx: &mut Context<'_>,
+        op: impl FnOnce(Pin<&mut MergeIO<R, W>>, &mut Context<'_>) -> Poll<Result<T>>,
+    ) -> Poll<Result<T>>
+    where
+        R: Unpin,
+        W: Unpin,
+    {
+        let mut guard = match self.try_lock() {
+            Ok(Some(guard)) => guard,
+            Ok(None) => {
+                self.shared
+                    .waiters
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .push(cx.waker().clone());
+                // The lock might have been released before the waker was
+                // registered, so check again to avoid missing the wake-up.
+                match self.try_lock() {
+                    Ok(Some(guard)) => guard,
+                    Ok(None) => return Poll::Pending,
+                    Err(err) => return Poll::Ready(Err(err)),
+                }
+            }
+            Err(err) => return Poll::Ready(Err(err)),
+        };
+        let poll = op(Pin::new(&mut *guard), cx);
+        drop(guard);
+
+        let waiters = std::mem::take(
+            &mut *self
+                .shared
+                .waiters
+                .lock()
+                .unwrap_or_else(|err| err.into_inner()),
+        );
+        for waker in waiters {
+            waker.wake();
+        }
+        poll
+    }
+}
+
+impl<R, W> AsyncRead for SharedMergeIO<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.poll_locked(cx, |io, cx| io.poll_read(cx, buf))
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.poll_locked(cx, |io, cx| io.poll_read_vectored(cx, bufs))
+    }
+}
+
+impl<R, W> AsyncWrite for SharedMergeIO<R, W>
+where
+    R: Unpin,
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.poll_locked(cx, |io, cx| io.poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.poll_locked(cx, |io, cx| io.poll_write_vectored(cx, bufs))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_locked(cx, |io, cx| io.poll_flush(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_locked(cx, |io, cx| io.poll_close(cx))
+    }
+}
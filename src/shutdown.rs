@@ -0,0 +1,353 @@
+//! Closing streams gracefully.
+
+use futures_channel::oneshot;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::io::AsyncWriteExt;
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Flushes the inner I/O object to completion before closing it.
+///
+/// This keeps buffered data from being lost by code that calls
+/// [`close`](futures::io::AsyncWriteExt::close) without flushing first, on
+/// writers whose `poll_close` doesn't flush by itself.
+#[pin_project]
+#[derive(Debug)]
+pub struct GracefulShutdownIO<T> {
+    #[pin]
+    inner: T,
+    flushed: bool,
+}
+
+/// An alias for [`GracefulShutdownIO`](crate::shutdown::GracefulShutdownIO).
+pub type DrainOnCloseIO<T> = GracefulShutdownIO<T>;
+
+impl<T> GracefulShutdownIO<T> {
+    /// Creates new [`GracefulShutdownIO`](crate::shutdown::GracefulShutdownIO).
+    pub fn new(inner: T) -> Self {
+        GracefulShutdownIO {
+            inner,
+            flushed: false,
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `GracefulShutdownIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for GracefulShutdownIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for GracefulShutdownIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        *this.flushed = false;
+        this.inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        *this.flushed = false;
+        this.inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if !*this.flushed {
+            match this.inner.as_mut().poll_flush(cx) {
+                Poll::Ready(Ok(())) => *this.flushed = true,
+                poll => return poll,
+            }
+        }
+        this.inner.poll_close(cx)
+    }
+}
+
+/// Lets the read and write sides be closed separately, like a TCP
+/// half-close.
+///
+/// Once [`close_write`](crate::shutdown::HalfCloseIO::close_write) has closed
+/// the inner writer, writes fail with
+/// [`ErrorKind::BrokenPipe`](std::io::ErrorKind::BrokenPipe), and flushing
+/// and closing succeed without touching it. Once
+/// [`close_read`](crate::shutdown::HalfCloseIO::close_read) has been called,
+/// reads return EOF without touching the inner reader.
+#[pin_project]
+#[derive(Debug)]
+pub struct HalfCloseIO<T> {
+    #[pin]
+    inner: T,
+    read_closed: bool,
+    write_closed: bool,
+}
+
+impl<T> HalfCloseIO<T> {
+    /// Creates new [`HalfCloseIO`](crate::shutdown::HalfCloseIO) with both
+    /// sides open.
+    pub fn new(inner: T) -> Self {
+        HalfCloseIO {
+            inner,
+            read_closed: false,
+            write_closed: false,
+        }
+    }
+
+    /// Marks the read side closed.
+    pub async fn close_read(&mut self) {
+        self.read_closed = true;
+    }
+
+    /// Returns `true` if the read side has been closed.
+    pub fn read_closed(&self) -> bool {
+        self.read_closed
+    }
+
+    /// Returns `true` if the write side has been closed.
+    pub fn write_closed(&self) -> bool {
+        self.write_closed
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `HalfCloseIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> HalfCloseIO<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    /// Closes the inner writer and marks the write side closed. Does
+    /// nothing if it's already closed.
+    pub async fn close_write(&mut self) -> Result<()> {
+        if !self.write_closed {
+            self.inner.close().await?;
+            self.write_closed = true;
+        }
+        Ok(())
+    }
+}
+
+fn write_closed() -> Error {
+    Error::new(ErrorKind::BrokenPipe, "write side is closed")
+}
+
+impl<T> AsyncRead for HalfCloseIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if *this.read_closed {
+            return Poll::Ready(Ok(0));
+        }
+        this.inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if *this.read_closed {
+            return Poll::Ready(Ok(0));
+        }
+        this.inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for HalfCloseIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        if *this.write_closed {
+            return Poll::Ready(Err(write_closed()));
+        }
+        this.inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if *this.write_closed {
+            return Poll::Ready(Err(write_closed()));
+        }
+        this.inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        if *this.write_closed {
+            return Poll::Ready(Ok(()));
+        }
+        this.inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        if *this.write_closed {
+            return Poll::Ready(Ok(()));
+        }
+        let poll = this.inner.poll_close(cx);
+        if let Poll::Ready(Ok(())) = poll {
+            *this.write_closed = true;
+        }
+        poll
+    }
+}
+
+/// Sends `()` on a [`oneshot`](futures::channel::oneshot) channel once the
+/// inner I/O object has been closed successfully.
+///
+/// Only the first successful close sends; later closes complete normally. A
+/// dropped receiver is ignored.
+#[pin_project]
+#[derive(Debug)]
+pub struct ShutdownSignalIO<T> {
+    #[pin]
+    inner: T,
+    tx: Option<oneshot::Sender<()>>,
+}
+
+impl<T> ShutdownSignalIO<T> {
+    /// Creates new [`ShutdownSignalIO`](crate::shutdown::ShutdownSignalIO)
+    /// signalling on `tx`.
+    pub fn new(inner: T, tx: oneshot::Sender<()>) -> Self {
+        ShutdownSignalIO {
+            inner,
+            tx: Some(tx),
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ShutdownSignalIO` into the inner I/O object, dropping
+    /// the sender if it hasn't been used yet.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for ShutdownSignalIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for ShutdownSignalIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        if let Poll::Ready(Ok(())) = poll {
+            if let Some(tx) = this.tx.take() {
+                let _ = tx.send(());
+            }
+        }
+        poll
+    }
+}
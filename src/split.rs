@@ -0,0 +1,296 @@
+//! Owned and borrowed halves of a split [`MergeIO`](crate::MergeIO).
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+/// Returns an ID shared by the two halves of a single split.
+pub(crate) fn next_split_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The reading half of a [`MergeIO`](crate::MergeIO), created by
+/// [`MergeIO::into_split`](crate::MergeIO::into_split).
+#[pin_project]
+#[derive(Debug)]
+pub struct ReadHalf<R> {
+    #[pin]
+    reader: R,
+    id: u64,
+}
+
+/// The writing half of a [`MergeIO`](crate::MergeIO), created by
+/// [`MergeIO::into_split`](crate::MergeIO::into_split).
+#[pin_project]
+#[derive(Debug)]
+pub struct WriteHalf<W> {
+    #[pin]
+    writer: W,
+    id: u64,
+}
+
+/// The reading half of a pinned [`MergeIO`](crate::MergeIO), borrowed by
+/// [`MergeIO::pin_split`](crate::MergeIO::pin_split).
+#[derive(Debug)]
+pub struct PinnedReadHalf<'a, R> {
+    reader: Pin<&'a mut R>,
+}
+
+/// The writing half of a pinned [`MergeIO`](crate::MergeIO), borrowed by
+/// [`MergeIO::pin_split`](crate::MergeIO::pin_split).
+#[derive(Debug)]
+pub struct PinnedWriteHalf<'a, W> {
+    writer: Pin<&'a mut W>,
+}
+
+impl<R> ReadHalf<R> {
+    pub(crate) fn new(reader: R, id: u64) -> Self {
+        ReadHalf { reader, id }
+    }
+
+    /// Returns whether `other` was split off the same
+    /// [`MergeIO`](crate::MergeIO) as this half.
+    pub fn is_pair_of<W>(&self, other: &WriteHalf<W>) -> bool {
+        self.id == other.id
+    }
+
+    /// Reconstructs the [`MergeIO`](crate::MergeIO) this half and `other`
+    /// were split off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the halves come from different splits, use
+    /// [`is_pair_of`](crate::split::ReadHalf::is_pair_of) to check first.
+    pub fn reunite<W>(self, other: WriteHalf<W>) -> MergeIO<R, W> {
+        assert!(
+            self.is_pair_of(&other),
+            "tried to reunite halves that are not from the same split"
+        );
+        MergeIO::new(self.reader, other.writer)
+    }
+
+    /// Provides access to `reader`.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Provides `mut` access to `reader`.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Deconstructs `ReadHalf` into the `reader`.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<W> WriteHalf<W> {
+    pub(crate) fn new(writer: W, id: u64) -> Self {
+        WriteHalf { writer, id }
+    }
+
+    /// Provides access to `writer`.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Provides `mut` access to `writer`.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Deconstructs `WriteHalf` into the `writer`.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<'a, R> PinnedReadHalf<'a, R> {
+    pub(crate) fn new(reader: Pin<&'a mut R>) -> Self {
+        PinnedReadHalf { reader }
+    }
+
+    /// Provides access to `reader`.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Provides pinned `mut` access to `reader`.
+    pub fn get_pin_mut(&mut self) -> Pin<&mut R> {
+        self.reader.as_mut()
+    }
+}
+
+impl<'a, W> PinnedWriteHalf<'a, W> {
+    pub(crate) fn new(writer: Pin<&'a mut W>) -> Self {
+        PinnedWriteHalf { writer }
+    }
+
+    /// Provides access to `writer`.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Provides pinned `mut` access to `writer`.
+    pub fn get_pin_mut(&mut self) -> Pin<&mut W> {
+        self.writer.as_mut()
+    }
+}
+
+impl<R> AsRef<R> for ReadHalf<R> {
+    fn as_ref(&self) -> &R {
+        &self.reader
+    }
+}
+
+impl<R> AsMut<R> for ReadHalf<R> {
+    fn as_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+}
+
+impl<W> AsRef<W> for WriteHalf<W> {
+    fn as_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W> AsMut<W> for WriteHalf<W> {
+    fn as_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<R> AsyncRead for ReadHalf<R>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().reader.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().reader.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<W> AsyncWrite for WriteHalf<W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().writer.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().writer.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_close(cx)
+    }
+}
+
+impl<R> AsyncRead for PinnedReadHalf<'_, R>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.reader.as_mut().poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.reader.as_mut().poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<W> AsyncWrite for PinnedWriteHalf<'_, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.writer.as_mut().poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.writer.as_mut().poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.writer.as_mut().poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.writer.as_mut().poll_close(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> tokio::io::AsyncRead for ReadHalf<R>
+where
+    R: tokio::io::AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        self.project().reader.poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W> tokio::io::AsyncWrite for WriteHalf<W>
+where
+    W: tokio::io::AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().writer.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().writer.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,307 @@
+//! Capturing traffic for record-and-replay tests.
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::io::Cursor;
+use pin_project::pin_project;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, IoSlice, Result, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A [`MergeIO`](crate::MergeIO) replaying captured traffic.
+pub type ReplayIO = MergeIO<Cursor<Vec<u8>>, Cursor<Vec<u8>>>;
+
+/// Records every byte read from and written to the inner I/O object.
+#[pin_project]
+#[derive(Debug)]
+pub struct SpyIO<T> {
+    #[pin]
+    inner: T,
+    reads: Vec<u8>,
+    writes: Vec<u8>,
+}
+
+impl<T> SpyIO<T> {
+    /// Creates new [`SpyIO`](crate::spy::SpyIO).
+    pub fn new(inner: T) -> Self {
+        SpyIO {
+            inner,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// Returns all the bytes read so far.
+    pub fn captured_reads(&self) -> &[u8] {
+        &self.reads
+    }
+
+    /// Returns all the bytes written so far.
+    pub fn captured_writes(&self) -> &[u8] {
+        &self.writes
+    }
+
+    /// Returns a [`ReplayIO`](crate::spy::ReplayIO) whose reader yields the
+    /// captured reads.
+    ///
+    /// Its writer starts out empty, so the code under test can be run against
+    /// it and what it writes compared with
+    /// [`captured_writes`](crate::spy::SpyIO::captured_writes).
+    pub fn replay(&self) -> ReplayIO {
+        MergeIO::new(Cursor::new(self.reads.clone()), Cursor::new(Vec::new()))
+    }
+
+    /// Saves the captured reads and writes to two files, for golden-file
+    /// tests.
+    pub fn save_captures(
+        &self,
+        reads_path: impl AsRef<Path>,
+        writes_path: impl AsRef<Path>,
+    ) -> Result<()> {
+        std::fs::write(reads_path, &self.reads)?;
+        std::fs::write(writes_path, &self.writes)
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `SpyIO` into the inner I/O object, dropping the captured
+    /// traffic.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Creates a [`ReplayIO`](crate::spy::ReplayIO) replaying reads saved by
+/// [`SpyIO::save_captures`](crate::spy::SpyIO::save_captures).
+pub fn load_replay(reads_path: impl AsRef<Path>) -> Result<ReplayIO> {
+    let reads = std::fs::read(reads_path)?;
+    Ok(MergeIO::new(Cursor::new(reads), Cursor::new(Vec::new())))
+}
+
+impl<T> AsyncRead for SpyIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.reads.extend_from_slice(&buf[..n]);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for SpyIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.writes.extend_from_slice(&buf[..n]);
+        }
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(mut n)) = poll {
+            for buf in bufs {
+                let taken = n.min(buf.len());
+                this.writes.extend_from_slice(&buf[..taken]);
+                n -= taken;
+            }
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+const RECORD_READ: u8 = 0;
+const RECORD_WRITE: u8 = 1;
+
+/// Records every read from and write to the inner I/O object into a file,
+/// for golden-file tests.
+///
+/// Every completed operation is appended as a record made of a direction
+/// byte (`0` for reads, `1` for writes), a big-endian `u64` length and the
+/// data. The data still passes through to the caller. Recording errors don't
+/// affect the I/O; the first one is returned by
+/// [`finish`](crate::spy::RecordReplayIO::finish).
+#[pin_project]
+#[derive(Debug)]
+pub struct RecordReplayIO<T> {
+    #[pin]
+    inner: T,
+    file: BufWriter<File>,
+    error: Option<Error>,
+}
+
+impl<T> RecordReplayIO<T> {
+    /// Creates new [`RecordReplayIO`](crate::spy::RecordReplayIO) recording
+    /// into a new file at `path`, replacing any existing one.
+    pub fn record(inner: T, path: impl AsRef<Path>) -> Result<Self> {
+        Ok(RecordReplayIO {
+            inner,
+            file: BufWriter::new(File::create(path)?),
+            error: None,
+        })
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Writes out the recording and deconstructs `RecordReplayIO` into the
+    /// inner I/O object, failing if anything couldn't be recorded.
+    pub fn finish(self) -> Result<T> {
+        let RecordReplayIO {
+            inner,
+            mut file,
+            error,
+        } = self;
+        if let Some(err) = error {
+            return Err(err);
+        }
+        file.flush()?;
+        Ok(inner)
+    }
+}
+
+impl RecordReplayIO<()> {
+    /// Loads a recording made by
+    /// [`record`](crate::spy::RecordReplayIO::record) into a
+    /// [`MergeIO`](crate::MergeIO) whose reader yields the recorded reads.
+    ///
+    /// Its writer starts out empty, so the code under test can be run
+    /// against it and what it writes compared with
+    /// [`recorded_writes`](crate::spy::RecordReplayIO::recorded_writes).
+    pub fn replay(path: impl AsRef<Path>) -> Result<MergeIO<Cursor<Vec<u8>>, Vec<u8>>> {
+        let reads = load_records(path.as_ref(), RECORD_READ)?;
+        Ok(MergeIO::new(Cursor::new(reads), Vec::new()))
+    }
+
+    /// Loads the writes from a recording made by
+    /// [`record`](crate::spy::RecordReplayIO::record).
+    pub fn recorded_writes(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        load_records(path.as_ref(), RECORD_WRITE)
+    }
+}
+
+fn load_records(path: &Path, direction: u8) -> Result<Vec<u8>> {
+    let file = std::fs::read(path)?;
+    let mut rest = &file[..];
+    let mut data = Vec::new();
+    while !rest.is_empty() {
+        if rest.len() < 9 {
+            return Err(Error::new(ErrorKind::InvalidData, "truncated record"));
+        }
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&rest[1..9]);
+        let len = u64::from_be_bytes(len);
+        let body = &rest[9..];
+        if len > body.len() as u64 {
+            return Err(Error::new(ErrorKind::InvalidData, "truncated record"));
+        }
+        let (record, tail) = body.split_at(len as usize);
+        match rest[0] {
+            d if d == direction => data.extend_from_slice(record),
+            RECORD_READ | RECORD_WRITE => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "unknown record direction",
+                ))
+            }
+        }
+        rest = tail;
+    }
+    Ok(data)
+}
+
+fn append_record(file: &mut impl Write, error: &mut Option<Error>, direction: u8, data: &[u8]) {
+    if error.is_some() || data.is_empty() {
+        return;
+    }
+    let result = file
+        .write_all(&[direction])
+        .and_then(|()| file.write_all(&(data.len() as u64).to_be_bytes()))
+        .and_then(|()| file.write_all(data));
+    if let Err(err) = result {
+        *error = Some(err);
+    }
+}
+
+impl<T> AsyncRead for RecordReplayIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            append_record(this.file, this.error, RECORD_READ, &buf[..n]);
+        }
+        poll
+    }
+}
+
+impl<T> AsyncWrite for RecordReplayIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            append_record(this.file, this.error, RECORD_WRITE, &buf[..n]);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
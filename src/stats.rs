@@ -0,0 +1,164 @@
+//! Fine-grained I/O metrics.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Metrics collected by [`StatsIO`](crate::stats::StatsIO).
+///
+/// Call counters only track polls that returned `Ready`, successful or not.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IoStats {
+    /// Total number of bytes read.
+    pub bytes_read: u64,
+    /// Total number of bytes written.
+    pub bytes_written: u64,
+    /// Number of completed read polls.
+    pub read_calls: u64,
+    /// Number of completed write polls.
+    pub write_calls: u64,
+    /// Number of completed flush polls.
+    pub flush_calls: u64,
+    /// Number of completed close polls.
+    pub close_calls: u64,
+}
+
+/// Wraps an I/O object and collects [`IoStats`](crate::stats::IoStats) for
+/// it.
+#[pin_project]
+#[derive(Debug)]
+pub struct StatsIO<T> {
+    #[pin]
+    inner: T,
+    stats: IoStats,
+}
+
+impl<T> StatsIO<T> {
+    /// Creates new [`StatsIO`](crate::stats::StatsIO) with zeroed stats.
+    pub fn new(inner: T) -> Self {
+        StatsIO {
+            inner,
+            stats: IoStats::default(),
+        }
+    }
+
+    /// Provides access to the collected stats.
+    pub fn stats(&self) -> &IoStats {
+        &self.stats
+    }
+
+    /// Provides `mut` access to the collected stats, e.g. to reset them.
+    pub fn stats_mut(&mut self) -> &mut IoStats {
+        &mut self.stats
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `StatsIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn record<T>(poll: &Poll<Result<T>>, calls: &mut u64) {
+    if poll.is_ready() {
+        *calls += 1;
+    }
+}
+
+fn record_bytes(poll: &Poll<Result<usize>>, calls: &mut u64, bytes: &mut u64) {
+    record(poll, calls);
+    if let Poll::Ready(Ok(n)) = poll {
+        *bytes += *n as u64;
+    }
+}
+
+impl<T> AsyncRead for StatsIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        record_bytes(
+            &poll,
+            &mut this.stats.read_calls,
+            &mut this.stats.bytes_read,
+        );
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        record_bytes(
+            &poll,
+            &mut this.stats.read_calls,
+            &mut this.stats.bytes_read,
+        );
+        poll
+    }
+}
+
+impl<T> AsyncWrite for StatsIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        record_bytes(
+            &poll,
+            &mut this.stats.write_calls,
+            &mut this.stats.bytes_written,
+        );
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        record_bytes(
+            &poll,
+            &mut this.stats.write_calls,
+            &mut this.stats.bytes_written,
+        );
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_flush(cx);
+        record(&poll, &mut this.stats.flush_calls);
+        poll
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        record(&poll, &mut this.stats.close_calls);
+        poll
+    }
+}
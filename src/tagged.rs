@@ -0,0 +1,117 @@
+//! Session identifiers for [`MergeIO`](crate::MergeIO).
+
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::fmt;
+use std::io::{Error, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Wraps [`MergeIO`](crate::MergeIO) and carries an identifier, shown in the
+/// [`Debug`] output and prepended as `"[{id}] "` to the message of every
+/// error.
+///
+/// The error kind is kept as is.
+#[pin_project]
+#[derive(Debug)]
+pub struct TaggedMergeIO<R, W, ID> {
+    id: ID,
+    #[pin]
+    inner: MergeIO<R, W>,
+}
+
+impl<R, W, ID> TaggedMergeIO<R, W, ID> {
+    /// Creates new [`TaggedMergeIO`](crate::tagged::TaggedMergeIO) tagged
+    /// with `id`.
+    pub fn new(inner: MergeIO<R, W>, id: ID) -> Self {
+        TaggedMergeIO { id, inner }
+    }
+
+    /// Returns the identifier.
+    pub fn id(&self) -> &ID {
+        &self.id
+    }
+
+    /// Provides access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_ref(&self) -> &MergeIO<R, W> {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner [`MergeIO`](crate::MergeIO).
+    pub fn get_mut(&mut self) -> &mut MergeIO<R, W> {
+        &mut self.inner
+    }
+
+    /// Deconstructs `TaggedMergeIO` into the inner
+    /// [`MergeIO`](crate::MergeIO) and the identifier.
+    pub fn into_inner(self) -> (MergeIO<R, W>, ID) {
+        (self.inner, self.id)
+    }
+}
+
+fn tag<T, ID>(poll: Poll<Result<T>>, id: &ID) -> Poll<Result<T>>
+where
+    ID: fmt::Display,
+{
+    match poll {
+        Poll::Ready(Err(err)) => {
+            Poll::Ready(Err(Error::new(err.kind(), format!("[{}] {}", id, err))))
+        }
+        poll => poll,
+    }
+}
+
+impl<R, W, ID> AsyncRead for TaggedMergeIO<R, W, ID>
+where
+    R: AsyncRead,
+    ID: fmt::Display,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        tag(this.inner.poll_read(cx, buf), this.id)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        tag(this.inner.poll_read_vectored(cx, bufs), this.id)
+    }
+}
+
+impl<R, W, ID> AsyncWrite for TaggedMergeIO<R, W, ID>
+where
+    W: AsyncWrite,
+    ID: fmt::Display,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        tag(this.inner.poll_write(cx, buf), this.id)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        tag(this.inner.poll_write_vectored(cx, bufs), this.id)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        tag(this.inner.poll_flush(cx), this.id)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        tag(this.inner.poll_close(cx), this.id)
+    }
+}
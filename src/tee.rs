@@ -0,0 +1,481 @@
+//! Combinators duplicating traffic to a secondary writer.
+
+use crate::util::poll_drain;
+use crate::MergeIO;
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::future::poll_fn;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Copies every byte read from the inner I/O object to a `secondary` writer.
+///
+/// Bytes that the secondary writer doesn't accept right away are buffered,
+/// and the buffer is drained before the next read from the inner I/O object
+/// is attempted, so the secondary writer applies backpressure to reads.
+///
+/// A read that got data from the inner I/O object always returns it. If the
+/// secondary writer fails while taking that data, its error is returned by
+/// the next read or tee flush instead, and the secondary writer misses the
+/// data of the read it failed on.
+#[pin_project]
+#[derive(Debug)]
+pub struct TeeReadIO<T, W2> {
+    #[pin]
+    inner: T,
+    #[pin]
+    secondary: W2,
+    pending: Vec<u8>,
+    error: Option<Error>,
+}
+
+impl<T, W2> TeeReadIO<T, W2> {
+    /// Creates new [`TeeReadIO`](crate::tee::TeeReadIO) copying reads to
+    /// `secondary`.
+    pub fn new(inner: T, secondary: W2) -> Self {
+        TeeReadIO {
+            inner,
+            secondary,
+            pending: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the bytes that were read but not yet accepted by the secondary
+    /// writer.
+    pub fn pending_tee(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Provides access to the secondary writer.
+    pub fn secondary(&self) -> &W2 {
+        &self.secondary
+    }
+
+    /// Provides `mut` access to the secondary writer.
+    pub fn secondary_mut(&mut self) -> &mut W2 {
+        &mut self.secondary
+    }
+
+    /// Deconstructs `TeeReadIO` into the inner I/O object and the secondary
+    /// writer, dropping any data not yet written to the latter.
+    pub fn into_inner(self) -> (T, W2) {
+        (self.inner, self.secondary)
+    }
+}
+
+impl<T, W2> TeeReadIO<T, W2>
+where
+    W2: AsyncWrite,
+{
+    /// Writes all the pending tee data to the secondary writer and flushes
+    /// it.
+    pub fn poll_flush_tee(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        match poll_drain(this.secondary.as_mut(), cx, this.pending) {
+            Poll::Ready(Ok(())) => this.secondary.poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    /// Writes all the pending tee data to the secondary writer and flushes
+    /// it.
+    pub async fn flush_tee(&mut self) -> Result<()>
+    where
+        Self: Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut *self).poll_flush_tee(cx)).await
+    }
+}
+
+impl<T, W2> AsyncRead for TeeReadIO<T, W2>
+where
+    T: AsyncRead,
+    W2: AsyncWrite,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        match poll_drain(this.secondary.as_mut(), cx, this.pending) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.pending.extend_from_slice(&buf[..n]);
+            // The caller gets the data regardless, so a failing secondary
+            // writer reports its error on the next read.
+            if let Poll::Ready(Err(err)) = poll_drain(this.secondary, cx, this.pending) {
+                this.pending.clear();
+                *this.error = Some(err);
+            }
+        }
+        poll
+    }
+}
+
+impl<T, W2> AsyncWrite for TeeReadIO<T, W2>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Polls two operations until both complete, remembering which one is done
+/// already so it isn't polled again. An error ends the operation, so the next
+/// call starts over with both.
+fn poll_both(
+    cx: &mut Context<'_>,
+    first_done: &mut bool,
+    second_done: &mut bool,
+    first: impl FnOnce(&mut Context<'_>) -> Poll<Result<()>>,
+    second: impl FnOnce(&mut Context<'_>) -> Poll<Result<()>>,
+) -> Poll<Result<()>> {
+    if !*first_done {
+        match first(cx) {
+            Poll::Ready(Ok(())) => *first_done = true,
+            Poll::Ready(Err(err)) => {
+                *first_done = false;
+                *second_done = false;
+                return Poll::Ready(Err(err));
+            }
+            Poll::Pending => {}
+        }
+    }
+    if !*second_done {
+        match second(cx) {
+            Poll::Ready(Ok(())) => *second_done = true,
+            Poll::Ready(Err(err)) => {
+                *first_done = false;
+                *second_done = false;
+                return Poll::Ready(Err(err));
+            }
+            Poll::Pending => {}
+        }
+    }
+    if !(*first_done && *second_done) {
+        return Poll::Pending;
+    }
+    *first_done = false;
+    *second_done = false;
+    Poll::Ready(Ok(()))
+}
+
+/// Duplicates every write to the inner I/O object to a `secondary` writer.
+///
+/// A write only completes once both writers have accepted the data. The inner
+/// writer decides how many bytes are taken from the buffer, and those bytes
+/// are then written to the secondary writer in full, so the count reported
+/// to the caller never exceeds what either writer accepted. Like with any
+/// writer, a write that returned `Pending` has to be retried with the same
+/// data.
+///
+/// Errors of the inner writer are returned right away. Once the inner writer
+/// has accepted some data, the write reports it as written even if the
+/// secondary writer then fails, and the secondary writer's error is returned
+/// by the next write, flush or close instead, so retrying never hands the
+/// same bytes to the inner writer twice. The secondary writer misses the
+/// data of the write it failed on.
+#[pin_project]
+#[derive(Debug)]
+pub struct TeeWriteIO<T, W2> {
+    #[pin]
+    inner: T,
+    #[pin]
+    secondary: W2,
+    pending: Vec<u8>,
+    accepted: usize,
+    error: Option<Error>,
+    inner_done: bool,
+    secondary_done: bool,
+}
+
+impl<T, W2> TeeWriteIO<T, W2> {
+    /// Creates new [`TeeWriteIO`](crate::tee::TeeWriteIO) copying writes to
+    /// `secondary`.
+    pub fn new(inner: T, secondary: W2) -> Self {
+        TeeWriteIO {
+            inner,
+            secondary,
+            pending: Vec::new(),
+            accepted: 0,
+            error: None,
+            inner_done: false,
+            secondary_done: false,
+        }
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Provides access to the secondary writer.
+    pub fn secondary(&self) -> &W2 {
+        &self.secondary
+    }
+
+    /// Provides `mut` access to the secondary writer.
+    pub fn secondary_mut(&mut self) -> &mut W2 {
+        &mut self.secondary
+    }
+
+    /// Deconstructs `TeeWriteIO` into the inner I/O object and the secondary
+    /// writer.
+    pub fn into_inner(self) -> (T, W2) {
+        (self.inner, self.secondary)
+    }
+}
+
+impl<T, W2> AsyncRead for TeeWriteIO<T, W2>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T, W2> AsyncWrite for TeeWriteIO<T, W2>
+where
+    T: AsyncWrite,
+    W2: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        if *this.accepted == 0 {
+            let n = match this.inner.poll_write(cx, buf) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Ok(n)) => n,
+                poll => return poll,
+            };
+            let mut written = 0;
+            while written < n {
+                match this.secondary.as_mut().poll_write(cx, &buf[written..n]) {
+                    Poll::Ready(Ok(0)) => {
+                        *this.error = Some(Error::new(
+                            ErrorKind::WriteZero,
+                            "secondary writer accepted no data",
+                        ));
+                        break;
+                    }
+                    Poll::Ready(Ok(m)) => written += m,
+                    Poll::Ready(Err(err)) => {
+                        *this.error = Some(err);
+                        break;
+                    }
+                    Poll::Pending => {
+                        this.pending.extend_from_slice(&buf[written..n]);
+                        *this.accepted = n;
+                        return Poll::Pending;
+                    }
+                }
+            }
+            return Poll::Ready(Ok(n));
+        }
+
+        match poll_drain(this.secondary, cx, this.pending) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => {
+                this.pending.clear();
+                *this.error = Some(err);
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+        Poll::Ready(Ok(std::mem::take(this.accepted)))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        if let Poll::Ready(Err(err)) = poll_drain(this.secondary.as_mut(), cx, this.pending) {
+            return Poll::Ready(Err(err));
+        }
+        let drained = this.pending.is_empty();
+        let (inner, secondary) = (this.inner, this.secondary);
+        poll_both(
+            cx,
+            this.inner_done,
+            this.secondary_done,
+            |cx| inner.poll_flush(cx),
+            |cx| {
+                if drained {
+                    secondary.poll_flush(cx)
+                } else {
+                    Poll::Pending
+                }
+            },
+        )
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        if let Poll::Ready(Err(err)) = poll_drain(this.secondary.as_mut(), cx, this.pending) {
+            return Poll::Ready(Err(err));
+        }
+        let drained = this.pending.is_empty();
+        let (inner, secondary) = (this.inner, this.secondary);
+        poll_both(
+            cx,
+            this.inner_done,
+            this.secondary_done,
+            |cx| inner.poll_close(cx),
+            |cx| {
+                if drained {
+                    secondary.poll_close(cx)
+                } else {
+                    Poll::Pending
+                }
+            },
+        )
+    }
+}
+
+/// Reads from `R` and fans every write out to both `W1` and `W2`.
+///
+/// This is a [`TeeWriteIO`](crate::tee::TeeWriteIO) over
+/// `MergeIO<R, W1>` with `W2` as the secondary writer, so a write completes
+/// only once both writers have accepted the data, and the part the slower
+/// writer hasn't taken yet is buffered. Flushing and closing apply to both
+/// writers.
+#[pin_project]
+#[derive(Debug)]
+pub struct BroadcastWriteIO<R, W1, W2> {
+    #[pin]
+    inner: TeeWriteIO<MergeIO<R, W1>, W2>,
+}
+
+impl<R, W1, W2> BroadcastWriteIO<R, W1, W2> {
+    /// Creates new [`BroadcastWriteIO`](crate::tee::BroadcastWriteIO).
+    pub fn new(reader: R, writer1: W1, writer2: W2) -> Self {
+        BroadcastWriteIO {
+            inner: TeeWriteIO::new(MergeIO::new(reader, writer1), writer2),
+        }
+    }
+
+    /// Provides access to the reader.
+    pub fn reader(&self) -> &R {
+        self.inner.get_ref().reader()
+    }
+
+    /// Provides access to the first writer.
+    pub fn writer1(&self) -> &W1 {
+        self.inner.get_ref().writer()
+    }
+
+    /// Provides access to the second writer.
+    pub fn writer2(&self) -> &W2 {
+        self.inner.secondary()
+    }
+
+    /// Deconstructs `BroadcastWriteIO` into the reader and both writers.
+    pub fn into_inner(self) -> (R, W1, W2) {
+        let (merged, writer2) = self.inner.into_inner();
+        let (reader, writer1) = merged.into_inner();
+        (reader, writer1, writer2)
+    }
+}
+
+impl<R, W1, W2> AsyncRead for BroadcastWriteIO<R, W1, W2>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<R, W1, W2> AsyncWrite for BroadcastWriteIO<R, W1, W2>
+where
+    W1: AsyncWrite,
+    W2: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
@@ -0,0 +1,632 @@
+//! Scripted I/O objects for deterministic tests of code built on top of
+//! [`MergeIO`](crate::MergeIO).
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// A single scripted outcome of [`MockReader::poll_read`](futures_io::AsyncRead::poll_read).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PollResult {
+    /// Return `Pending`, waking the task right away.
+    Pending,
+    /// Return the data; if it doesn't fit into the read buffer, the rest is
+    /// returned by the following reads.
+    Data(Vec<u8>),
+    /// Return an error of the given kind.
+    Error(ErrorKind),
+    /// Return EOF.
+    Eof,
+}
+
+/// A reader that replays a script of [`PollResult`]s, one per `poll_read`.
+///
+/// Once the script is exhausted every read returns EOF.
+#[derive(Debug)]
+pub struct MockReader {
+    script: VecDeque<PollResult>,
+    calls: usize,
+}
+
+impl MockReader {
+    /// Creates new [`MockReader`](crate::test_utils::MockReader) replaying
+    /// `script`.
+    pub fn new(script: Vec<PollResult>) -> Self {
+        MockReader {
+            script: script.into(),
+            calls: 0,
+        }
+    }
+
+    /// Returns the number of `poll_read` calls so far.
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Panics if some of the script hasn't been replayed yet.
+    #[track_caller]
+    pub fn assert_exhausted(&self) {
+        assert!(
+            self.script.is_empty(),
+            "MockReader has unread entries: {:?}",
+            self.script
+        );
+    }
+}
+
+impl AsyncRead for MockReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.calls += 1;
+        match self.script.pop_front() {
+            None | Some(PollResult::Eof) => Poll::Ready(Ok(0)),
+            Some(PollResult::Pending) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Some(PollResult::Error(kind)) => {
+                Poll::Ready(Err(Error::new(kind, "scripted MockReader error")))
+            }
+            Some(PollResult::Data(mut data)) => {
+                let n = buf.len().min(data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                if n < data.len() {
+                    self.script.push_front(PollResult::Data(data.split_off(n)));
+                }
+                Poll::Ready(Ok(n))
+            }
+        }
+    }
+}
+
+/// A single scripted outcome of [`MockWriter::poll_write`](futures_io::AsyncWrite::poll_write).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteBehavior {
+    /// Accept the whole buffer.
+    Accept,
+    /// Accept at most the given number of bytes.
+    Partial(usize),
+    /// Return `Pending`, waking the task right away.
+    Pending,
+    /// Return an error of the given kind.
+    Error(ErrorKind),
+}
+
+/// A writer that records everything written to it and follows a script of
+/// [`WriteBehavior`]s, one per `poll_write`.
+///
+/// Once the script is exhausted every write is accepted in full.
+#[derive(Debug, Default)]
+pub struct MockWriter {
+    script: VecDeque<WriteBehavior>,
+    written: Vec<u8>,
+    calls: usize,
+    flushes: usize,
+    closed: bool,
+}
+
+impl MockWriter {
+    /// Creates new [`MockWriter`](crate::test_utils::MockWriter) accepting
+    /// all writes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new [`MockWriter`](crate::test_utils::MockWriter) following
+    /// `script`.
+    pub fn with_script(script: Vec<WriteBehavior>) -> Self {
+        MockWriter {
+            script: script.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns all the bytes accepted so far.
+    pub fn written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Returns the number of `poll_write` calls so far.
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Returns the number of completed flushes.
+    pub fn flushes(&self) -> usize {
+        self.flushes
+    }
+
+    /// Returns `true` if the writer has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Panics if some of the script hasn't been followed yet.
+    #[track_caller]
+    pub fn assert_exhausted(&self) {
+        assert!(
+            self.script.is_empty(),
+            "MockWriter has unused entries: {:?}",
+            self.script
+        );
+    }
+}
+
+impl AsyncWrite for MockWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.calls += 1;
+        let n = match self.script.pop_front() {
+            None | Some(WriteBehavior::Accept) => buf.len(),
+            Some(WriteBehavior::Partial(max)) => buf.len().min(max),
+            Some(WriteBehavior::Pending) => {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Some(WriteBehavior::Error(kind)) => {
+                return Poll::Ready(Err(Error::new(kind, "scripted MockWriter error")))
+            }
+        };
+        self.written.extend_from_slice(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.flushes += 1;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.closed = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Returns `Pending` from every operation without ever registering the
+/// waker, like a stream whose reactor is broken.
+///
+/// A task waiting on it is never woken.
+#[derive(Debug, Default)]
+pub struct WouldBlockIO {
+    polls: usize,
+}
+
+impl WouldBlockIO {
+    /// Creates new [`WouldBlockIO`](crate::test_utils::WouldBlockIO).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of times any operation was polled.
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+}
+
+impl AsyncRead for WouldBlockIO {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.polls += 1;
+        Poll::Pending
+    }
+}
+
+impl AsyncWrite for WouldBlockIO {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.polls += 1;
+        Poll::Pending
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.polls += 1;
+        Poll::Pending
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.polls += 1;
+        Poll::Pending
+    }
+}
+
+/// Returns `Pending` from every operation, remembering the waker of the
+/// last task that polled it until [`wake`](crate::test_utils::PendingIO::wake)
+/// is called.
+#[derive(Debug, Default)]
+pub struct PendingIO {
+    waker: Mutex<Option<Waker>>,
+    polls: usize,
+}
+
+impl PendingIO {
+    /// Creates new [`PendingIO`](crate::test_utils::PendingIO).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wakes the task that last polled this object, returning `false` if
+    /// there was no waker to wake.
+    pub fn wake(&self) -> bool {
+        match self.waker.lock().unwrap().take() {
+            Some(waker) => {
+                waker.wake();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of times any operation was polled.
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    fn poll_pending<T>(&mut self, cx: &mut Context<'_>) -> Poll<T> {
+        self.polls += 1;
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl AsyncRead for PendingIO {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.poll_pending(cx)
+    }
+}
+
+impl AsyncWrite for PendingIO {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        _buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.poll_pending(cx)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_pending(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_pending(cx)
+    }
+}
+
+/// Makes the first `unblock_after` calls to
+/// [`poll_write`](futures::io::AsyncWrite::poll_write) return `Pending`,
+/// simulating a write buffer that takes time to drain.
+///
+/// The task is woken right away every time, so callers handling `Pending`
+/// correctly just get polled again. Writes pass through untouched after that,
+/// as do reads, flushes and closes all along.
+#[pin_project]
+#[derive(Debug)]
+pub struct WouldBlockWriteIO<T> {
+    #[pin]
+    inner: T,
+    blocks_left: usize,
+}
+
+impl<T> WouldBlockWriteIO<T> {
+    /// Creates new [`WouldBlockWriteIO`](crate::test_utils::WouldBlockWriteIO).
+    pub fn new(inner: T, unblock_after: usize) -> Self {
+        WouldBlockWriteIO {
+            inner,
+            blocks_left: unblock_after,
+        }
+    }
+
+    /// Returns how many more writes are going to return `Pending`.
+    pub fn blocks_left(&self) -> usize {
+        self.blocks_left
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `WouldBlockWriteIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Returns whether the call has to block, counting it if so.
+fn would_block(blocks_left: &mut usize, cx: &mut Context<'_>) -> bool {
+    if *blocks_left == 0 {
+        return false;
+    }
+    *blocks_left -= 1;
+    cx.waker().wake_by_ref();
+    true
+}
+
+impl<T> AsyncRead for WouldBlockWriteIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for WouldBlockWriteIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        if would_block(this.blocks_left, cx) {
+            return Poll::Pending;
+        }
+        this.inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Makes the first `unblock_after` calls to
+/// [`poll_read`](futures::io::AsyncRead::poll_read) return `Pending`, the
+/// read side counterpart of
+/// [`WouldBlockWriteIO`](crate::test_utils::WouldBlockWriteIO).
+#[pin_project]
+#[derive(Debug)]
+pub struct WouldBlockReadIO<T> {
+    #[pin]
+    inner: T,
+    blocks_left: usize,
+}
+
+impl<T> WouldBlockReadIO<T> {
+    /// Creates new [`WouldBlockReadIO`](crate::test_utils::WouldBlockReadIO).
+    pub fn new(inner: T, unblock_after: usize) -> Self {
+        WouldBlockReadIO {
+            inner,
+            blocks_left: unblock_after,
+        }
+    }
+
+    /// Returns how many more reads are going to return `Pending`.
+    pub fn blocks_left(&self) -> usize {
+        self.blocks_left
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `WouldBlockReadIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for WouldBlockReadIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if would_block(this.blocks_left, cx) {
+            return Poll::Pending;
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<T> AsyncWrite for WouldBlockReadIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Defines a wrapper that delegates to the inner I/O object, except for the
+/// operations flagged `true`, which panic.
+macro_rules! panic_on {
+    (
+        $(#[$attr:meta])*
+        $name:ident { read: $read:expr, write: $write:expr, flush: $flush:expr, close: $close:expr }
+    ) => {
+        $(#[$attr])*
+        ///
+        /// Calls of the forbidden operations are counted before panicking,
+        /// and the counter outlives the wrapper, see
+        /// [`call_counter`](Self::call_counter).
+        #[pin_project]
+        #[derive(Debug)]
+        pub struct $name<T> {
+            #[pin]
+            inner: T,
+            message: String,
+            calls: Arc<AtomicUsize>,
+        }
+
+        impl<T> $name<T> {
+            #[doc = concat!("Creates new [`", stringify!($name), "`](crate::test_utils::", stringify!($name), ") panicking with `message`.")]
+            pub fn new(inner: T, message: &str) -> Self {
+                $name {
+                    inner,
+                    message: message.to_owned(),
+                    calls: Arc::default(),
+                }
+            }
+
+            /// Returns the number of calls of the forbidden operations.
+            pub fn calls(&self) -> usize {
+                self.calls.load(Ordering::SeqCst)
+            }
+
+            /// Returns the shared call counter, still readable after the
+            /// panic has dropped the wrapper.
+            pub fn call_counter(&self) -> Arc<AtomicUsize> {
+                self.calls.clone()
+            }
+
+            /// Provides access to the inner I/O object.
+            pub fn get_ref(&self) -> &T {
+                &self.inner
+            }
+
+            /// Provides `mut` access to the inner I/O object.
+            pub fn get_mut(&mut self) -> &mut T {
+                &mut self.inner
+            }
+
+            #[doc = concat!("Deconstructs `", stringify!($name), "` into the inner I/O object.")]
+            pub fn into_inner(self) -> T {
+                self.inner
+            }
+        }
+
+        impl<T> AsyncRead for $name<T>
+        where
+            T: AsyncRead,
+        {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<Result<usize>> {
+                let this = self.project();
+                if $read {
+                    forbidden(this.calls, this.message);
+                }
+                this.inner.poll_read(cx, buf)
+            }
+        }
+
+        impl<T> AsyncWrite for $name<T>
+        where
+            T: AsyncWrite,
+        {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<Result<usize>> {
+                let this = self.project();
+                if $write {
+                    forbidden(this.calls, this.message);
+                }
+                this.inner.poll_write(cx, buf)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                let this = self.project();
+                if $flush {
+                    forbidden(this.calls, this.message);
+                }
+                this.inner.poll_flush(cx)
+            }
+
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                let this = self.project();
+                if $close {
+                    forbidden(this.calls, this.message);
+                }
+                this.inner.poll_close(cx)
+            }
+        }
+    };
+}
+
+fn forbidden(calls: &AtomicUsize, message: &str) {
+    calls.fetch_add(1, Ordering::SeqCst);
+    panic!("{}", message);
+}
+
+panic_on! {
+    /// Panics if it's ever read from, for the half of a
+    /// [`MergeIO`](crate::MergeIO) a test expects to stay unused.
+    PanicOnReadIO { read: true, write: false, flush: false, close: false }
+}
+
+panic_on! {
+    /// Panics if it's ever written to or flushed, for the half of a
+    /// [`MergeIO`](crate::MergeIO) a test expects to stay unused.
+    PanicOnWriteIO { read: false, write: true, flush: true, close: false }
+}
+
+panic_on! {
+    /// Panics if it's ever flushed.
+    PanicOnFlushIO { read: false, write: false, flush: true, close: false }
+}
+
+panic_on! {
+    /// Panics if it's ever closed.
+    PanicOnCloseIO { read: false, write: false, flush: false, close: true }
+}
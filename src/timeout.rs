@@ -0,0 +1,243 @@
+//! Wrappers failing I/O operations that take too long.
+//!
+//! Deadlines are tracked with Tokio timers, so a Tokio runtime has to be
+//! running.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Sleep};
+
+/// Tracks how long an operation has been pending.
+///
+/// The timer is armed the first time the operation returns `Pending` and
+/// disarmed once it completes, so repeated polls of the same operation don't
+/// extend the deadline.
+#[derive(Debug)]
+struct Deadline {
+    timeout: Duration,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Deadline {
+    fn new(timeout: Duration) -> Self {
+        Deadline {
+            timeout,
+            sleep: None,
+        }
+    }
+
+    fn poll_op<T>(&mut self, cx: &mut Context<'_>, poll: Poll<Result<T>>) -> Poll<Result<T>> {
+        if poll.is_ready() {
+            self.sleep = None;
+            return poll;
+        }
+        let timeout = self.timeout;
+        let sleep = self.sleep.get_or_insert_with(|| Box::pin(sleep(timeout)));
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                self.sleep = None;
+                Poll::Ready(Err(Error::new(
+                    ErrorKind::TimedOut,
+                    "I/O operation timed out",
+                )))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Fails reads that stay pending for longer than the configured timeout with
+/// [`ErrorKind::TimedOut`](std::io::ErrorKind::TimedOut), created by
+/// [`MergeIO::read_timeout`](crate::MergeIO::read_timeout).
+///
+/// The stream remains usable after a timeout.
+#[pin_project]
+#[derive(Debug)]
+pub struct ReadTimeoutIO<T> {
+    #[pin]
+    inner: T,
+    deadline: Deadline,
+}
+
+/// Fails writes, flushes and closes that stay pending for longer than the
+/// configured timeout with
+/// [`ErrorKind::TimedOut`](std::io::ErrorKind::TimedOut), created by
+/// [`MergeIO::write_timeout`](crate::MergeIO::write_timeout).
+///
+/// The stream remains usable after a timeout.
+#[pin_project]
+#[derive(Debug)]
+pub struct WriteTimeoutIO<T> {
+    #[pin]
+    inner: T,
+    deadline: Deadline,
+}
+
+impl<T> ReadTimeoutIO<T> {
+    /// Creates new [`ReadTimeoutIO`](crate::timeout::ReadTimeoutIO).
+    pub fn new(inner: T, timeout: Duration) -> Self {
+        ReadTimeoutIO {
+            inner,
+            deadline: Deadline::new(timeout),
+        }
+    }
+
+    /// Returns the configured timeout.
+    pub fn timeout(&self) -> Duration {
+        self.deadline.timeout
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `ReadTimeoutIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> WriteTimeoutIO<T> {
+    /// Creates new [`WriteTimeoutIO`](crate::timeout::WriteTimeoutIO).
+    pub fn new(inner: T, timeout: Duration) -> Self {
+        WriteTimeoutIO {
+            inner,
+            deadline: Deadline::new(timeout),
+        }
+    }
+
+    /// Returns the configured timeout.
+    pub fn timeout(&self) -> Duration {
+        self.deadline.timeout
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `WriteTimeoutIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for ReadTimeoutIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        this.deadline.poll_op(cx, poll)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_vectored(cx, bufs);
+        this.deadline.poll_op(cx, poll)
+    }
+}
+
+impl<T> AsyncWrite for ReadTimeoutIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+impl<T> AsyncRead for WriteTimeoutIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T> AsyncWrite for WriteTimeoutIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        this.deadline.poll_op(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write_vectored(cx, bufs);
+        this.deadline.poll_op(cx, poll)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_flush(cx);
+        this.deadline.poll_op(cx, poll)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        this.deadline.poll_op(cx, poll)
+    }
+}
@@ -0,0 +1,147 @@
+//! [`tracing`](https://docs.rs/tracing) instrumentation.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::io::{IoSlice, IoSliceMut, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Emits a `tracing` event for every read and write poll.
+///
+/// Each poll runs inside a `merge_io` span carrying `stream_name`. Completed
+/// reads and writes emit a trace-level event with `direction` and `bytes`
+/// fields, polls returning `Pending` a debug-level one and errors, including
+/// those of flushes and closes, a warning.
+#[pin_project]
+#[derive(Debug)]
+pub struct TracedIO<T> {
+    #[pin]
+    inner: T,
+    name: &'static str,
+}
+
+impl<T> TracedIO<T> {
+    /// Creates new [`TracedIO`](crate::traced::TracedIO) reporting under
+    /// `name`.
+    pub fn new(inner: T, name: &'static str) -> Self {
+        TracedIO { inner, name }
+    }
+
+    /// Returns the name the stream is reported under.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `TracedIO` into the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Runs `poll` inside the span of `name` and reports its outcome.
+fn traced<T>(
+    name: &'static str,
+    direction: &'static str,
+    poll: impl FnOnce() -> Poll<Result<T>>,
+    bytes: impl FnOnce(&T) -> Option<usize>,
+) -> Poll<Result<T>> {
+    let span = tracing::trace_span!("merge_io", stream_name = name);
+    let _enter = span.enter();
+    let poll = poll();
+    match &poll {
+        Poll::Ready(Ok(value)) => {
+            if let Some(bytes) = bytes(value) {
+                tracing::trace!(direction, bytes, stream_name = name);
+            }
+        }
+        Poll::Ready(Err(err)) => {
+            tracing::warn!(direction, stream_name = name, error = %err);
+        }
+        Poll::Pending => {
+            tracing::debug!(direction, stream_name = name, "pending");
+        }
+    }
+    poll
+}
+
+impl<T> AsyncRead for TracedIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        traced(
+            this.name,
+            "read",
+            || this.inner.poll_read(cx, buf),
+            |&n| Some(n),
+        )
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        traced(
+            this.name,
+            "read",
+            || this.inner.poll_read_vectored(cx, bufs),
+            |&n| Some(n),
+        )
+    }
+}
+
+impl<T> AsyncWrite for TracedIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        traced(
+            this.name,
+            "write",
+            || this.inner.poll_write(cx, buf),
+            |&n| Some(n),
+        )
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        traced(
+            this.name,
+            "write",
+            || this.inner.poll_write_vectored(cx, bufs),
+            |&n| Some(n),
+        )
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        traced(this.name, "flush", || this.inner.poll_flush(cx), |()| None)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        traced(this.name, "close", || this.inner.poll_close(cx), |()| None)
+    }
+}
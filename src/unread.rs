@@ -0,0 +1,124 @@
+//! Pushing bytes back into a stream.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, IoSlice, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Allows pushing bytes back so that they're returned by the next reads,
+/// before any data from the inner I/O object.
+///
+/// Useful for parsers that read ahead and need to give back what they
+/// didn't consume.
+#[pin_project]
+#[derive(Debug)]
+pub struct UnreadIO<T> {
+    #[pin]
+    inner: T,
+    pushback: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl<T> UnreadIO<T> {
+    /// Creates new [`UnreadIO`](crate::unread::UnreadIO) holding at most
+    /// `capacity` pushed back bytes at a time.
+    pub fn new(inner: T, capacity: usize) -> Self {
+        UnreadIO {
+            inner,
+            pushback: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Pushes `bytes` back in front of any bytes pushed back earlier.
+    ///
+    /// Fails with [`ErrorKind::OutOfMemory`](std::io::ErrorKind::OutOfMemory)
+    /// without pushing anything back if the pushed back bytes would exceed
+    /// the capacity.
+    pub fn unread(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.capacity - self.pushback.len() < bytes.len() {
+            return Err(Error::new(
+                ErrorKind::OutOfMemory,
+                "pushback exceeds the capacity",
+            ));
+        }
+        for &byte in bytes.iter().rev() {
+            self.pushback.push_front(byte);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of pushed back bytes not yet read.
+    pub fn pushback_len(&self) -> usize {
+        self.pushback.len()
+    }
+
+    /// Returns the maximum number of pushed back bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Provides access to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Provides `mut` access to the inner I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deconstructs `UnreadIO` into the inner I/O object, dropping the
+    /// pushed back bytes.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> AsyncRead for UnreadIO<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.project();
+        if this.pushback.is_empty() {
+            return this.inner.poll_read(cx, buf);
+        }
+        let n = buf.len().min(this.pushback.len());
+        for (dst, src) in buf.iter_mut().zip(this.pushback.drain(..n)) {
+            *dst = src;
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<T> AsyncWrite for UnreadIO<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
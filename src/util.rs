@@ -0,0 +1,143 @@
+//! Helpers shared by the wrappers.
+
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The largest payload the framing wrappers put into a single frame.
+pub(crate) const MAX_PAYLOAD: usize = 64 * 1024;
+
+/// How much of a skipped frame is read at once.
+const SKIP_CHUNK: usize = 8 * 1024;
+
+/// Creates an [`ErrorKind::InvalidData`] error.
+pub(crate) fn invalid_data<E>(err: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Error::new(ErrorKind::InvalidData, err)
+}
+
+fn incomplete_frame() -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        "stream ended before a complete frame",
+    )
+}
+
+/// Writes out as much of `buf` as `writer` accepts without blocking, removing
+/// the written bytes from `buf`.
+pub(crate) fn poll_drain<W>(
+    mut writer: Pin<&mut W>,
+    cx: &mut Context<'_>,
+    buf: &mut Vec<u8>,
+) -> Poll<Result<()>>
+where
+    W: AsyncWrite + ?Sized,
+{
+    while !buf.is_empty() {
+        match writer.as_mut().poll_write(cx, buf) {
+            Poll::Ready(Ok(0)) => {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::WriteZero,
+                    "failed to write buffered data",
+                )))
+            }
+            Poll::Ready(Ok(n)) => {
+                buf.drain(..n);
+            }
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+    Poll::Ready(Ok(()))
+}
+
+/// Like [`poll_drain`], for buffers that are drained again before anything
+/// else is written: whatever `writer` doesn't accept right away, and any
+/// error, is left for that next drain.
+pub(crate) fn start_drain<W>(writer: Pin<&mut W>, cx: &mut Context<'_>, buf: &mut Vec<u8>)
+where
+    W: AsyncWrite + ?Sized,
+{
+    let _ = poll_drain(writer, cx, buf);
+}
+
+/// Reads frames made of a fixed-size header announcing the length of a
+/// payload, followed by that payload, keeping partial frames across polls.
+///
+/// Frames announcing a payload larger than the maximum fail with
+/// [`ErrorKind::InvalidData`] as soon as their header is complete, and their
+/// payload is discarded before the next frame is read, so the stream stays
+/// in sync.
+#[derive(Debug, Default)]
+pub(crate) struct FrameReader {
+    buf: Vec<u8>,
+    filled: usize,
+    skip: usize,
+}
+
+impl FrameReader {
+    /// Polls for the next complete frame, header included, returning `None`
+    /// on a clean EOF between frames.
+    ///
+    /// `payload_len` gets the complete header and returns the length of the
+    /// payload following it, or an error for an invalid header, which is
+    /// then returned again by every later poll.
+    pub(crate) fn poll_frame<R>(
+        &mut self,
+        mut reader: Pin<&mut R>,
+        cx: &mut Context<'_>,
+        header_len: usize,
+        max_payload: usize,
+        payload_len: impl Fn(&[u8]) -> Result<usize>,
+    ) -> Poll<Result<Option<&[u8]>>>
+    where
+        R: AsyncRead + ?Sized,
+    {
+        while self.skip > 0 {
+            let len = self.skip.min(SKIP_CHUNK);
+            if self.buf.len() < len {
+                self.buf.resize(len, 0);
+            }
+            match reader.as_mut().poll_read(cx, &mut self.buf[..len]) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(incomplete_frame())),
+                Poll::Ready(Ok(n)) => self.skip -= n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        loop {
+            let needed = if self.filled < header_len {
+                header_len
+            } else {
+                let len = payload_len(&self.buf[..header_len])?;
+                if len > max_payload {
+                    self.filled = 0;
+                    self.skip = len;
+                    return Poll::Ready(Err(invalid_data(
+                        "frame exceeds the maximum payload size",
+                    )));
+                }
+                header_len + len
+            };
+            if self.filled == needed {
+                self.filled = 0;
+                return Poll::Ready(Ok(Some(&self.buf[..needed])));
+            }
+            if self.buf.len() < needed {
+                self.buf.resize(needed, 0);
+            }
+            let range = self.filled..needed;
+            match reader.as_mut().poll_read(cx, &mut self.buf[range]) {
+                Poll::Ready(Ok(0)) if self.filled == 0 => return Poll::Ready(Ok(None)),
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(incomplete_frame())),
+                Poll::Ready(Ok(n)) => self.filled += n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+use std::time::Duration;
+
+use merge_io::abort::AbortableIO;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_abort_pending_read() -> Result<()> {
+    let (local, _remote) = merge_io::duplex(4);
+    let (mut stream, handle) = AbortableIO::new(local);
+    assert!(!handle.is_aborted());
+
+    let aborter = tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            handle.abort();
+        }
+    });
+
+    let mut buf = [0u8; 4];
+    let err = stream.read(&mut buf).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+    aborter.await.unwrap();
+
+    assert!(handle.is_aborted());
+    assert!(stream.is_aborted());
+    let err = stream.write_all(&[1]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+
+    Ok(())
+}
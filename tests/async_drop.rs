@@ -0,0 +1,132 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::channel::oneshot;
+use futures::io::{AsyncWrite, AsyncWriteExt, Cursor};
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use merge_io::async_drop::AsyncDropIO;
+use merge_io::test_utils::MockWriter;
+use merge_io::MergeIO;
+
+/// Holds written data back until flushed, publishing it to a shared buffer.
+#[derive(Debug, Default)]
+struct SharedWriter {
+    pending: Vec<u8>,
+    flushed: Arc<Mutex<Vec<u8>>>,
+    closes: Arc<AtomicUsize>,
+}
+
+impl AsyncWrite for SharedWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.pending.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = &mut *self;
+        this.flushed.lock().unwrap().append(&mut this.pending);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.closes.fetch_add(1, Ordering::SeqCst);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_flushes_on_drop() -> Result<()> {
+    let writer = SharedWriter::default();
+    let flushed = writer.flushed.clone();
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), writer);
+    let mut stream = AsyncDropIO::new(stream, tokio::runtime::Handle::current());
+
+    stream.write_all(b"hello").await?;
+    assert!(flushed.lock().unwrap().is_empty());
+    drop(stream);
+
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(*flushed.lock().unwrap(), b"hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_cleanup_after_close() -> Result<()> {
+    let writer = SharedWriter::default();
+    let closes = writer.closes.clone();
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), writer);
+    let mut stream = AsyncDropIO::new(stream, tokio::runtime::Handle::current());
+
+    stream.write_all(b"hello").await?;
+    stream.close().await?;
+    drop(stream);
+
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(closes.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_error_callback() -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), BrokenFlush);
+    let mut stream = AsyncDropIO::new(stream, tokio::runtime::Handle::current()).on_close_error(
+        move |err: Error| {
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(err.kind());
+            }
+        },
+    );
+
+    stream.write_all(b"data").await?;
+    drop(stream);
+
+    assert_eq!(rx.await.unwrap(), ErrorKind::BrokenPipe);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_into_inner_skips_cleanup() -> Result<()> {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+    let mut stream = AsyncDropIO::new(stream, tokio::runtime::Handle::current());
+
+    stream.write_all(b"data").await?;
+    let stream = stream.into_inner();
+    tokio::task::yield_now().await;
+    assert!(!stream.writer().is_closed());
+
+    Ok(())
+}
+
+/// Fails every flush.
+#[derive(Debug)]
+struct BrokenFlush;
+
+impl AsyncWrite for BrokenFlush {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Err(Error::new(ErrorKind::BrokenPipe, "flush failed")))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,104 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::base64::{Base64Alphabet, Base64Config, Base64IO};
+use merge_io::fault::PartialReadIO;
+use merge_io::MergeIO;
+
+async fn encode(config: Base64Config, data: &[u8]) -> Result<Vec<u8>> {
+    let mut stream = Base64IO::new(Vec::<u8>::new(), config);
+    stream.write_all(data).await?;
+    stream.flush().await?;
+    Ok(stream.into_inner())
+}
+
+async fn decode(config: Base64Config, encoded: Vec<u8>) -> Result<Vec<u8>> {
+    let reader = PartialReadIO::new(Cursor::new(encoded), 3);
+    let mut stream = Base64IO::new(MergeIO::new(reader, Vec::<u8>::new()), config);
+    let mut decoded = Vec::new();
+    stream.read_to_end(&mut decoded).await?;
+    Ok(decoded)
+}
+
+#[test]
+fn test_base64_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let data: Vec<u8> = (0..=255)
+            .chain((0..=255).rev())
+            .chain(vec![0xff, 0x00])
+            .collect();
+
+        for &alphabet in &[Base64Alphabet::Standard, Base64Alphabet::UrlSafe] {
+            for &padding in &[true, false] {
+                let config = Base64Config::new(alphabet, padding);
+                let encoded = encode(config, &data).await?;
+                assert!(encoded.iter().all(u8::is_ascii_graphic));
+                assert_eq!(encoded.ends_with(b"=="), padding);
+                assert_eq!(decode(config, encoded).await?, data);
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_base64_known_output() -> Result<()> {
+    executor::block_on(async {
+        let data = b"\xfb\xff\xfeab";
+        let standard = Base64Config::default();
+        let url_safe = Base64Config::new(Base64Alphabet::UrlSafe, false);
+        assert_eq!(encode(standard, data).await?, b"+//+YWI=");
+        assert_eq!(encode(url_safe, data).await?, b"-__-YWI");
+        Ok(())
+    })
+}
+
+#[test]
+fn test_base64_flush_partial_group() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = Base64IO::new(Vec::<u8>::new(), Base64Config::default());
+
+        stream.write_all(b"abcd").await?;
+        assert_eq!(stream.get_ref(), b"YWJj");
+        stream.flush().await?;
+        assert_eq!(stream.get_ref(), b"YWJjZA==");
+
+        // Padded groups may be followed by more data.
+        stream.write_all(b"ef").await?;
+        stream.close().await?;
+        let encoded = stream.into_inner();
+        assert_eq!(encoded, b"YWJjZA==ZWY=");
+        assert_eq!(decode(Base64Config::default(), encoded).await?, b"abcdef");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_base64_skips_whitespace() -> Result<()> {
+    executor::block_on(async {
+        let decoded = decode(Base64Config::default(), b"YWJj\r\nZGVm\n".to_vec()).await?;
+        assert_eq!(decoded, b"abcdef");
+        Ok(())
+    })
+}
+
+#[test]
+fn test_base64_invalid_input() {
+    executor::block_on(async {
+        let config = Base64Config::default();
+        for input in &[&b"YW*j"[..], b"YWJjY", b"Y===", b"YW=j", b"YWJj+"] {
+            let err = decode(config, input.to_vec()).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{:?}", input);
+        }
+
+        // The other alphabet's characters are rejected.
+        let url_safe = Base64Config::new(Base64Alphabet::UrlSafe, true);
+        let err = decode(url_safe, b"+/+/".to_vec()).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    })
+}
@@ -0,0 +1,94 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+
+use merge_io::MergeIO;
+
+#[test]
+fn test_limit_read_truncates() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer).limit_read(5);
+
+        let mut read_buf = [0u8; 8];
+        assert_eq!(stream.read(&mut read_buf).await?, 5);
+        assert_eq!(&read_buf[..5], &[1, 2, 3, 4, 5]);
+        assert_eq!(stream.remaining(), 0);
+
+        assert_eq!(stream.read(&mut read_buf).await?, 0);
+        assert_eq!(stream.get_ref().reader().position(), 5);
+
+        stream.write_all(&[10]).await?;
+        assert_eq!(stream.get_ref().writer(), &[10]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_limit_read_exact_boundary() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer).limit_read(4);
+
+        let mut read_buf = [0u8; 2];
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(stream.remaining(), 2);
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(read_buf, [3, 4]);
+        assert_eq!(stream.remaining(), 0);
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_limit_write_chunks() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(Vec::<u8>::new());
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer).limit_write(10);
+
+        stream.write_all(&[1, 2, 3, 4]).await?;
+        assert_eq!(stream.remaining_quota(), 6);
+        stream.write_all(&[5, 6, 7, 8]).await?;
+        assert_eq!(stream.write(&[9, 10, 11, 12]).await?, 2);
+        assert!(stream.quota_exhausted());
+
+        let err = stream.write(&[13]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::StorageFull);
+
+        assert_eq!(
+            stream.into_inner().into_inner().1,
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_limit_write_error_kind() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(Vec::<u8>::new());
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer)
+            .limit_write(2)
+            .with_error_kind(ErrorKind::WriteZero);
+
+        let err = stream.write_all(&[1, 2, 3]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WriteZero);
+        assert_eq!(stream.get_ref().writer(), &[1, 2]);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,127 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::buffered::{BufferedWriteIO, PrefetchReadIO};
+use merge_io::test_utils::{MockReader, MockWriter, PollResult};
+use merge_io::MergeIO;
+
+#[test]
+fn test_coalesces_small_writes() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+        let mut stream = BufferedWriteIO::new(stream, 64);
+
+        for i in 0..1000 {
+            assert_eq!(stream.write(&[i as u8]).await?, 1);
+        }
+        assert_eq!(stream.buffered_len(), 1000 % 64);
+        stream.flush().await?;
+        assert_eq!(stream.buffered_len(), 0);
+
+        let writer = stream.into_inner().into_inner().1;
+        assert_eq!(writer.calls(), 16);
+        let expected: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        assert_eq!(writer.written(), &expected[..]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_large_write_bypasses_buffer() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+        let mut stream = BufferedWriteIO::new(stream, 8);
+
+        stream.write_all(b"abc").await?;
+        stream.write_all(b"0123456789").await?;
+        assert_eq!(stream.buffered_len(), 0);
+
+        let writer = stream.get_ref().writer();
+        assert_eq!(writer.calls(), 2);
+        assert_eq!(writer.written(), b"abc0123456789");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_close_flushes() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+        let mut stream = BufferedWriteIO::new(stream, 64);
+
+        stream.write_all(b"hello").await?;
+        assert!(stream.get_ref().writer().written().is_empty());
+        stream.close().await?;
+        assert_eq!(stream.get_ref().writer().written(), b"hello");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_prefetch_serves_from_buffer() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Data(b"abc".to_vec()),
+            PollResult::Data(b"def".to_vec()),
+            PollResult::Pending,
+            PollResult::Data(b"ghi".to_vec()),
+        ]);
+        let stream = MergeIO::new(reader, MockWriter::new());
+        let mut stream = PrefetchReadIO::new(stream, 16);
+
+        let mut byte = [0u8; 1];
+        assert_eq!(stream.read(&mut byte).await?, 1);
+        assert_eq!(&byte, b"a");
+        assert_eq!(stream.buffered_len(), 5);
+        assert_eq!(stream.get_ref().reader().calls(), 3);
+
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert_eq!(&buf, b"bcde");
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"fghi");
+        stream.get_ref().reader().assert_exhausted();
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_prefetch_error_after_buffered_data() {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Data(b"abc".to_vec()),
+            PollResult::Error(ErrorKind::ConnectionReset),
+        ]);
+        let stream = MergeIO::new(reader, MockWriter::new());
+        let mut stream = PrefetchReadIO::new(stream, 16);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 3);
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+    })
+}
+
+#[test]
+fn test_prefetch_large_read_bypasses_buffer() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![PollResult::Data(vec![7; 32])]);
+        let stream = MergeIO::new(reader, MockWriter::new());
+        let mut stream = PrefetchReadIO::new(stream, 8);
+
+        let mut buf = [0u8; 32];
+        assert_eq!(stream.read(&mut buf).await?, 32);
+        assert_eq!(stream.buffered_len(), 0);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,40 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::capacity::{CapacityReportingIO, ReadCapacity, WriteCapacity};
+use merge_io::MergeIO;
+
+#[test]
+fn test_capacity_reporting() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1, 2, 3, 4, 5]), Vec::<u8>::new());
+        assert_eq!(stream.read_bytes_available(), Some(5));
+        assert_eq!(stream.write_bytes_available(), Some(usize::MAX));
+
+        let mut stream = CapacityReportingIO::new(stream);
+        let mut buf = [0u8; 2];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(stream.read_bytes_available(), Some(3));
+
+        let mut rest = vec![0u8; stream.read_bytes_available().unwrap()];
+        stream.read_exact(&mut rest).await?;
+        assert_eq!(rest, [3, 4, 5]);
+        assert_eq!(stream.read_bytes_available(), Some(0));
+
+        stream.write_all(b"data").await?;
+        assert_eq!(stream.get_ref().writer(), b"data");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_capacity_slice() {
+    let stream = MergeIO::new(&b"abc"[..], Cursor::new(Vec::<u8>::new()));
+    assert_eq!(stream.read_bytes_available(), Some(3));
+    assert_eq!(stream.write_bytes_available(), Some(usize::MAX));
+}
@@ -0,0 +1,33 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::chain::ChainReadIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_chain() -> Result<()> {
+    executor::block_on(async {
+        let first = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+        let mut stream = ChainReadIO::new(first, Cursor::new(vec![4, 5]));
+
+        let mut buf = [0u8; 2];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(buf, [1, 2]);
+        stream.write_all(&[10]).await?;
+        assert!(!stream.has_switched());
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(rest, [3, 4, 5]);
+        assert!(stream.has_switched());
+        stream.write_all(&[20]).await?;
+
+        let (first, _) = stream.into_inner();
+        assert_eq!(first.writer(), &[10, 20]);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,108 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::chunked::ChunkedIO;
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+fn reader(chunks: &[&[u8]]) -> MockReader {
+    let mut script = Vec::new();
+    for chunk in chunks {
+        script.push(PollResult::Data(chunk.to_vec()));
+        script.push(PollResult::Pending);
+    }
+    MockReader::new(script)
+}
+
+#[test]
+fn test_read_spans_multiple_polls() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(reader(&[b"ab", b"c", b"defg", b"h"]), MockWriter::new());
+        let mut stream = ChunkedIO::new(stream, 4);
+
+        let mut buf = [0u8; 16];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert_eq!(&buf[..4], b"abcd");
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert_eq!(&buf[..4], b"efgh");
+        assert_eq!(stream.read(&mut buf).await?, 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_read_smaller_than_chunk() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(reader(&[b"abcdef"]), MockWriter::new());
+        let mut stream = ChunkedIO::new(stream, 4);
+
+        let mut buf = [0u8; 3];
+        assert_eq!(stream.read(&mut buf).await?, 3);
+        assert_eq!(&buf, b"abc");
+        assert_eq!(stream.read(&mut buf).await?, 1);
+        assert_eq!(&buf[..1], b"d");
+        assert_eq!(stream.read(&mut buf).await?, 2);
+        assert_eq!(&buf[..2], b"ef");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_eof_mid_chunk() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(reader(&[b"abcd", b"ef"]), MockWriter::new());
+        let mut stream = ChunkedIO::new(stream, 4);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert_eq!(stream.read(&mut buf).await?, 2);
+        assert_eq!(&buf[..2], b"ef");
+        assert_eq!(stream.read(&mut buf).await?, 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_writes_whole_chunks() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(MockReader::new(vec![]), MockWriter::new());
+        let mut stream = ChunkedIO::new(stream, 4);
+
+        stream.write_all(b"ab").await?;
+        assert_eq!(stream.get_ref().writer().calls(), 0);
+        stream.write_all(b"cdefghij").await?;
+        assert_eq!(stream.get_ref().writer().calls(), 2);
+        stream.flush().await?;
+
+        let writer = stream.get_ref().writer();
+        assert_eq!(writer.calls(), 3);
+        assert_eq!(writer.written(), b"abcdefghij");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_partially_accepted_chunk() -> Result<()> {
+    executor::block_on(async {
+        let writer =
+            MockWriter::with_script(vec![WriteBehavior::Partial(2), WriteBehavior::Pending]);
+        let stream = MergeIO::new(MockReader::new(vec![]), writer);
+        let mut stream = ChunkedIO::new(stream, 4);
+
+        stream.write_all(b"abcdefgh").await?;
+
+        // The rest of the first chunk goes out on its own before the second.
+        let writer = stream.get_ref().writer();
+        assert_eq!(writer.calls(), 4);
+        assert_eq!(writer.written(), b"abcdefgh");
+
+        Ok(())
+    })
+}
@@ -0,0 +1,73 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use merge_io::circuit_breaker::{CircuitBreakerIO, CircuitState};
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+const LONG: Duration = Duration::from_secs(3600);
+
+#[test]
+fn test_opens_after_threshold() {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Error(ErrorKind::ConnectionReset),
+            PollResult::Data(vec![1]),
+            PollResult::Error(ErrorKind::ConnectionReset),
+            PollResult::Error(ErrorKind::ConnectionReset),
+            PollResult::Data(vec![2]),
+        ]);
+        let mut stream = CircuitBreakerIO::new(MergeIO::new(reader, MockWriter::new()), 2, LONG);
+        let mut buf = [0u8; 1];
+
+        // A success in between resets the count.
+        assert!(stream.read(&mut buf).await.is_err());
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 1);
+        assert!(stream.read(&mut buf).await.is_err());
+        assert_eq!(stream.state(), CircuitState::Closed);
+        assert!(stream.read(&mut buf).await.is_err());
+        assert_eq!(stream.state(), CircuitState::Open);
+
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+        let err = stream.write(&[1]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+        assert_eq!(stream.get_ref().reader().calls(), 4);
+        assert_eq!(stream.get_ref().writer().calls(), 0);
+
+        stream.force_reset();
+        assert_eq!(stream.state(), CircuitState::Closed);
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 1);
+        assert_eq!(buf, [2]);
+    })
+}
+
+#[test]
+fn test_half_open_probe() {
+    executor::block_on(async {
+        let writer = MockWriter::with_script(vec![
+            WriteBehavior::Error(ErrorKind::BrokenPipe),
+            WriteBehavior::Error(ErrorKind::BrokenPipe),
+            WriteBehavior::Accept,
+        ]);
+        let stream = MergeIO::new(MockReader::new(vec![]), writer);
+        let mut stream = CircuitBreakerIO::new(stream, 1, Duration::ZERO);
+
+        assert!(stream.write(&[1]).await.is_err());
+        assert_eq!(stream.state(), CircuitState::HalfOpen);
+
+        // A failed probe reopens the circuit...
+        assert!(stream.write(&[2]).await.is_err());
+        assert_eq!(stream.state(), CircuitState::HalfOpen);
+        assert_eq!(stream.get_ref().writer().calls(), 2);
+
+        // ... and a successful one closes it.
+        stream.write_all(&[3]).await.unwrap();
+        assert_eq!(stream.state(), CircuitState::Closed);
+        assert_eq!(stream.get_ref().writer().written(), [3]);
+    })
+}
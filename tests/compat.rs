@@ -0,0 +1,27 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::Cursor;
+use futures01::Future;
+use std::io::Result;
+
+use merge_io::MergeIO;
+
+fn echo_legacy<T>(stream: T) -> impl Future<Item = T, Error = std::io::Error>
+where
+    T: tokio_io::AsyncRead + tokio_io::AsyncWrite,
+{
+    tokio_io::io::read_exact(stream, [0u8; 5])
+        .and_then(|(stream, buf)| tokio_io::io::write_all(stream, buf))
+        .and_then(|(stream, _)| tokio_io::io::flush(stream))
+}
+
+#[test]
+fn test_into_compat() -> Result<()> {
+    let stream = MergeIO::new(Cursor::new(b"hello".to_vec()), Vec::<u8>::new());
+
+    let compat = echo_legacy(stream.into_compat()).wait()?;
+
+    let (_, written) = compat.into_inner().into_inner();
+    assert_eq!(written, b"hello");
+    Ok(())
+}
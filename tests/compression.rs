@@ -0,0 +1,86 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Read, Result};
+
+use merge_io::compression::{CompressedWriteIO, DecompressedReadIO};
+use merge_io::fault::PartialReadIO;
+use merge_io::MergeIO;
+
+fn structured_data() -> Vec<u8> {
+    (0..64 * 1024)
+        .flat_map(|i: u32| format!("record {:08}\n", i % 5000).into_bytes())
+        .collect()
+}
+
+#[test]
+fn test_compression_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let data = structured_data();
+
+        let mut writer = CompressedWriteIO::new(Vec::<u8>::new());
+        writer.write_all(&data).await?;
+        writer.close().await?;
+        assert_eq!(writer.total_in(), data.len() as u64);
+        let compressed = writer.into_inner();
+        assert!(compressed.len() < data.len() / 4);
+
+        // The output is a regular zlib stream.
+        let mut decoded = Vec::new();
+        flate2::read::ZlibDecoder::new(&compressed[..]).read_to_end(&mut decoded)?;
+        assert_eq!(decoded, data);
+
+        let reader = PartialReadIO::new(Cursor::new(compressed), 7);
+        let mut stream = MergeIO::new(DecompressedReadIO::new(reader), Vec::<u8>::new());
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_compression_flush_makes_data_readable() -> Result<()> {
+    executor::block_on(async {
+        let mut writer = CompressedWriteIO::new(Vec::<u8>::new());
+        writer.write_all(b"hello").await?;
+        writer.flush().await?;
+
+        // Without finishing the stream, everything flushed can be decoded.
+        let mut reader = DecompressedReadIO::new(Cursor::new(writer.get_ref().clone()));
+        let mut buf = [0u8; 5];
+        reader.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"hello");
+
+        writer.close().await?;
+        let err = writer.write(b"more").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_decompression_errors() {
+    executor::block_on(async {
+        let mut reader = DecompressedReadIO::new(Cursor::new(b"not zlib at all".to_vec()));
+        let mut read_buf = Vec::new();
+        let err = reader.read_to_end(&mut read_buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut writer = CompressedWriteIO::new(Vec::<u8>::new());
+        writer.write_all(&structured_data()).await.unwrap();
+        writer.close().await.unwrap();
+        let mut compressed = writer.into_inner();
+        compressed.truncate(compressed.len() / 2);
+
+        let mut reader = DecompressedReadIO::new(Cursor::new(compressed));
+        let err = reader.read_to_end(&mut read_buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let mut reader = DecompressedReadIO::new(Cursor::new(Vec::<u8>::new()));
+        assert_eq!(reader.read(&mut [0u8; 4]).await.unwrap(), 0);
+    })
+}
@@ -0,0 +1,71 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use merge_io::conditional::ConditionalIO;
+use merge_io::MergeIO;
+use std::io::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_conditional_read_gate() -> Result<()> {
+    let read_gate = Arc::new(AtomicBool::new(false));
+    let write_gate = Arc::new(AtomicBool::new(true));
+    let mut stream = ConditionalIO::new(
+        MergeIO::new(Cursor::new(b"data".to_vec()), Vec::<u8>::new()),
+        Arc::clone(&read_gate),
+        write_gate,
+    );
+    let handle = stream.handle();
+
+    // Writes go through while reads are held back.
+    stream.write_all(b"out").await?;
+    assert!(stream.read(&mut [0u8; 4]).now_or_never().is_none());
+
+    let reader = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        Ok::<_, std::io::Error>((stream, buf))
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!reader.is_finished());
+
+    handle.open_read();
+    assert!(read_gate.load(Ordering::SeqCst));
+    let (stream, buf) = tokio::time::timeout(Duration::from_secs(5), reader)
+        .await
+        .expect("opening the gate wakes the reader")
+        .unwrap()?;
+    assert_eq!(buf, b"data");
+    assert_eq!(stream.get_ref().writer(), b"out");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conditional_write_gate() -> Result<()> {
+    let read_gate = Arc::new(AtomicBool::new(true));
+    let write_gate = Arc::new(AtomicBool::new(true));
+    let mut stream = ConditionalIO::new(
+        MergeIO::new(futures::io::empty(), Vec::<u8>::new()),
+        read_gate,
+        Arc::clone(&write_gate),
+    );
+    let handle = stream.handle();
+
+    stream.write_all(b"first").await?;
+    handle.close_write();
+    assert!(stream.write(b"second").now_or_never().is_none());
+    assert!(stream.flush().now_or_never().is_none());
+
+    // Opening the gate directly works too, as long as the waiting task is
+    // woken afterwards.
+    write_gate.store(true, Ordering::SeqCst);
+    handle.wake();
+    stream.write_all(b"second").await?;
+    assert_eq!(stream.get_ref().writer(), b"firstsecond");
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::counted::CountedMergeIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_counted() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = CountedMergeIO::new(MergeIO::new(reader, writer));
+
+        let mut read_buf = [0u8; 2];
+        stream.read_exact(&mut read_buf).await?;
+        stream.write_all(&[10, 20, 30]).await?;
+        stream.read_exact(&mut read_buf).await?;
+        stream.write_all(&[40]).await?;
+
+        assert_eq!(stream.bytes_read(), 4);
+        assert_eq!(stream.bytes_written(), 4);
+
+        stream.reset_counters();
+        assert_eq!(stream.bytes_read(), 0);
+        assert_eq!(stream.bytes_written(), 0);
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(stream.bytes_read(), 2);
+
+        let inner = stream.into_inner();
+        assert_eq!(inner.writer(), &[10, 20, 30, 40]);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,75 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use merge_io::deferred::DeferredConnectIO;
+use merge_io::MergeIO;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_deferred_connects_on_first_read() -> Result<()> {
+    executor::block_on(async {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let factory_calls = Arc::clone(&calls);
+        let mut stream = DeferredConnectIO::new(move || {
+            factory_calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok(MergeIO::new(&b"hello"[..], Vec::<u8>::new())) }
+        });
+        assert!(!stream.is_connected());
+
+        // Flushing doesn't need a connection.
+        stream.flush().await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"hello");
+        assert!(stream.is_connected());
+
+        stream.write_all(b"world").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(stream.into_inner().unwrap().writer(), b"world");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_deferred_connects_on_first_write() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = DeferredConnectIO::new(|| async {
+            Ok(MergeIO::new(futures::io::empty(), Vec::<u8>::new()))
+        });
+        assert!(stream.get_ref().is_none());
+
+        stream.write_all(b"data").await?;
+        assert_eq!(stream.get_ref().unwrap().writer(), b"data");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_deferred_connect_error() {
+    executor::block_on(async {
+        let mut stream = DeferredConnectIO::new(|| async {
+            Err::<MergeIO<futures::io::Empty, Vec<u8>>, _>(Error::new(
+                ErrorKind::ConnectionRefused,
+                "refused",
+            ))
+        });
+
+        let err = stream.write_all(b"data").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+        assert!(!stream.is_connected());
+
+        // The factory is gone, so later calls fail without connecting again.
+        let mut buf = [0u8; 4];
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+        let err = stream.write_all(b"data").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+    })
+}
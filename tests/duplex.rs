@@ -0,0 +1,67 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::future::join;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use std::io::{ErrorKind, Result};
+
+#[test]
+fn test_duplex_both_directions() -> Result<()> {
+    executor::block_on(async {
+        let (mut a, mut b) = merge_io::duplex(4);
+
+        a.write_all(b"ping").await?;
+        let mut read_buf = [0u8; 4];
+        b.read_exact(&mut read_buf).await?;
+        assert_eq!(&read_buf, b"ping");
+
+        b.write_all(b"pong").await?;
+        a.read_exact(&mut read_buf).await?;
+        assert_eq!(&read_buf, b"pong");
+
+        a.close().await?;
+        let mut rest = Vec::new();
+        b.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_duplex_backpressure() -> Result<()> {
+    executor::block_on(async {
+        let (mut a, mut b) = merge_io::duplex(1);
+
+        // The channel holds one message plus one per sender.
+        a.write_all(&[1]).await?;
+        a.write_all(&[2]).await?;
+        assert!(a.write(&[3]).now_or_never().is_none());
+
+        let writer = async {
+            a.write_all(&[3]).await?;
+            a.close().await
+        };
+        let reader = async {
+            let mut read_buf = Vec::new();
+            b.read_to_end(&mut read_buf).await?;
+            Ok::<_, std::io::Error>(read_buf)
+        };
+        let (written, read_buf) = join(writer, reader).await;
+        written?;
+        assert_eq!(read_buf?, vec![1, 2, 3]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_duplex_peer_dropped() {
+    executor::block_on(async {
+        let (mut a, b) = merge_io::duplex(1);
+        drop(b);
+
+        let err = a.write_all(&[1]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    })
+}
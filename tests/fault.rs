@@ -0,0 +1,249 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::fault::{
+    CorruptingIO, ErrorAfterIO, PartialReadIO, PartialWriteIO, SlowStartWriteIO,
+};
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+#[test]
+fn test_partial_writes_arrive() -> Result<()> {
+    executor::block_on(async {
+        let data: Vec<u8> = (0..=255).cycle().take(64 * 1024).collect();
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+        let mut stream = PartialWriteIO::new(stream, 42, 0.5);
+
+        stream.write_all(&data).await?;
+        let writer = stream.get_ref().writer();
+        assert_eq!(writer.written(), &data[..]);
+        assert!(writer.calls() > 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_partial_writes_deterministic() -> Result<()> {
+    executor::block_on(async {
+        let mut calls = Vec::new();
+        for _ in 0..2 {
+            let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+            let mut stream = PartialWriteIO::new(stream, 7, 1.0);
+            stream.write_all(&[0; 4096]).await?;
+            calls.push(stream.get_ref().writer().calls());
+        }
+        assert_eq!(calls[0], calls[1]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_partial_reads_arrive() -> Result<()> {
+    executor::block_on(async {
+        let message: Vec<u8> = (0..=255).collect();
+        let reader = MockReader::new(vec![PollResult::Data(message.clone())]);
+        let stream = MergeIO::new(reader, MockWriter::new());
+        let mut stream = PartialReadIO::new(stream, 3);
+
+        let mut buf = vec![0u8; message.len()];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(buf, message);
+        assert!(stream.get_ref().reader().calls() > 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_error_after() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1; 16]), MockWriter::new());
+        let mut stream = ErrorAfterIO::new(stream, Some(6), Some(3), ErrorKind::ConnectionReset);
+
+        // The call crossing the threshold fails, after reading up to it.
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(stream.get_ref().reader().position(), 6);
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+
+        let err = stream.write(&[1, 2, 3, 4]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(stream.get_ref().writer().written(), &[1, 2, 3]);
+        assert!(stream.write(&[5]).await.is_err());
+        assert_eq!(stream.get_ref().writer().calls(), 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_error_after_exact_threshold() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1; 16]), MockWriter::new());
+        let mut stream = ErrorAfterIO::new(stream, Some(4), Some(4), ErrorKind::Other);
+
+        // Calls ending right at the threshold don't cross it.
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert!(stream.read(&mut buf).await.is_err());
+        assert_eq!(stream.write(&[1, 2, 3, 4]).await?, 4);
+        assert!(stream.write(&[5]).await.is_err());
+        assert_eq!(stream.get_ref().writer().written(), &[1, 2, 3, 4]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_error_after_never() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1; 16]), MockWriter::new());
+        let mut stream = ErrorAfterIO::new(stream, None, Some(0), ErrorKind::Other);
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf.len(), 16);
+        assert!(stream.write(&[1]).await.is_err());
+        assert_eq!(stream.get_ref().writer().calls(), 0);
+
+        Ok(())
+    })
+}
+
+fn corrupted(data: &[u8], bit_error_rate: f64, seed: u64) -> Result<Vec<u8>> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(data.to_vec()), Vec::<u8>::new());
+        let mut stream = CorruptingIO::new(stream, bit_error_rate, seed);
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        stream.write_all(data).await?;
+        // Reads and writes are corrupted independently.
+        assert_ne!(&read_buf, stream.get_ref().writer());
+        Ok(read_buf)
+    })
+}
+
+fn flipped_bits(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+#[test]
+fn test_corrupting_flips_bits_at_rate() -> Result<()> {
+    let data = vec![0u8; 10_000];
+    let read = corrupted(&data, 0.01, 7)?;
+    // 80000 bits at 1% should flip about 800 of them.
+    let flips = flipped_bits(&data, &read);
+    assert!((600..1000).contains(&flips), "{}", flips);
+
+    assert_eq!(read, corrupted(&data, 0.01, 7)?);
+    assert_ne!(read, corrupted(&data, 0.01, 8)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_corrupting_extremes() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![0x0F, 0xAA]), Vec::<u8>::new());
+        let mut stream = CorruptingIO::new(stream, 1.0, 1);
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, [0xF0, 0x55]);
+
+        let stream = MergeIO::new(Cursor::new(vec![0x0F, 0xAA]), Vec::<u8>::new());
+        let mut stream = CorruptingIO::new(stream, 0.0, 1);
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, [0x0F, 0xAA]);
+        stream.write_all(b"clean").await?;
+        assert_eq!(stream.get_ref().writer(), b"clean");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_corrupting_partial_writes_stay_consistent() -> Result<()> {
+    executor::block_on(async {
+        let data = vec![0u8; 4096];
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut whole = CorruptingIO::new(stream, 0.01, 3);
+        whole.write_all(&data).await?;
+
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut partial = CorruptingIO::new(PartialWriteIO::new(stream, 5, 0.3), 0.01, 3);
+        partial.write_all(&data).await?;
+
+        assert_eq!(
+            whole.get_ref().writer(),
+            partial.get_ref().get_ref().writer()
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_corrupting_drops() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(MockReader::new(Vec::new()), MockWriter::new());
+        let mut stream = CorruptingIO::new(stream, 0.0, 1).with_drop_probability(1.0);
+
+        assert_eq!(stream.read(&mut [0u8; 4]).await?, 0);
+        let err = stream.write(b"data").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(stream.get_ref().reader().calls(), 0);
+        assert_eq!(stream.get_ref().writer().calls(), 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_slow_start_converges_to_max_window() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = SlowStartWriteIO::new(Vec::<u8>::new(), 4, 100);
+        let data = vec![7u8; 1000];
+
+        let mut sizes = Vec::new();
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let n = stream.write(rest).await?;
+            sizes.push(n);
+            rest = &rest[n..];
+        }
+
+        assert_eq!(sizes[..6], [4, 8, 16, 32, 64, 100]);
+        assert!(sizes[5..sizes.len() - 1].iter().all(|&n| n == 100));
+        assert_eq!(stream.window(), stream.max_window());
+        assert_eq!(stream.into_inner(), data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_slow_start_counts_bytes_written() -> Result<()> {
+    executor::block_on(async {
+        let writer =
+            MockWriter::with_script(vec![WriteBehavior::Partial(1), WriteBehavior::Partial(3)]);
+        let mut stream = SlowStartWriteIO::new(writer, 8, 64);
+
+        assert_eq!(stream.write(&[0; 32]).await?, 1);
+        assert_eq!(stream.window(), 9);
+        assert_eq!(stream.write(&[0; 32]).await?, 3);
+        assert_eq!(stream.window(), 12);
+        assert_eq!(stream.write(&[0; 32]).await?, 12);
+        assert_eq!(stream.window(), 24);
+
+        Ok(())
+    })
+}
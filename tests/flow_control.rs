@@ -0,0 +1,107 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::future::join;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use merge_io::flow_control::WindowedFlowControlIO;
+use merge_io::test_utils::WouldBlockWriteIO;
+use std::io::Result;
+
+#[test]
+fn test_flow_control_transfer() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(4);
+        let mut a = WindowedFlowControlIO::new(a, 8);
+        let mut b = WindowedFlowControlIO::new(b, 8);
+
+        let data: Vec<u8> = (0..100).collect();
+        let send = async {
+            a.write_all(&data).await?;
+            a.close().await
+        };
+        let mut received = Vec::new();
+        let (sent, read) = join(send, b.read_to_end(&mut received)).await;
+        sent?;
+        read?;
+        assert_eq!(received, data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_flow_control_waits_for_ack() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let mut a = WindowedFlowControlIO::new(a, 8);
+        let mut b = WindowedFlowControlIO::new(b, 8);
+
+        assert_eq!(a.write(&[1; 10]).await?, 8);
+        assert_eq!(a.send_window(), 0);
+        assert!(a.write(b"x").now_or_never().is_none());
+
+        // Consuming half the window sends an acknowledgement.
+        let mut buf = [0u8; 4];
+        b.read_exact(&mut buf).await?;
+        assert_eq!(a.write(b"x").await?, 1);
+        assert_eq!(a.send_window(), 3);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_flow_control_keeps_data_read_while_waiting() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let mut a = WindowedFlowControlIO::new(a, 4);
+        let mut b = WindowedFlowControlIO::new(b, 4);
+
+        a.write_all(b"full").await?;
+        b.write_all(b"ok").await?;
+        b.flush().await?;
+        assert!(a.write(b"x").now_or_never().is_none());
+
+        let mut buf = [0u8; 4];
+        b.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"full");
+        assert_eq!(a.write(b"x").await?, 1);
+
+        let mut buf = [0u8; 2];
+        a.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"ok");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_flow_control_retries_ack_on_read() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let mut a = WindowedFlowControlIO::new(a, 8);
+        let mut b = WindowedFlowControlIO::new(WouldBlockWriteIO::new(b, 1), 8);
+
+        assert_eq!(a.write(&[1; 8]).await?, 8);
+
+        // The acknowledgement can't be written right away...
+        let mut buf = [0u8; 4];
+        b.read_exact(&mut buf).await?;
+        assert!(a.write(b"x").now_or_never().is_none());
+
+        // ...so the next read sends it, without the receiver ever writing.
+        let mut buf = [0u8; 2];
+        b.read_exact(&mut buf).await?;
+        let written = a.write(b"x").now_or_never().expect("no acknowledgement");
+        assert_eq!(written?, 1);
+        assert_eq!(a.send_window(), 3);
+
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "window must not be zero")]
+fn test_flow_control_zero_window() {
+    let _ = WindowedFlowControlIO::new(Vec::<u8>::new(), 0);
+}
@@ -0,0 +1,180 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use std::io::{ErrorKind, Result};
+
+use merge_io::framing::{FramedIO, LengthPrefix, PacketIO};
+use merge_io::test_utils::{MockReader, PollResult};
+use merge_io::MergeIO;
+
+#[test]
+fn test_frame_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(8);
+        let mut a = FramedIO::new(a, 1024);
+        let mut b = FramedIO::new(b, 1024);
+
+        a.write_frame(b"hello").await?;
+        a.write_frame(b"").await?;
+        a.write_frame(b"world").await?;
+
+        assert_eq!(b.read_frame().await?, b"hello");
+        assert_eq!(b.read_frame().await?, b"");
+        assert_eq!(b.read_frame().await?, b"world");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_frame_wire_format() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut framed = FramedIO::new(stream, 16);
+
+        framed.write_frame(&[7, 8, 9]).await?;
+        assert_eq!(framed.get_ref().writer(), &[0, 0, 0, 3, 7, 8, 9]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_split_header() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Data(vec![0, 0]),
+            PollResult::Pending,
+            PollResult::Data(vec![0, 2, 1, 2]),
+        ]);
+        let mut framed = FramedIO::new(MergeIO::new(reader, Vec::<u8>::new()), 16);
+
+        assert_eq!(framed.read_frame().await?, vec![1, 2]);
+        framed.get_ref().reader().assert_exhausted();
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_split_body() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Data(vec![0, 0, 0, 4, 1]),
+            PollResult::Data(vec![2]),
+            PollResult::Pending,
+            PollResult::Data(vec![3, 4, 0, 0, 0, 1]),
+            PollResult::Data(vec![5]),
+        ]);
+        let mut framed = FramedIO::new(MergeIO::new(reader, Vec::<u8>::new()), 16);
+
+        assert_eq!(framed.read_frame().await?, vec![1, 2, 3, 4]);
+        assert_eq!(framed.read_frame().await?, vec![5]);
+
+        let err = framed.read_frame().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_max_frame_size() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![0, 0, 1, 0]);
+        let mut framed = FramedIO::new(MergeIO::new(reader, Vec::<u8>::new()), 16);
+
+        let err = framed.read_frame().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = framed.write_frame(&[0u8; 17]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(framed.get_ref().writer().is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_frame_after_oversize_frame() -> Result<()> {
+    executor::block_on(async {
+        let mut data = vec![0, 0, 0, 20];
+        data.extend_from_slice(&[0; 20]);
+        data.extend_from_slice(&[0, 0, 0, 2, 1, 2]);
+        let reader = MockReader::new(vec![
+            PollResult::Data(data[..10].to_vec()),
+            PollResult::Pending,
+            PollResult::Data(data[10..].to_vec()),
+        ]);
+        let mut framed = FramedIO::new(MergeIO::new(reader, Vec::<u8>::new()), 16);
+
+        // The oversize payload is skipped, so the next frame is intact.
+        let err = framed.read_frame().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(framed.read_frame().await?, vec![1, 2]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_packet_round_trip() -> Result<()> {
+    executor::block_on(async {
+        for &prefix in &[LengthPrefix::U8, LengthPrefix::U16, LengthPrefix::U32] {
+            let (a, b) = merge_io::duplex(3);
+            let mut a = PacketIO::new(a, prefix, 200);
+            let mut b = PacketIO::new(b, prefix, 200);
+
+            let big = vec![7u8; 200];
+            let send = async {
+                a.send_packet(b"hi").await?;
+                a.send_packet(b"").await?;
+                a.send_packet(&big).await
+            };
+            let recv = async {
+                let packets = (
+                    b.recv_packet().await?,
+                    b.recv_packet().await?,
+                    b.recv_packet().await?,
+                );
+                Ok::<_, std::io::Error>(packets)
+            };
+            let ((), (first, second, third)) = futures::try_join!(send, recv)?;
+            assert_eq!(first, b"hi");
+            assert!(second.is_empty());
+            assert_eq!(third, big);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_packet_wire_format() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut packets = PacketIO::new(stream, LengthPrefix::U16, 1024);
+        packets.send_packet(b"abc").await?;
+        assert_eq!(packets.get_ref().writer(), &[0, 3, b'a', b'b', b'c']);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_packet_size_limits() {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![5, 1, 2, 3, 4, 5]), Vec::<u8>::new());
+        let mut packets = PacketIO::new(stream, LengthPrefix::U8, 4);
+
+        let err = packets.send_packet(&[0; 5]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = packets.recv_packet().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        assert_eq!(
+            PacketIO::new(stream, LengthPrefix::U8, 1024).max_packet_size(),
+            255
+        );
+    })
+}
@@ -0,0 +1,56 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::hexdump::HexDumpIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_dump() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"hi\n".to_vec()), Vec::<u8>::new());
+        let mut stream = HexDumpIO::new(stream, Vec::new());
+
+        stream.write_all(b"Hello, world! 0123456789").await?;
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+
+        let (_, output) = stream.into_inner();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "> 00000000: 4865 6c6c 6f2c 2077 6f72 6c64 2120 3031  Hello, world! 01\n\
+             > 00000010: 3233 3435 3637 3839                      23456789\n\
+             < 00000000: 6869 0a                                  hi.\n"
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_line_width() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut stream = HexDumpIO::new(stream, Vec::new()).with_line_width(32);
+
+        stream.write_all(&[0xab; 40]).await?;
+
+        let (_, output) = stream.into_inner();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("> 00000000: abab"));
+        assert!(lines[1].starts_with("> 00000020: abab"));
+
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_line_width() {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+    let _ = HexDumpIO::new(stream, Vec::<u8>::new()).with_line_width(8);
+}
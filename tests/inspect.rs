@@ -0,0 +1,40 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::MergeIO;
+
+#[test]
+fn test_inspect() -> Result<()> {
+    executor::block_on(async {
+        let mut seen_reads = Vec::new();
+        let mut seen_writes = Vec::new();
+
+        {
+            let reader = Cursor::new(vec![1, 2, 3, 4]);
+            let writer: Vec<u8> = vec![];
+            let mut stream = MergeIO::new(reader, writer)
+                .inspect_read(|data: &[u8]| seen_reads.push(data.to_vec()))
+                .inspect_write(|data: &[u8]| seen_writes.extend_from_slice(data));
+
+            let mut read_buf = [0u8; 3];
+            stream.read_exact(&mut read_buf).await?;
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).await?;
+
+            stream.write_all(&[10, 20]).await?;
+            stream.write_all(&[30]).await?;
+            stream.write_all(&[]).await?;
+
+            assert_eq!(stream.get_ref().writer(), &[10, 20, 30]);
+        }
+
+        assert_eq!(seen_reads, vec![vec![1, 2, 3], vec![4]]);
+        assert_eq!(seen_writes, vec![10, 20, 30]);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,115 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::integrity::CrcCheckedIO;
+use merge_io::test_utils::{MockReader, PollResult};
+use merge_io::MergeIO;
+
+async fn encode(chunks: &[&[u8]]) -> Result<Vec<u8>> {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+    let mut checked = CrcCheckedIO::new(stream);
+    for chunk in chunks {
+        checked.write_all(chunk).await?;
+    }
+    checked.flush().await?;
+    Ok(checked.into_inner().into_inner().1)
+}
+
+#[test]
+fn test_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let wire = encode(&[b"hello", b" ", b"world"]).await?;
+        assert_eq!(wire.len(), 3 * 8 + 11);
+
+        let stream = MergeIO::new(Cursor::new(wire), Vec::<u8>::new());
+        let mut checked = CrcCheckedIO::new(stream);
+        let mut buf = Vec::new();
+        checked.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"hello world");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_flipped_bit_is_detected() -> Result<()> {
+    executor::block_on(async {
+        let mut wire = encode(&[b"payload"]).await?;
+        wire[6] ^= 0b0001_0000;
+
+        let stream = MergeIO::new(Cursor::new(wire), Vec::<u8>::new());
+        let mut checked = CrcCheckedIO::new(stream);
+        let mut buf = Vec::new();
+        let err = checked.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(buf.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_frame_split_across_reads() -> Result<()> {
+    executor::block_on(async {
+        let wire = encode(&[b"abc"]).await?;
+        let reader = MockReader::new(vec![
+            PollResult::Data(wire[..2].to_vec()),
+            PollResult::Pending,
+            PollResult::Data(wire[2..6].to_vec()),
+            PollResult::Data(wire[6..].to_vec()),
+        ]);
+        let mut checked = CrcCheckedIO::new(MergeIO::new(reader, Vec::<u8>::new()));
+
+        let mut buf = [0u8; 2];
+        checked.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"ab");
+        checked.read_exact(&mut buf[..1]).await?;
+        assert_eq!(&buf[..1], b"c");
+        assert_eq!(checked.read(&mut buf).await?, 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_max_frame_size() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut checked = CrcCheckedIO::new(stream).with_max_frame_size(4);
+        checked.write_all(b"abcdef").await?;
+        checked.flush().await?;
+        let wire = checked.into_inner().into_inner().1;
+        assert_eq!(wire.len(), 2 * 8 + 6);
+
+        let stream = MergeIO::new(Cursor::new(wire), Vec::<u8>::new());
+        let mut checked = CrcCheckedIO::new(stream).with_max_frame_size(2);
+        let mut buf = Vec::new();
+        let err = checked.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // The oversize frame is skipped, the next one still arrives.
+        checked.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"ef");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_truncated_frame() -> Result<()> {
+    executor::block_on(async {
+        let mut wire = encode(&[b"abc"]).await?;
+        wire.pop();
+
+        let stream = MergeIO::new(Cursor::new(wire), Vec::<u8>::new());
+        let mut checked = CrcCheckedIO::new(stream);
+        let mut buf = Vec::new();
+        let err = checked.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        Ok(())
+    })
+}
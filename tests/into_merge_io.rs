@@ -0,0 +1,43 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::IntoMergeIO;
+
+async fn echo_once(stream: impl IntoMergeIO) -> Result<()> {
+    let mut stream = stream.into_merge_io();
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf).await?;
+    stream.write_all(&buf).await?;
+    stream.flush().await
+}
+
+#[test]
+fn test_tuple() -> Result<()> {
+    futures::executor::block_on(async {
+        let mut writer = Vec::new();
+        echo_once((Cursor::new(b"ping".to_vec()), &mut writer)).await?;
+        assert_eq!(writer, b"ping");
+        Ok(())
+    })
+}
+
+#[tokio::test]
+async fn test_tcp_stream() -> Result<()> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        echo_once(stream).await
+    });
+
+    let mut client = tokio::net::TcpStream::connect(addr).await?.into_merge_io();
+    client.write_all(b"ping").await?;
+    let mut buf = [0u8; 4];
+    client.read_exact(&mut buf).await?;
+    assert_eq!(&buf, b"ping");
+
+    server.await.unwrap()
+}
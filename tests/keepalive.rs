@@ -0,0 +1,72 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use merge_io::keepalive::KeepaliveIO;
+use std::io::Result;
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_keepalive_sends_heartbeat_when_idle() -> Result<()> {
+    let (a, mut b) = merge_io::duplex(16);
+    let mut a = KeepaliveIO::new(a, Duration::from_secs(1), b"PING".to_vec());
+
+    a.write_all(b"hello").await?;
+    // Nothing arrives, but waiting for it keeps the stream polled while the
+    // interval elapses.
+    let mut buf = [0u8; 4];
+    let waited = tokio::time::timeout(Duration::from_millis(1500), a.read(&mut buf)).await;
+    assert!(waited.is_err());
+    a.write_all(b"world").await?;
+    a.close().await?;
+
+    let mut raw = Vec::new();
+    b.read_to_end(&mut raw).await?;
+    assert_eq!(raw, b"helloPINGworld");
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keepalive_no_heartbeat_while_busy() -> Result<()> {
+    let (a, mut b) = merge_io::duplex(16);
+    let mut a = KeepaliveIO::new(a, Duration::from_secs(1), b"PING".to_vec());
+
+    let mut buf = [0u8; 4];
+    for _ in 0..3 {
+        a.write_all(b"tick").await?;
+        let waited = tokio::time::timeout(Duration::from_millis(600), a.read(&mut buf)).await;
+        assert!(waited.is_err());
+    }
+    a.close().await?;
+
+    let mut raw = Vec::new();
+    b.read_to_end(&mut raw).await?;
+    assert_eq!(raw, b"tickticktick");
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keepalive_consumes_heartbeats() -> Result<()> {
+    let (mut a, b) = merge_io::duplex(16);
+    let mut b = KeepaliveIO::new(b, Duration::from_secs(1), b"PING".to_vec());
+
+    a.write_all(b"PINGxxPI").await?;
+    a.write_all(b"NGyy").await?;
+    a.write_all(b"PIZPIN").await?;
+    a.close().await?;
+
+    // A heartbeat split across reads is still dropped, while a partial one
+    // is returned as regular data.
+    let mut data = Vec::new();
+    b.read_to_end(&mut data).await?;
+    assert_eq!(data, b"xxyyPIZPIN");
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "heartbeat payload must not be empty")]
+fn test_keepalive_empty_payload() {
+    let _ = KeepaliveIO::new(Vec::<u8>::new(), Duration::from_secs(1), Vec::new());
+}
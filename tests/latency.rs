@@ -0,0 +1,52 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use merge_io::latency::{JitteredLatencyIO, LatencyIO};
+use merge_io::MergeIO;
+
+#[tokio::test(start_paused = true)]
+async fn test_latency_per_call() -> Result<()> {
+    let stream = MergeIO::new(Cursor::new(vec![0u8; 4096]), Vec::<u8>::new());
+    let mut stream = LatencyIO::new(stream, Duration::from_millis(50), Duration::from_millis(20));
+
+    let start = Instant::now();
+    let mut buf = [0u8; 4096];
+    stream.read_exact(&mut buf).await?;
+    assert_eq!(start.elapsed(), Duration::from_millis(50));
+
+    let start = Instant::now();
+    stream.write_all(&[1]).await?;
+    stream.write_all(&[2; 4096]).await?;
+    assert_eq!(start.elapsed(), Duration::from_millis(40));
+    assert_eq!(stream.get_ref().writer().len(), 4097);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_jittered_latency() -> Result<()> {
+    let min = Duration::from_millis(10);
+    let max = Duration::from_millis(30);
+
+    let mut totals = Vec::new();
+    for _ in 0..2 {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut stream = JitteredLatencyIO::new(stream, min, max, 99);
+
+        let start = Instant::now();
+        for _ in 0..10 {
+            let before = Instant::now();
+            stream.write_all(&[1]).await?;
+            let latency = before.elapsed();
+            assert!(min <= latency && latency <= max, "{:?}", latency);
+        }
+        totals.push(start.elapsed());
+    }
+    assert_eq!(totals[0], totals[1]);
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::layer::{IoLayer, LayeredIOBuilder, LogLayer};
+use merge_io::stats::StatsIO;
+use merge_io::MergeIO;
+
+#[derive(Debug)]
+struct StatsLayer;
+
+impl<I> IoLayer<I> for StatsLayer
+where
+    I: futures::io::AsyncRead + futures::io::AsyncWrite,
+{
+    type Output = StatsIO<I>;
+
+    fn wrap(self, inner: I) -> Self::Output {
+        StatsIO::new(inner)
+    }
+}
+
+#[test]
+fn test_layers_are_applied_in_order() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+        let mut stream = LayeredIOBuilder::new(stream)
+            .layer(LogLayer::new(Vec::<u8>::new()))
+            .layer(StatsLayer)
+            .build();
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        stream.write_all(&[4, 5]).await?;
+        stream.flush().await?;
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(stream.stats().bytes_read, 3);
+        assert_eq!(stream.stats().bytes_written, 2);
+
+        let (merged, log) = stream.into_inner().into_inner();
+        assert_eq!(merged.writer(), &[4, 5]);
+        assert_eq!(
+            String::from_utf8(log).unwrap(),
+            "read: 3 bytes\nread: 0 bytes\nwrite: 2 bytes\nflush: ok\n"
+        );
+
+        Ok(())
+    })
+}
@@ -0,0 +1,21 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use static_assertions::const_assert_eq;
+use std::io::Cursor;
+use std::mem::size_of;
+
+use merge_io::counted::CountedMergeIO;
+use merge_io::MergeIO;
+
+// `MergeIO` adds nothing on top of its halves.
+const_assert_eq!(
+    size_of::<MergeIO<Cursor<Vec<u8>>, Vec<u8>>>(),
+    size_of::<Cursor<Vec<u8>>>() + size_of::<Vec<u8>>()
+);
+const_assert_eq!(size_of::<MergeIO<(), ()>>(), 0);
+
+// `CountedMergeIO` stores two `u64` counters.
+const_assert_eq!(
+    size_of::<CountedMergeIO<Cursor<Vec<u8>>, Vec<u8>>>(),
+    size_of::<MergeIO<Cursor<Vec<u8>>, Vec<u8>>>() + 2 * size_of::<u64>()
+);
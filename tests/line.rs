@@ -0,0 +1,30 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, Cursor};
+use std::io::Result;
+
+use merge_io::MergeIO;
+
+#[test]
+fn test_read_lines_and_write() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(b"first\nsecond\nrest".to_vec());
+        let mut stream = MergeIO::new(reader, Vec::<u8>::new()).with_line_reader();
+
+        assert_eq!(stream.read_line().await?, "first\n");
+        stream.write_all(b"ack 1\n").await?;
+        assert_eq!(stream.read_line().await?, "second\n");
+        stream.write_all(b"ack 2\n").await?;
+
+        // Raw reads continue from the buffered position.
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"rest");
+        assert_eq!(stream.read_line().await?, "");
+
+        assert_eq!(stream.get_ref().writer(), b"ack 1\nack 2\n");
+
+        Ok(())
+    })
+}
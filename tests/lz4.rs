@@ -0,0 +1,61 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::lz4::LZ4IO;
+use merge_io::MergeIO;
+
+fn structured_data() -> Vec<u8> {
+    (0..1024 * 1024 / 16)
+        .flat_map(|i: u32| format!("record {:08}\n", i % 5000).into_bytes())
+        .take(1024 * 1024)
+        .collect()
+}
+
+#[test]
+fn test_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let data = structured_data();
+
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut compressor = LZ4IO::new(stream);
+        compressor.write_all(&data).await?;
+        compressor.flush().await?;
+        let compressed = compressor.into_inner().into_inner().1;
+        // Every 64 KiB chunk is compressed on its own and holds 4096 distinct
+        // records, so only what the records have in common shrinks, leaving
+        // about a quarter of the input.
+        assert!(compressed.len() < data.len() / 3);
+
+        let stream = MergeIO::new(Cursor::new(compressed), Vec::<u8>::new());
+        let mut decompressor = LZ4IO::new(stream);
+        let mut decompressed = Vec::new();
+        decompressor.read_to_end(&mut decompressed).await?;
+        assert!(decompressed == data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_corrupt_input() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut compressor = LZ4IO::new(stream);
+        compressor.write_all(&structured_data()[..4096]).await?;
+        compressor.flush().await?;
+        let mut compressed = compressor.into_inner().into_inner().1;
+        let len = compressed.len();
+        compressed[len - 20..].iter_mut().for_each(|b| *b = 0xff);
+
+        let stream = MergeIO::new(Cursor::new(compressed), Vec::<u8>::new());
+        let mut decompressor = LZ4IO::new(stream);
+        let mut buf = Vec::new();
+        let err = decompressor.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        Ok(())
+    })
+}
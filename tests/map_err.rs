@@ -0,0 +1,74 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use merge_io::MergeIO;
+
+#[derive(Debug)]
+struct Broken;
+
+impl AsyncRead for Broken {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Poll::Ready(Err(Error::other("broken")))
+    }
+}
+
+impl AsyncWrite for Broken {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(Err(Error::other("broken")))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn upstream(err: Error) -> Error {
+    Error::new(err.kind(), format!("upstream: {}", err))
+}
+
+#[test]
+fn test_map_read_err() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = MergeIO::new(Broken, Vec::<u8>::new()).map_read_err(upstream);
+
+        let err = stream.read(&mut [0u8; 4]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(err.to_string(), "upstream: broken");
+
+        stream.write_all(&[1, 2]).await?;
+        assert_eq!(stream.into_inner().writer(), &[1, 2]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_map_write_err() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2]);
+        let mut stream = MergeIO::new(reader, Broken).map_write_err(upstream);
+
+        let err = stream.write(&[1]).await.unwrap_err();
+        assert_eq!(err.to_string(), "upstream: broken");
+
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, vec![1, 2]);
+
+        Ok(())
+    })
+}
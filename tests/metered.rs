@@ -0,0 +1,96 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use std::io::{ErrorKind, Result};
+
+type Snapshot = Vec<(metrics::Key, DebugValue)>;
+
+use merge_io::metered::MeteredIO;
+use merge_io::test_utils::{MockWriter, WriteBehavior};
+use merge_io::MergeIO;
+
+fn snapshot(snapshotter: &Snapshotter) -> Snapshot {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| (key.key().clone(), value))
+        .collect()
+}
+
+fn value<'a>(snapshot: &'a Snapshot, name: &str, direction: &str) -> Option<&'a DebugValue> {
+    snapshot
+        .iter()
+        .find(|(key, _)| {
+            key.name() == name
+                && key
+                    .labels()
+                    .any(|label| label.key() == "direction" && label.value() == direction)
+        })
+        .map(|(_, value)| value)
+}
+
+#[test]
+fn test_metered_counters() -> Result<()> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let writer = MockWriter::with_script(vec![WriteBehavior::Error(ErrorKind::ConnectionReset)]);
+    let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), writer);
+    let mut stream = metrics::with_local_recorder(&recorder, || MeteredIO::new(stream, "conn"));
+    assert_eq!(stream.prefix(), "conn");
+
+    executor::block_on(async {
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        let err = stream.write_all(b"hello").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        stream.write_all(b"hello").await?;
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    let snapshot = snapshot(&snapshotter);
+    let counter = |name, direction| match value(&snapshot, name, direction) {
+        Some(DebugValue::Counter(n)) => *n,
+        value => panic!("unexpected value for {}: {:?}", name, value),
+    };
+    assert_eq!(counter("conn.bytes_read", "read"), 3);
+    assert_eq!(counter("conn.bytes_written", "write"), 5);
+    assert_eq!(counter("conn.errors", "write"), 1);
+    assert_eq!(counter("conn.errors", "read"), 0);
+    assert!(value(&snapshot, "conn.poll_bytes", "read").is_none());
+    Ok(())
+}
+
+#[test]
+fn test_metered_histograms() -> Result<()> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let stream = MergeIO::new(Cursor::new(vec![0; 10]), Vec::<u8>::new());
+    let mut stream = metrics::with_local_recorder(&recorder, || {
+        MeteredIO::new(stream, "conn").with_histograms()
+    });
+
+    executor::block_on(async {
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await?;
+        stream.write_all(b"ab").await?;
+        stream.write_all(b"cde").await?;
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    let snapshot = snapshot(&snapshotter);
+    match value(&snapshot, "conn.poll_bytes", "write") {
+        Some(DebugValue::Histogram(values)) => {
+            let values: Vec<f64> = values.iter().map(|v| v.into_inner()).collect();
+            assert_eq!(values, [2.0, 3.0]);
+        }
+        value => panic!("unexpected value: {:?}", value),
+    }
+    match value(&snapshot, "conn.poll_bytes", "read") {
+        Some(DebugValue::Histogram(values)) => assert_eq!(values.len(), 1),
+        value => panic!("unexpected value: {:?}", value),
+    }
+    Ok(())
+}
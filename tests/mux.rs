@@ -0,0 +1,127 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use merge_io::mux::MuxIO;
+use std::io::{ErrorKind, Result};
+
+#[test]
+fn test_mux_channels_are_independent() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let a = MuxIO::new(a);
+        let b = MuxIO::new(b);
+
+        let mut a1 = a.open_channel(1);
+        let mut a2 = a.open_channel(2);
+        a1.write_all(b"one").await?;
+        a2.write_all(b"two").await?;
+        a2.flush().await?;
+
+        // Read out of order: the data for channel 1 has to wait in its
+        // buffer while channel 2 is read.
+        let mut buf = [0u8; 3];
+        b.open_channel(2).read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"two");
+        b.open_channel(1).read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"one");
+
+        let mut b1 = b.open_channel(1);
+        b1.write_all(b"back").await?;
+        b1.flush().await?;
+        let mut buf = [0u8; 4];
+        a1.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"back");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_mux_close_channel_sends_fin() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let a = MuxIO::new(a);
+        let b = MuxIO::new(b);
+
+        let mut a1 = a.open_channel(1);
+        let mut a2 = a.open_channel(2);
+        a1.write_all(b"last words").await?;
+        a.close_channel(1);
+        a2.write_all(b"still open").await?;
+        a2.flush().await?;
+
+        let err = a1.write(b"more").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+        let mut rest = Vec::new();
+        b.open_channel(1).read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"last words");
+
+        let mut buf = [0u8; 10];
+        b.open_channel(2).read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"still open");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_mux_channel_close() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let a = MuxIO::new(a);
+        let b = MuxIO::new(b);
+
+        let mut a1 = a.open_channel(1);
+        a1.write_all(b"data").await?;
+        a1.close().await?;
+
+        let mut rest = Vec::new();
+        b.open_channel(1).read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"data");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_mux_driver_buffers_until_eof() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(16);
+        let a = MuxIO::new(a);
+        let b = MuxIO::new(b);
+
+        let mut a1 = a.open_channel(1);
+        a1.write_all(b"hello").await?;
+        a1.flush().await?;
+        drop(a1);
+        drop(a);
+
+        b.clone().await?;
+
+        let mut rest = Vec::new();
+        b.open_channel(1).read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"hello");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_mux_rejects_unknown_frame_type() -> Result<()> {
+    executor::block_on(async {
+        let (mut a, b) = merge_io::duplex(16);
+        let b = MuxIO::new(b);
+
+        a.write_all(&[7, 0, 0, 0, 1, 0, 0, 0, 0]).await?;
+        a.flush().await?;
+
+        let err = b.clone().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = b.open_channel(1).read(&mut [0u8; 4]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,53 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::null::NullIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_sink() -> Result<()> {
+    executor::block_on(async {
+        let mut sink = NullIO::sink();
+        assert_eq!(sink.write(&[1, 2, 3]).await?, 3);
+        sink.close().await?;
+
+        let mut buf = [1u8; 4];
+        assert_eq!(sink.read(&mut buf).await?, 0);
+        assert_eq!(buf, [1; 4]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_zeros() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = MergeIO::new(NullIO::zeros(10), NullIO::sink());
+        stream.write_all(&[1; 100]).await?;
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, [0; 10]);
+        assert_eq!(stream.reader().remaining(), Some(0));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_infinite_zeros() -> Result<()> {
+    executor::block_on(async {
+        let mut zeros = NullIO::infinite_zeros();
+        let mut buf = [1u8; 1024];
+        for _ in 0..4 {
+            zeros.read_exact(&mut buf).await?;
+            assert_eq!(buf, [0; 1024]);
+        }
+        assert_eq!(zeros.remaining(), None);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,75 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::peek::PeekIO;
+use merge_io::test_utils::{MockReader, PollResult};
+use merge_io::MergeIO;
+
+#[test]
+fn test_peek_then_read() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1, 2, 3, 4]), Vec::<u8>::new());
+        let mut stream = PeekIO::new(stream);
+
+        let mut peeked = [0u8; 3];
+        assert_eq!(stream.peek(&mut peeked).await?, 3);
+        assert_eq!(peeked, [1, 2, 3]);
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, [1, 2, 3, 4]);
+
+        stream.write_all(&[5]).await?;
+        assert_eq!(stream.get_ref().writer(), &[5]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_peek_is_idempotent() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1, 2, 3, 4]), Vec::<u8>::new());
+        let mut stream = PeekIO::new(stream);
+
+        let mut first = [0u8; 2];
+        let mut second = [0u8; 2];
+        assert_eq!(stream.peek(&mut first).await?, 2);
+        assert_eq!(stream.peek(&mut second).await?, 2);
+        assert_eq!(first, second);
+        assert_eq!(stream.peeked().len(), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_peek_spans_inner_reads() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Data(vec![1, 2]),
+            PollResult::Data(vec![3, 4, 5]),
+        ]);
+        let mut stream = PeekIO::new(MergeIO::new(reader, Vec::<u8>::new()));
+
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.peek(&mut buf).await?, 2);
+        assert_eq!(&buf[..2], [1, 2]);
+        assert_eq!(stream.peek(&mut buf).await?, 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+
+        let mut read = [0u8; 3];
+        assert_eq!(stream.read(&mut read).await?, 3);
+        assert_eq!(read, [1, 2, 3]);
+        assert_eq!(stream.read(&mut read).await?, 1);
+        assert_eq!(read[0], 4);
+        assert_eq!(stream.read(&mut read).await?, 1);
+        assert_eq!(read[0], 5);
+        assert_eq!(stream.read(&mut read).await?, 0);
+
+        Ok(())
+    })
+}
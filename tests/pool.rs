@@ -0,0 +1,159 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::future::{self, FutureExt};
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use merge_io::pool::Pool;
+use merge_io::MergeIO;
+
+type Conn = MergeIO<Cursor<Vec<u8>>, Vec<u8>>;
+
+/// Returns a factory creating connections that read their own number.
+fn factory(created: &AtomicUsize) -> impl Fn() -> future::Ready<Result<Conn>> + '_ {
+    move || {
+        let id = created.fetch_add(1, Ordering::SeqCst) as u8;
+        future::ready(Ok(MergeIO::new(Cursor::new(vec![id]), Vec::new())))
+    }
+}
+
+async fn id(conn: &mut Conn) -> Result<u8> {
+    conn.reader_mut().set_position(0);
+    let mut buf = [0];
+    conn.read_exact(&mut buf).await?;
+    Ok(buf[0])
+}
+
+#[test]
+fn test_pool_reuses_connections() -> Result<()> {
+    executor::block_on(async {
+        let created = AtomicUsize::new(0);
+        let pool = Pool::new(factory(&created));
+
+        let mut first = pool.acquire().await?;
+        first.write_all(b"hello").await?;
+        let mut second = pool.acquire().await?;
+        assert_eq!((id(&mut first).await?, id(&mut second).await?), (0, 1));
+        assert_eq!(pool.size(), 2);
+        drop(first);
+        assert_eq!(pool.idle_count(), 1);
+
+        // The released connection is handed out again, as it was left.
+        let mut third = pool.acquire().await?;
+        assert_eq!(id(&mut third).await?, 0);
+        assert_eq!(third.writer(), b"hello");
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pool_waits_at_max_size() -> Result<()> {
+    executor::block_on(async {
+        let created = AtomicUsize::new(0);
+        let pool = Pool::new(factory(&created)).with_max_size(1);
+
+        let first = pool.acquire().await?;
+        let mut waiting = Box::pin(pool.acquire());
+        assert!(waiting.as_mut().now_or_never().is_none());
+
+        drop(first);
+        let mut second = waiting.await?;
+        assert_eq!(id(&mut second).await?, 0);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pool_fill_and_max_idle() -> Result<()> {
+    executor::block_on(async {
+        let created = AtomicUsize::new(0);
+        let pool = Pool::new(factory(&created))
+            .with_min_size(2)
+            .with_max_idle(1);
+
+        pool.fill().await?;
+        assert_eq!((pool.size(), pool.idle_count()), (2, 2));
+
+        let first = pool.acquire().await?;
+        let second = pool.acquire().await?;
+        let third = pool.acquire().await?;
+        assert_eq!(pool.size(), 3);
+        drop(first);
+        drop(second);
+        drop(third);
+        // Only one connection is kept idle, the others are closed.
+        assert_eq!((pool.size(), pool.idle_count()), (1, 1));
+
+        // Filling tops up to the minimum size again.
+        pool.fill().await?;
+        assert_eq!((pool.size(), pool.idle_count()), (2, 2));
+        assert_eq!(created.load(Ordering::SeqCst), 4);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pool_acquire_fills() -> Result<()> {
+    executor::block_on(async {
+        let created = AtomicUsize::new(0);
+        let pool = Pool::new(factory(&created)).with_min_size(2);
+        assert_eq!(pool.size(), 0);
+
+        // The first connection acquired brings the pool up to its minimum.
+        let mut conn = pool.acquire().await?;
+        assert_eq!(id(&mut conn).await?, 0);
+        assert_eq!((pool.size(), pool.idle_count()), (2, 1));
+        drop(conn.detach());
+
+        // So does every later one.
+        let _conn = pool.acquire().await?;
+        assert_eq!((pool.size(), pool.idle_count()), (2, 1));
+        assert_eq!(created.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pool_detach() -> Result<()> {
+    executor::block_on(async {
+        let created = AtomicUsize::new(0);
+        let pool = Pool::new(factory(&created)).with_max_size(1);
+
+        let conn = pool.acquire().await?;
+        let mut conn = conn.detach();
+        assert_eq!(id(&mut conn).await?, 0);
+        assert_eq!(pool.size(), 0);
+
+        let mut conn = pool.acquire().await?;
+        assert_eq!(id(&mut conn).await?, 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pool_connect_error() {
+    executor::block_on(async {
+        let pool = Pool::new(|| {
+            future::ready(Err::<Conn, _>(Error::new(
+                ErrorKind::ConnectionRefused,
+                "refused",
+            )))
+        })
+        .with_max_size(1);
+
+        let err = pool.acquire().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+        // The failed connection doesn't take up a slot.
+        assert_eq!(pool.size(), 0);
+        assert!(pool.acquire().await.is_err());
+    })
+}
@@ -0,0 +1,79 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use proptest::prelude::*;
+use std::io::IoSliceMut;
+
+use merge_io::test_utils::{MockWriter, WriteBehavior};
+use merge_io::MergeIO;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Read(usize),
+    ReadVectored(Vec<usize>),
+    Write(Vec<u8>),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..64usize).prop_map(Op::Read),
+        prop::collection::vec(0..32usize, 0..6).prop_map(Op::ReadVectored),
+        prop::collection::vec(any::<u8>(), 0..64).prop_map(Op::Write),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn read_write_invariants(
+        input in prop::collection::vec(any::<u8>(), 0..512),
+        ops in prop::collection::vec(op(), 0..48),
+        partial in prop::collection::vec(1..16usize, 0..32),
+    ) {
+        let script = partial.into_iter().map(WriteBehavior::Partial).collect();
+        let mut stream = MergeIO::new(Cursor::new(input.clone()), MockWriter::with_script(script));
+
+        let mut read = Vec::new();
+        let mut written = Vec::new();
+        executor::block_on(async {
+            for op in ops {
+                match op {
+                    Op::Read(len) => {
+                        let mut buf = vec![0u8; len];
+                        let n = stream.read(&mut buf).await.unwrap();
+                        prop_assert!(n <= len);
+                        read.extend_from_slice(&buf[..n]);
+                    }
+                    Op::ReadVectored(lens) => {
+                        let mut bufs: Vec<Vec<u8>> = lens.iter().map(|&len| vec![0u8; len]).collect();
+                        let mut slices: Vec<_> = bufs.iter_mut().map(|buf| IoSliceMut::new(buf)).collect();
+                        let n = stream.read_vectored(&mut slices).await.unwrap();
+                        prop_assert!(n <= lens.iter().sum::<usize>());
+                        let mut rest = n;
+                        for buf in &bufs {
+                            let taken = rest.min(buf.len());
+                            read.extend_from_slice(&buf[..taken]);
+                            rest -= taken;
+                        }
+                    }
+                    Op::Write(data) => {
+                        stream.write_all(&data).await.unwrap();
+                        written.extend_from_slice(&data);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        // Whatever wasn't read by the operations is still there to be read.
+        let mut rest = Vec::new();
+        executor::block_on(stream.read_to_end(&mut rest)).unwrap();
+        prop_assert_eq!(read.len() + rest.len(), input.len());
+        read.extend_from_slice(&rest);
+        prop_assert_eq!(&read, &input);
+
+        let (reader, writer) = stream.into_inner();
+        prop_assert_eq!(reader.position(), input.len() as u64);
+        prop_assert_eq!(writer.written(), &written[..]);
+    }
+}
@@ -0,0 +1,103 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+#[test]
+fn test_bridge() -> Result<()> {
+    executor::block_on(async {
+        let (mut left, mut proxy_left) = merge_io::duplex(4);
+        let (mut proxy_right, mut right) = merge_io::duplex(4);
+
+        let left_side = async {
+            left.write_all(b"ping from the left").await?;
+            left.close().await?;
+            let mut buf = Vec::new();
+            left.read_to_end(&mut buf).await?;
+            Result::Ok(buf)
+        };
+        let right_side = async {
+            right.write_all(b"pong").await?;
+            right.close().await?;
+            let mut buf = Vec::new();
+            right.read_to_end(&mut buf).await?;
+            Result::Ok(buf)
+        };
+
+        let (counts, from_right, from_left) = futures::try_join!(
+            merge_io::bridge(&mut proxy_left, &mut proxy_right),
+            left_side,
+            right_side,
+        )?;
+
+        assert_eq!(counts, (18, 4));
+        assert_eq!(from_right, b"pong");
+        assert_eq!(from_left, b"ping from the left");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_copy_bidirectional() -> Result<()> {
+    executor::block_on(async {
+        let (mut left, proxy_left) = merge_io::duplex(4);
+        let (mut proxy_right, mut right) = merge_io::duplex(4);
+        // Any stream works, including trait objects.
+        let mut proxy_left: Box<dyn merge_io::AsyncReadWrite + Unpin> = Box::new(proxy_left);
+
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let left_side = async {
+            left.write_all(&data).await?;
+            left.close().await?;
+            let mut buf = Vec::new();
+            left.read_to_end(&mut buf).await?;
+            Result::Ok(buf)
+        };
+        let right_side = async {
+            let mut buf = Vec::new();
+            // Only answers once the left side is done.
+            right.read_to_end(&mut buf).await?;
+            right.write_all(b"done").await?;
+            right.close().await?;
+            Result::Ok(buf)
+        };
+
+        let (counts, from_right, from_left) = futures::try_join!(
+            merge_io::copy_bidirectional(&mut *proxy_left, &mut proxy_right),
+            left_side,
+            right_side,
+        )?;
+
+        assert_eq!(counts, (data.len() as u64, 4));
+        assert_eq!(from_right, b"done");
+        assert_eq!(from_left, data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_copy_bidirectional_error() {
+    use merge_io::test_utils::{MockReader, MockWriter, PollResult};
+    use merge_io::MergeIO;
+    use std::io::ErrorKind;
+
+    executor::block_on(async {
+        let mut a = MergeIO::new(
+            MockReader::new(vec![PollResult::Data(b"hi".to_vec()), PollResult::Pending]),
+            MockWriter::new(),
+        );
+        let mut b = MergeIO::new(
+            MockReader::new(vec![PollResult::Error(ErrorKind::ConnectionReset)]),
+            MockWriter::new(),
+        );
+
+        let err = merge_io::copy_bidirectional(&mut a, &mut b)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(b.writer().written(), b"hi");
+    })
+}
@@ -0,0 +1,136 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use merge_io::rate_limit::{IoBudget, RateLimitedIO, ThrottledReadIO};
+use merge_io::MergeIO;
+
+const MEGABYTE: usize = 1024 * 1024;
+const RATE: u64 = 100 * 1024;
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limited_read() -> Result<()> {
+    let reader = Cursor::new(vec![7u8; MEGABYTE]);
+    let writer: Vec<u8> = vec![];
+    let mut stream =
+        RateLimitedIO::new(MergeIO::new(reader, writer), RATE, 0).with_read_burst(1024);
+
+    let started = Instant::now();
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+    let elapsed = started.elapsed();
+
+    assert_eq!(read_buf.len(), MEGABYTE);
+    let expected = Duration::from_secs_f64(MEGABYTE as f64 / RATE as f64);
+    assert!(elapsed > expected.mul_f64(0.95), "{:?}", elapsed);
+    assert!(elapsed < expected.mul_f64(1.05), "{:?}", elapsed);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limited_write() -> Result<()> {
+    let reader = Cursor::new(Vec::<u8>::new());
+    let writer: Vec<u8> = vec![];
+    let mut stream =
+        RateLimitedIO::new(MergeIO::new(reader, writer), 0, RATE).with_write_burst(1024);
+
+    let started = Instant::now();
+    stream.write_all(&vec![7u8; MEGABYTE]).await?;
+    let elapsed = started.elapsed();
+
+    assert_eq!(stream.get_ref().writer().len(), MEGABYTE);
+    let expected = Duration::from_secs_f64(MEGABYTE as f64 / RATE as f64);
+    assert!(elapsed > expected.mul_f64(0.95), "{:?}", elapsed);
+    assert!(elapsed < expected.mul_f64(1.05), "{:?}", elapsed);
+
+    let unlimited_started = Instant::now();
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+    assert_eq!(unlimited_started.elapsed(), Duration::ZERO);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_read() -> Result<()> {
+    let reader = Cursor::new(vec![7u8; MEGABYTE]);
+    let writer: Vec<u8> = vec![];
+    let mut stream = ThrottledReadIO::new(MergeIO::new(reader, writer), RATE).with_burst(1024);
+
+    let started = Instant::now();
+    stream.write_all(&vec![7u8; MEGABYTE]).await?;
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert_eq!(stream.get_ref().writer().len(), MEGABYTE);
+
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+    let elapsed = started.elapsed();
+
+    assert_eq!(read_buf.len(), MEGABYTE);
+    let expected = Duration::from_secs_f64(MEGABYTE as f64 / RATE as f64);
+    assert!(elapsed > expected.mul_f64(0.95), "{:?}", elapsed);
+    assert!(elapsed < expected.mul_f64(1.05), "{:?}", elapsed);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shared_budget() -> Result<()> {
+    let budget = IoBudget::new(RATE, 1024);
+    let write = |budget| async move {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut stream = stream.with_budget(budget);
+        stream.write_all(&vec![7u8; MEGABYTE / 4]).await?;
+        Ok::<_, std::io::Error>(stream.get_ref().writer().len())
+    };
+
+    let started = Instant::now();
+    let (a, b, c, d) = futures::try_join!(
+        write(budget.clone()),
+        write(budget.clone()),
+        write(budget.clone()),
+        write(budget.clone()),
+    )?;
+    let elapsed = started.elapsed();
+
+    assert_eq!(a + b + c + d, MEGABYTE);
+    let expected = Duration::from_secs_f64(MEGABYTE as f64 / RATE as f64);
+    assert!(elapsed > expected.mul_f64(0.95), "{:?}", elapsed);
+    assert!(elapsed < expected.mul_f64(1.05), "{:?}", elapsed);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_budgeted_read_and_write_wait_separately() -> Result<()> {
+    let budget = IoBudget::new(RATE, 1024);
+    let reader = Cursor::new(vec![7u8; MEGABYTE / 8]);
+    let writer: Vec<u8> = vec![];
+    let (mut read_half, mut write_half) = MergeIO::new(reader, writer).with_budget(budget).split();
+
+    // Both halves keep running out of budget, each from its own task.
+    let read = tokio::spawn(async move {
+        let mut read_buf = Vec::new();
+        read_half.read_to_end(&mut read_buf).await?;
+        Ok::<_, std::io::Error>(read_buf.len())
+    });
+    let write = tokio::spawn(async move { write_half.write_all(&vec![7u8; MEGABYTE / 8]).await });
+
+    let started = Instant::now();
+    let timeout = Duration::from_secs_f64(MEGABYTE as f64 / RATE as f64);
+    let (read, write) = tokio::time::timeout(timeout, async { tokio::join!(read, write) })
+        .await
+        .expect("a half was never woken up");
+    assert_eq!(read.unwrap()?, MEGABYTE / 8);
+    write.unwrap()?;
+
+    let expected = Duration::from_secs_f64((MEGABYTE / 4) as f64 / RATE as f64);
+    assert!(started.elapsed() > expected.mul_f64(0.95));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::future::poll_fn;
+use futures::AsyncReadExt;
+use std::io::{ErrorKind, Result};
+use std::pin::Pin;
+use std::task::Poll;
+
+use merge_io::ready::{AsyncReadReady, AsyncWriteReady};
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+#[test]
+fn test_read_ready() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![
+            PollResult::Pending,
+            PollResult::Data(b"data".to_vec()),
+            PollResult::Error(ErrorKind::ConnectionReset),
+        ]);
+        let mut stream = MergeIO::new(reader, MockWriter::new());
+
+        let mut pending = 0;
+        poll_fn(|cx| match Pin::new(&mut stream).poll_read_ready(cx) {
+            Poll::Pending => {
+                pending += 1;
+                Poll::Pending
+            }
+            ready => ready,
+        })
+        .await?;
+        assert_eq!(pending, 1);
+
+        // The probe doesn't consume any data.
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"data");
+
+        let err = poll_fn(|cx| AsyncReadReady::poll_read_ready(Pin::new(&mut stream), cx))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_write_ready() -> Result<()> {
+    executor::block_on(async {
+        let writer = MockWriter::with_script(vec![
+            WriteBehavior::Pending,
+            WriteBehavior::Accept,
+            WriteBehavior::Error(ErrorKind::BrokenPipe),
+        ]);
+        let mut stream = MergeIO::new(MockReader::new(Vec::new()), writer);
+
+        poll_fn(|cx| AsyncWriteReady::poll_write_ready(Pin::new(&mut stream), cx)).await?;
+        assert_eq!(stream.writer().calls(), 2);
+        assert!(stream.writer().written().is_empty());
+
+        let err = poll_fn(|cx| Pin::new(&mut stream).poll_write_ready(cx))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,100 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::future;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Result};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use merge_io::reconnect::{ExponentialBackoff, ReconnectIO};
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+type MockStream = MergeIO<MockReader, MockWriter>;
+
+fn backoff() -> ExponentialBackoff {
+    ExponentialBackoff::new(Duration::from_millis(100), Duration::from_millis(250))
+}
+
+#[test]
+fn test_backoff_delay() {
+    let backoff = backoff();
+    assert_eq!(backoff.delay(0), Duration::from_millis(100));
+    assert_eq!(backoff.delay(1), Duration::from_millis(200));
+    assert_eq!(backoff.delay(2), Duration::from_millis(250));
+    assert_eq!(backoff.delay(100), Duration::from_millis(250));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_reconnects() -> Result<()> {
+    let streams = RefCell::new(VecDeque::from(vec![
+        MergeIO::new(
+            MockReader::new(vec![PollResult::Error(ErrorKind::ConnectionReset)]),
+            MockWriter::new(),
+        ),
+        MergeIO::new(
+            MockReader::new(vec![PollResult::Data(vec![1, 2, 3])]),
+            MockWriter::new(),
+        ),
+    ]));
+    let factory = || {
+        future::ready(Ok::<MockStream, Error>(
+            streams.borrow_mut().pop_front().unwrap(),
+        ))
+    };
+    let mut stream = ReconnectIO::new(factory, 3, backoff());
+
+    let start = Instant::now();
+    let mut buf = [0u8; 3];
+    stream.read_exact(&mut buf).await?;
+    assert_eq!(buf, [1, 2, 3]);
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+    assert!(streams.borrow().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_write_reconnects() -> Result<()> {
+    let streams = RefCell::new(VecDeque::from(vec![
+        MergeIO::new(
+            MockReader::new(vec![]),
+            MockWriter::with_script(vec![WriteBehavior::Error(ErrorKind::BrokenPipe)]),
+        ),
+        MergeIO::new(MockReader::new(vec![]), MockWriter::new()),
+    ]));
+    let factory = || {
+        future::ready(Ok::<MockStream, Error>(
+            streams.borrow_mut().pop_front().unwrap(),
+        ))
+    };
+    let mut stream = ReconnectIO::new(factory, 1, backoff());
+
+    stream.write_all(b"hello").await?;
+    assert_eq!(stream.get_ref().unwrap().writer().written(), b"hello");
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_attempts_exhausted() {
+    let connects = Cell::new(0);
+    let factory = || {
+        connects.set(connects.get() + 1);
+        future::ready(Err::<MockStream, Error>(Error::new(
+            ErrorKind::ConnectionRefused,
+            "refused",
+        )))
+    };
+    let mut stream = ReconnectIO::new(factory, 2, backoff());
+
+    let start = Instant::now();
+    let mut buf = [0u8; 1];
+    let err = stream.read(&mut buf).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    assert_eq!(connects.get(), 3);
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+    assert!(!stream.is_connected());
+}
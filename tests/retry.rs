@@ -0,0 +1,78 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+
+use merge_io::retry::RetryIO;
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+fn interrupted_reader() -> MockReader {
+    MockReader::new(vec![
+        PollResult::Error(ErrorKind::Interrupted),
+        PollResult::Error(ErrorKind::WouldBlock),
+        PollResult::Data(vec![1, 2]),
+        PollResult::Error(ErrorKind::Interrupted),
+        PollResult::Error(ErrorKind::Interrupted),
+        PollResult::Data(vec![3]),
+    ])
+}
+
+#[test]
+fn test_read_retried() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(interrupted_reader(), MockWriter::new());
+        let mut stream = RetryIO::new(stream, 2);
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(stream.retries(), 0);
+        assert_eq!(stream.get_ref().reader().calls(), 7);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_retries_exhausted() {
+    executor::block_on(async {
+        let stream = MergeIO::new(interrupted_reader(), MockWriter::new());
+        let mut stream = RetryIO::new(stream, 1);
+
+        let mut buf = [0u8; 4];
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+        assert_eq!(stream.retries(), 0);
+    })
+}
+
+#[test]
+fn test_other_errors_not_retried() {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![PollResult::Error(ErrorKind::ConnectionReset)]);
+        let mut stream = RetryIO::new(MergeIO::new(reader, MockWriter::new()), 5);
+
+        let mut buf = [0u8; 4];
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(stream.get_ref().reader().calls(), 1);
+    })
+}
+
+#[test]
+fn test_write_retried() -> Result<()> {
+    executor::block_on(async {
+        let writer = MockWriter::with_script(vec![
+            WriteBehavior::Error(ErrorKind::Interrupted),
+            WriteBehavior::Accept,
+        ]);
+        let mut stream = RetryIO::new(MergeIO::new(MockReader::new(vec![]), writer), 1);
+
+        stream.write_all(b"data").await?;
+        assert_eq!(stream.get_ref().writer().written(), b"data");
+
+        Ok(())
+    })
+}
@@ -0,0 +1,85 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncWriteExt, Cursor};
+use futures::FutureExt;
+use std::io::{ErrorKind, Result};
+
+use merge_io::select::{select_read, WhichStream};
+use merge_io::test_utils::{MockReader, PollResult};
+
+#[test]
+fn test_select_read_first_ready() -> Result<()> {
+    executor::block_on(async {
+        let mut a = MockReader::new(vec![PollResult::Pending, PollResult::Data(b"a".to_vec())]);
+        let mut b = MockReader::new(vec![PollResult::Data(b"b".to_vec())]);
+        let mut buf = [0; 4];
+
+        assert_eq!(
+            select_read(&mut a, &mut b, &mut buf).await?,
+            (1, WhichStream::B)
+        );
+        assert_eq!(&buf[..1], b"b");
+        assert_eq!(
+            select_read(&mut a, &mut b, &mut buf).await?,
+            (1, WhichStream::A)
+        );
+        assert_eq!(&buf[..1], b"a");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_select_read_prefers_a() -> Result<()> {
+    executor::block_on(async {
+        let mut a = Cursor::new(b"first".to_vec());
+        let mut b = Cursor::new(b"second".to_vec());
+        let mut buf = [0; 16];
+
+        assert_eq!(
+            select_read(&mut a, &mut b, &mut buf).await?,
+            (5, WhichStream::A)
+        );
+        // EOF counts as ready as well.
+        assert_eq!(
+            select_read(&mut a, &mut b, &mut buf).await?,
+            (0, WhichStream::A)
+        );
+        assert_eq!(b.position(), 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_select_read_waits_for_either() -> Result<()> {
+    executor::block_on(async {
+        let (mut a, mut a_peer) = merge_io::duplex(16);
+        let (mut b, mut b_peer) = merge_io::duplex(16);
+        let mut buf = [0; 16];
+
+        assert!(select_read(&mut a, &mut b, &mut buf)
+            .now_or_never()
+            .is_none());
+
+        b_peer.write_all(b"late").await?;
+        a_peer.write_all(b"later").await?;
+        let (n, which) = select_read(&mut a, &mut b, &mut buf).await?;
+        assert_eq!((n, which), (5, WhichStream::A));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_select_read_error() {
+    executor::block_on(async {
+        let mut a = MockReader::new(vec![PollResult::Pending]);
+        let mut b = MockReader::new(vec![PollResult::Error(ErrorKind::ConnectionReset)]);
+        let mut buf = [0; 4];
+
+        let err = select_read(&mut a, &mut b, &mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+    })
+}
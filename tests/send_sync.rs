@@ -0,0 +1,27 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+use std::cell::Cell;
+use std::rc::Rc;
+
+use merge_io::duplex::DuplexStream;
+use merge_io::split::{ReadHalf, WriteHalf};
+use merge_io::{DynMergeIO, MergeIO};
+
+type TcpMergeIO = MergeIO<tokio::net::tcp::OwnedReadHalf, tokio::net::tcp::OwnedWriteHalf>;
+
+assert_impl_all!(MergeIO<Vec<u8>, Vec<u8>>: Send, Sync, Unpin);
+assert_impl_all!(TcpMergeIO: Send, Sync, Unpin);
+assert_impl_all!(DuplexStream: Send, Unpin);
+assert_impl_all!(DynMergeIO<'static>: Send, Unpin);
+assert_impl_all!(ReadHalf<Vec<u8>>: Send, Sync);
+assert_impl_all!(WriteHalf<Vec<u8>>: Send, Sync);
+
+// `Cell` is `Send` but not `Sync`.
+assert_impl_all!(MergeIO<Cell<u8>, Vec<u8>>: Send);
+assert_not_impl_any!(MergeIO<Cell<u8>, Vec<u8>>: Sync);
+assert_not_impl_any!(MergeIO<Vec<u8>, Cell<u8>>: Sync);
+
+// `Rc` is neither.
+assert_not_impl_any!(MergeIO<Rc<u8>, Vec<u8>>: Send, Sync);
+assert_not_impl_any!(MergeIO<Vec<u8>, Rc<u8>>: Send, Sync);
@@ -0,0 +1,91 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+
+use merge_io::sequenced::SequencedIO;
+use merge_io::MergeIO;
+
+fn frame(seq: u64, payload: &[u8]) -> Vec<u8> {
+    let mut frame = seq.to_be_bytes().to_vec();
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[test]
+fn test_sequenced_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut stream = SequencedIO::new(stream);
+        stream.write_all(b"first").await?;
+        stream.write_all(b"second").await?;
+        stream.flush().await?;
+        assert_eq!(stream.next_write_seq(), 2);
+
+        let wire = stream.get_ref().writer().clone();
+        assert_eq!(wire, [frame(0, b"first"), frame(1, b"second")].concat());
+
+        let mut reader = SequencedIO::new(MergeIO::new(Cursor::new(wire), Vec::<u8>::new()));
+        let mut read_buf = Vec::new();
+        reader.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, b"firstsecond");
+        assert_eq!(reader.next_read_seq(), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sequenced_gap() -> Result<()> {
+    executor::block_on(async {
+        let wire = [frame(0, b"a"), frame(2, b"b"), frame(3, b"c")].concat();
+        let mut stream = SequencedIO::new(MergeIO::new(Cursor::new(wire), Vec::<u8>::new()));
+
+        let mut buf = [0u8; 1];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"a");
+
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // Reading picks up after the number received.
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(rest, b"bc");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sequenced_close_resets() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(frame(0, b"x")), Vec::<u8>::new());
+        let mut stream = SequencedIO::new(stream);
+        stream.write_all(b"data").await?;
+        let mut buf = [0u8; 1];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!((stream.next_read_seq(), stream.next_write_seq()), (1, 1));
+
+        stream.close().await?;
+        assert_eq!((stream.next_read_seq(), stream.next_write_seq()), (0, 0));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sequenced_truncated_frame() {
+    executor::block_on(async {
+        let mut wire = frame(0, b"payload");
+        wire.truncate(wire.len() - 2);
+        let mut stream = SequencedIO::new(MergeIO::new(Cursor::new(wire), Vec::<u8>::new()));
+
+        let mut read_buf = Vec::new();
+        let err = stream.read_to_end(&mut read_buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    })
+}
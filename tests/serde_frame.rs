@@ -0,0 +1,89 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Result};
+
+use merge_io::framing::FramedIO;
+use merge_io::serde_frame::{CborCodec, Codec, JsonCodec, SerdeFrameIO};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Message {
+    Hello { name: String },
+    Data(Vec<u8>),
+    Bye,
+}
+
+fn messages() -> Vec<Message> {
+    vec![
+        Message::Hello {
+            name: "héllo".to_owned(),
+        },
+        Message::Data(vec![0, 1, 255]),
+        Message::Bye,
+    ]
+}
+
+async fn round_trip<C: Codec>() -> Result<()> {
+    let (a, b) = merge_io::duplex(16);
+    let mut a = SerdeFrameIO::<_, C>::new(FramedIO::new(a, 1024));
+    let mut b = SerdeFrameIO::<_, C>::new(FramedIO::new(b, 1024));
+
+    let send = async {
+        for msg in messages() {
+            a.send(&msg).await?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    let recv = async {
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(b.recv::<Message>().await?);
+        }
+        Ok::<_, std::io::Error>(received)
+    };
+    let (sent, received) = futures::join!(send, recv);
+    sent?;
+    assert_eq!(received?, messages());
+    Ok(())
+}
+
+#[test]
+fn test_serde_frame_json_round_trip() -> Result<()> {
+    executor::block_on(round_trip::<JsonCodec>())
+}
+
+#[test]
+fn test_serde_frame_cbor_round_trip() -> Result<()> {
+    executor::block_on(round_trip::<CborCodec>())
+}
+
+#[test]
+fn test_serde_frame_invalid_message() -> Result<()> {
+    executor::block_on(async {
+        let (a, b) = merge_io::duplex(1024);
+        let mut a = FramedIO::new(a, 1024);
+        let mut b = SerdeFrameIO::<_, JsonCodec>::new(FramedIO::new(b, 1024));
+
+        a.write_frame(b"{\"Data\":").await?;
+        a.write_frame(b"\"Bye\"").await?;
+
+        let err = b.recv::<Message>().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        // The bad frame was consumed, the next one is still readable.
+        assert_eq!(b.recv::<Message>().await?, Message::Bye);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_serde_frame_message_too_large() {
+    executor::block_on(async {
+        let (a, _b) = merge_io::duplex(1024);
+        let mut a = SerdeFrameIO::<_, CborCodec>::new(FramedIO::new(a, 8));
+
+        let err = a.send(&Message::Data(vec![0; 64])).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    })
+}
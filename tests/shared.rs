@@ -0,0 +1,65 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::shared::SharedMergeIO;
+use merge_io::MergeIO;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_writes() -> Result<()> {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+    let stream = SharedMergeIO::new(stream);
+
+    let tasks: Vec<_> = (0u8..8)
+        .map(|id| {
+            let mut stream = stream.clone();
+            tokio::spawn(async move {
+                for _ in 0..100 {
+                    assert_eq!(stream.write(&[id; 16]).await?, 16);
+                    tokio::task::yield_now().await;
+                }
+                Result::Ok(())
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap()?;
+    }
+
+    let written = stream.try_unwrap().unwrap().into_inner().1;
+    assert_eq!(written.len(), 8 * 100 * 16);
+    for chunk in written.chunks(16) {
+        assert!(chunk.iter().all(|&b| b == chunk[0]));
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_and_write_from_different_tasks() -> Result<()> {
+    let stream = MergeIO::new(Cursor::new(vec![7u8; 4096]), Vec::<u8>::new());
+    let stream = SharedMergeIO::new(stream);
+
+    let mut reader = stream.clone();
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await?;
+        Result::Ok(buf)
+    });
+    let mut writer = stream.clone();
+    let write = tokio::spawn(async move {
+        for _ in 0..64 {
+            writer.write_all(b"heartbeat").await?;
+        }
+        writer.flush().await
+    });
+
+    assert_eq!(read.await.unwrap()?, vec![7u8; 4096]);
+    write.await.unwrap()?;
+
+    let written = stream.try_unwrap().unwrap().into_inner().1;
+    assert_eq!(written, b"heartbeat".repeat(64));
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use merge_io::shutdown::{DrainOnCloseIO, GracefulShutdownIO, HalfCloseIO, ShutdownSignalIO};
+use merge_io::test_utils::MockWriter;
+use merge_io::MergeIO;
+
+/// Holds written data back until flushed, and drops it when closed.
+#[derive(Debug, Default)]
+struct LazyWriter {
+    pending: Vec<u8>,
+    flushed: Vec<u8>,
+}
+
+impl AsyncWrite for LazyWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.pending.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = &mut *self;
+        this.flushed.append(&mut this.pending);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.pending.clear();
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn test_close_without_flush() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), LazyWriter::default());
+        let mut stream = GracefulShutdownIO::new(stream);
+
+        stream.write_all(b"hello").await?;
+        stream.close().await?;
+
+        let writer = stream.into_inner().into_inner().1;
+        assert_eq!(writer.flushed, b"hello");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_flushes_before_close() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+        let mut stream = DrainOnCloseIO::new(stream);
+
+        stream.write_all(b"data").await?;
+        stream.close().await?;
+
+        let writer = stream.get_ref().writer();
+        assert_eq!(writer.flushes(), 1);
+        assert!(writer.is_closed());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_half_close_write() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"reply".to_vec()), MockWriter::new());
+        let mut stream = HalfCloseIO::new(stream);
+
+        stream.write_all(b"request").await?;
+        stream.close_write().await?;
+        assert!(stream.write_closed());
+        assert!(!stream.read_closed());
+        assert!(stream.get_ref().writer().is_closed());
+
+        let err = stream.write(b"more").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        stream.flush().await?;
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"reply");
+        assert_eq!(stream.get_ref().writer().written(), b"request");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_half_close_read() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"unread".to_vec()), MockWriter::new());
+        let mut stream = HalfCloseIO::new(stream);
+
+        stream.close_read().await;
+        assert!(stream.read_closed());
+
+        let mut buf = [0u8; 8];
+        assert_eq!(stream.read(&mut buf).await?, 0);
+        stream.write_all(b"still open").await?;
+        assert_eq!(stream.get_ref().writer().written(), b"still open");
+
+        Ok(())
+    })
+}
+
+#[tokio::test]
+async fn test_shutdown_signal() -> Result<()> {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), MockWriter::new());
+    let mut stream = ShutdownSignalIO::new(stream, tx);
+
+    let supervisor = tokio::spawn(rx);
+    let owner = tokio::spawn(async move {
+        stream.write_all(b"bye").await?;
+        stream.close().await?;
+        stream.close().await?;
+        Ok::<_, std::io::Error>(stream)
+    });
+
+    let stream = owner.await.unwrap()?;
+    assert!(stream.get_ref().writer().is_closed());
+    assert_eq!(supervisor.await.unwrap(), Ok(()));
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::MergeIO;
+
+#[tokio::test]
+async fn test_split_spawn() -> Result<()> {
+    let reader = Cursor::new(vec![1, 2, 3, 4]);
+    let writer: Vec<u8> = vec![];
+    let stream = MergeIO::new(reader, writer);
+
+    let (mut read_half, mut write_half) = stream.into_split();
+
+    let read_task = tokio::task::spawn(async move {
+        let mut read_buf = Vec::new();
+        read_half.read_to_end(&mut read_buf).await?;
+        Ok::<_, std::io::Error>((read_half, read_buf))
+    });
+    let write_task = tokio::task::spawn(async move {
+        write_half.write_all(&[10, 20, 30, 40]).await?;
+        Ok::<_, std::io::Error>(write_half)
+    });
+
+    let (read_half, read_buf) = read_task.await.unwrap()?;
+    let write_half = write_task.await.unwrap()?;
+
+    assert_eq!(&read_buf, &[1, 2, 3, 4]);
+
+    let stream = MergeIO::unsplit(read_half, write_half);
+    assert_eq!(stream.writer(), &[10, 20, 30, 40]);
+
+    Ok(())
+}
+
+#[test]
+fn test_split_into_inner() {
+    let stream = MergeIO::new(Cursor::new(vec![1u8]), vec![2u8]);
+    let (read_half, write_half) = stream.into_split();
+
+    assert_eq!(read_half.into_inner().into_inner(), vec![1]);
+    assert_eq!(write_half.into_inner(), vec![2]);
+}
+
+#[test]
+fn test_as_ref_as_mut() {
+    fn len(buf: impl AsRef<Vec<u8>>) -> usize {
+        buf.as_ref().len()
+    }
+
+    let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), vec![4, 5]);
+    let (mut read_half, mut write_half) = stream.into_split();
+
+    assert_eq!(len(&write_half), 2);
+    write_half.as_mut().push(6);
+    assert_eq!(AsRef::<Vec<u8>>::as_ref(&write_half), &[4, 5, 6]);
+
+    read_half.as_mut().set_position(2);
+    assert_eq!(AsRef::<Cursor<Vec<u8>>>::as_ref(&read_half).position(), 2);
+}
+
+#[test]
+fn test_reunite() {
+    let stream = MergeIO::new(Cursor::new(vec![1u8]), vec![2u8]);
+    let (read_half, write_half) = stream.into_split();
+    assert!(read_half.is_pair_of(&write_half));
+
+    let (reader, writer) = read_half.reunite(write_half).into_inner();
+    assert_eq!(reader.into_inner(), vec![1]);
+    assert_eq!(writer, vec![2]);
+}
+
+#[test]
+#[should_panic(expected = "not from the same split")]
+fn test_reunite_mismatched_halves() {
+    let (read_a, _write_a) = MergeIO::new(Cursor::new(vec![1u8]), vec![2u8]).into_split();
+    let (_read_b, write_b) = MergeIO::new(Cursor::new(vec![3u8]), vec![4u8]).into_split();
+    assert!(!read_a.is_pair_of(&write_b));
+
+    read_a.reunite(write_b);
+}
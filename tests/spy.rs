@@ -0,0 +1,81 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Cursor};
+use std::io::Result;
+
+use merge_io::spy::{load_replay, RecordReplayIO, SpyIO};
+use merge_io::MergeIO;
+
+/// Echoes the whole input back in upper case.
+async fn shout<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> Result<()> {
+    let mut input = Vec::new();
+    stream.read_to_end(&mut input).await?;
+    stream.write_all(&input.to_ascii_uppercase()).await?;
+    stream.flush().await
+}
+
+#[test]
+fn test_capture_and_replay() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"hello".to_vec()), Vec::<u8>::new());
+        let mut spy = SpyIO::new(stream);
+        shout(&mut spy).await?;
+
+        assert_eq!(spy.captured_reads(), b"hello");
+        assert_eq!(spy.captured_writes(), b"HELLO");
+
+        let mut replay = spy.replay();
+        shout(&mut replay).await?;
+        assert_eq!(replay.writer().get_ref(), spy.captured_writes());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_golden_files() -> Result<()> {
+    executor::block_on(async {
+        let dir = std::env::temp_dir().join(format!("merge-io-spy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let (reads_path, writes_path) = (dir.join("reads.bin"), dir.join("writes.bin"));
+
+        let stream = MergeIO::new(Cursor::new(b"golden".to_vec()), Vec::<u8>::new());
+        let mut spy = SpyIO::new(stream);
+        shout(&mut spy).await?;
+        spy.save_captures(&reads_path, &writes_path)?;
+
+        let mut replay = load_replay(&reads_path)?;
+        shout(&mut replay).await?;
+        assert_eq!(replay.writer().get_ref(), &std::fs::read(&writes_path)?);
+
+        std::fs::remove_dir_all(&dir)
+    })
+}
+
+#[test]
+fn test_record_replay() -> Result<()> {
+    executor::block_on(async {
+        let dir = std::env::temp_dir().join(format!("merge-io-record-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("session.bin");
+
+        let stream = MergeIO::new(Cursor::new(b"golden".to_vec()), Vec::<u8>::new());
+        let mut recorder = RecordReplayIO::record(stream, &path)?;
+        shout(&mut recorder).await?;
+        let stream = recorder.finish()?;
+        assert_eq!(stream.writer(), b"GOLDEN");
+
+        let file = std::fs::read(&path)?;
+        assert_eq!(file[0], 0);
+        assert_eq!(file[1..9], 6u64.to_be_bytes());
+        assert_eq!(&file[9..15], b"golden");
+
+        let mut replay = RecordReplayIO::replay(&path)?;
+        shout(&mut replay).await?;
+        assert_eq!(replay.writer(), &RecordReplayIO::recorded_writes(&path)?);
+        assert_eq!(replay.writer(), b"GOLDEN");
+
+        std::fs::remove_dir_all(&dir)
+    })
+}
@@ -0,0 +1,37 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::Result;
+
+use merge_io::stats::IoStats;
+use merge_io::MergeIO;
+
+#[test]
+fn test_stats() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer).with_stats();
+
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        stream.write_all(&[10, 20, 30]).await?;
+        stream.flush().await?;
+        stream.close().await?;
+
+        let stats = *stream.stats();
+        assert_eq!(stats.bytes_read, 4);
+        assert_eq!(stats.bytes_written, 3);
+        assert!(stats.read_calls >= 2);
+        assert_eq!(stats.write_calls, 1);
+        assert_eq!(stats.flush_calls, 1);
+        assert_eq!(stats.close_calls, 1);
+
+        *stream.stats_mut() = IoStats::default();
+        assert_eq!(stream.stats(), &IoStats::default());
+
+        Ok(())
+    })
+}
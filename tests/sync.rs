@@ -0,0 +1,53 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Result, Write};
+
+use merge_io::MergeIO;
+
+#[test]
+fn test_sync_duplex() -> Result<()> {
+    let reader = Cursor::new(vec![1, 2, 3, 4]);
+    let writer: Vec<u8> = vec![];
+    let mut stream = MergeIO::new(reader, writer);
+
+    stream.write_all(&[10, 20, 30, 40])?;
+    stream.flush()?;
+
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf)?;
+
+    assert_eq!(&read_buf, &[1, 2, 3, 4]);
+    assert_eq!(stream.writer(), &[10, 20, 30, 40]);
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_buf_reader() -> Result<()> {
+    let reader = Cursor::new(b"first\nsecond\n".to_vec());
+    let writer: Vec<u8> = vec![];
+    let stream = MergeIO::new(reader, writer);
+
+    let lines = BufReader::new(stream)
+        .lines()
+        .collect::<Result<Vec<String>>>()?;
+    assert_eq!(lines, vec!["first", "second"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_buf_writer() -> Result<()> {
+    let reader = Cursor::new(Vec::<u8>::new());
+    let writer: Vec<u8> = vec![];
+    let stream = MergeIO::new(reader, writer);
+
+    let mut buf_writer = BufWriter::new(stream);
+    buf_writer.write_all(b"hello")?;
+    buf_writer.write_all(b" world")?;
+
+    let stream = buf_writer.into_inner().map_err(|err| err.into_error())?;
+    assert_eq!(stream.writer(), b"hello world");
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::ErrorKind;
+
+use merge_io::tagged::TaggedMergeIO;
+use merge_io::test_utils::{MockReader, MockWriter, PollResult, WriteBehavior};
+use merge_io::MergeIO;
+
+#[test]
+fn test_errors_are_tagged() {
+    executor::block_on(async {
+        let stream = MergeIO::new(
+            MockReader::new(vec![PollResult::Error(ErrorKind::ConnectionReset)]),
+            MockWriter::with_script(vec![WriteBehavior::Error(ErrorKind::BrokenPipe)]),
+        );
+        let mut stream = TaggedMergeIO::new(stream, 42);
+        assert_eq!(*stream.id(), 42);
+
+        let mut buf = [0u8; 4];
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(err.to_string(), "[42] scripted MockReader error");
+
+        let err = stream.write(b"data").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(err.to_string(), "[42] scripted MockWriter error");
+    })
+}
+
+#[test]
+fn test_debug_includes_id() {
+    let stream = MergeIO::new(MockReader::new(vec![]), MockWriter::new());
+    let stream = TaggedMergeIO::new(stream, "conn-7");
+    assert!(format!("{:?}", stream).starts_with("TaggedMergeIO { id: \"conn-7\""));
+}
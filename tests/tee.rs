@@ -0,0 +1,259 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use merge_io::tee::{BroadcastWriteIO, TeeReadIO, TeeWriteIO};
+use merge_io::test_utils::{MockWriter, WriteBehavior};
+use merge_io::MergeIO;
+
+/// Accepts a single byte per write, returning `Pending` every other call.
+#[derive(Debug, Default)]
+struct Trickle {
+    data: Vec<u8>,
+    ready: bool,
+}
+
+impl AsyncWrite for Trickle {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        self.ready = !self.ready;
+        if !self.ready {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.data.push(buf[0]);
+        Poll::Ready(Ok(1))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn test_tee_read() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = TeeReadIO::new(MergeIO::new(reader, writer), Vec::<u8>::new());
+
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        stream.write_all(&[10]).await?;
+
+        assert_eq!(read_buf, vec![1, 2, 3, 4]);
+        assert!(stream.pending_tee().is_empty());
+        let (inner, secondary) = stream.into_inner();
+        assert_eq!(secondary, vec![1, 2, 3, 4]);
+        assert_eq!(inner.writer(), &[10]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tee_read_slow_secondary() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = TeeReadIO::new(MergeIO::new(reader, writer), Trickle::default());
+
+        let mut read_buf = [0u8; 4];
+        stream.read_exact(&mut read_buf).await?;
+        assert!(!stream.pending_tee().is_empty());
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        stream.flush_tee().await?;
+
+        assert_eq!(read_buf, [1, 2, 3, 4]);
+        assert_eq!(rest, vec![5, 6]);
+        assert!(stream.pending_tee().is_empty());
+        assert_eq!(stream.secondary().data, vec![1, 2, 3, 4, 5, 6]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tee_read_secondary_error() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6]);
+        let secondary = MockWriter::with_script(vec![WriteBehavior::Error(ErrorKind::BrokenPipe)]);
+        let mut stream = TeeReadIO::new(MergeIO::new(reader, Vec::<u8>::new()), secondary);
+
+        // The data read is returned even though the secondary writer fails...
+        let mut read_buf = [0u8; 3];
+        assert_eq!(stream.read(&mut read_buf).await?, 3);
+        assert_eq!(read_buf, [1, 2, 3]);
+
+        // ...and its error comes with the next read.
+        let err = stream.read(&mut read_buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(read_buf, [4, 5, 6]);
+        assert_eq!(stream.secondary().written(), &[4, 5, 6]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tee_write() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2]);
+        let writer: Vec<u8> = vec![];
+        let mut stream = TeeWriteIO::new(MergeIO::new(reader, writer), Vec::<u8>::new());
+
+        stream.write_all(&[10, 20, 30]).await?;
+        stream.write_all(&[40]).await?;
+        stream.flush().await?;
+        stream.close().await?;
+
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, vec![1, 2]);
+
+        let (inner, secondary) = stream.into_inner();
+        assert_eq!(inner.writer(), &[10, 20, 30, 40]);
+        assert_eq!(secondary, vec![10, 20, 30, 40]);
+
+        Ok(())
+    })
+}
+
+/// Fails the first flush, and accepts everything otherwise.
+#[derive(Debug, Default)]
+struct FlakyFlush {
+    failed: bool,
+}
+
+impl AsyncWrite for FlakyFlush {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if !self.failed {
+            self.failed = true;
+            return Poll::Ready(Err(ErrorKind::BrokenPipe.into()));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn test_tee_write_secondary_error() -> Result<()> {
+    executor::block_on(async {
+        let secondary = MockWriter::with_script(vec![
+            WriteBehavior::Partial(1),
+            WriteBehavior::Error(ErrorKind::BrokenPipe),
+        ]);
+        let mut stream = TeeWriteIO::new(MockWriter::new(), secondary);
+
+        // The inner writer took the data, so the write succeeds...
+        assert_eq!(stream.write(&[1, 2, 3]).await?, 3);
+        assert_eq!(stream.get_ref().written(), &[1, 2, 3]);
+
+        // ...and the secondary writer's error comes with the next one,
+        // before the inner writer is touched again.
+        let err = stream.write(&[4]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(stream.get_ref().written(), &[1, 2, 3]);
+
+        stream.write_all(&[4]).await?;
+        assert_eq!(stream.get_ref().written(), &[1, 2, 3, 4]);
+        assert_eq!(stream.secondary().written(), &[1, 4]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tee_write_flush_after_error() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = TeeWriteIO::new(MockWriter::new(), FlakyFlush::default());
+
+        let err = stream.flush().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(stream.get_ref().flushes(), 1);
+
+        // The failed flush doesn't count as done for the inner writer.
+        stream.flush().await?;
+        assert_eq!(stream.get_ref().flushes(), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tee_write_slow_secondary() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(Vec::<u8>::new());
+        let writer: Vec<u8> = vec![];
+        let mut stream = TeeWriteIO::new(MergeIO::new(reader, writer), Trickle::default());
+
+        stream.write_all(&[1, 2, 3, 4, 5]).await?;
+        stream.flush().await?;
+
+        assert_eq!(stream.get_ref().writer(), &[1, 2, 3, 4, 5]);
+        assert_eq!(stream.secondary().data, vec![1, 2, 3, 4, 5]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_broadcast_fast_and_slow() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2]);
+        let mut stream = BroadcastWriteIO::new(reader, MockWriter::new(), Trickle::default());
+
+        stream.write_all(&[1, 2, 3, 4, 5]).await?;
+        stream.flush().await?;
+        assert_eq!(stream.writer1().written(), &[1, 2, 3, 4, 5]);
+        assert_eq!(stream.writer2().data, vec![1, 2, 3, 4, 5]);
+
+        let mut read_buf = Vec::new();
+        stream.read_to_end(&mut read_buf).await?;
+        assert_eq!(read_buf, vec![1, 2]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_broadcast_slow_and_fast() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(Vec::<u8>::new());
+        let mut stream = BroadcastWriteIO::new(reader, Trickle::default(), MockWriter::new());
+
+        stream.write_all(&[1, 2, 3]).await?;
+        stream.write_all(&[4, 5]).await?;
+        stream.close().await?;
+
+        let (_, writer1, writer2) = stream.into_inner();
+        assert_eq!(writer1.data, vec![1, 2, 3, 4, 5]);
+        assert_eq!(writer2.written(), &[1, 2, 3, 4, 5]);
+        assert!(writer2.is_closed());
+
+        Ok(())
+    })
+}
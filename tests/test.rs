@@ -1,10 +1,17 @@
 #![warn(missing_debug_implementations, rust_2018_idioms)]
 
 use futures::executor;
-use futures::{AsyncReadExt, AsyncWriteExt, io::Cursor};
-use std::io::Result;
+use futures::io::{BufReader, BufWriter, Cursor};
+use futures::{
+    AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt,
+};
+use pin_project::pin_project;
+use std::io::{Result, SeekFrom};
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-use merge_io::MergeIO;
+use merge_io::{DynMergeIO, MergeIO};
 
 #[test]
 fn test_duplex() -> Result<()> {
@@ -34,3 +41,552 @@ fn test_duplex() -> Result<()> {
         Ok(())
     })
 }
+
+#[pin_project]
+struct NotUnpin<T> {
+    #[pin]
+    inner: T,
+    _pin: PhantomPinned,
+}
+
+impl<T> NotUnpin<T> {
+    fn new(inner: T) -> Self {
+        NotUnpin {
+            inner,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for NotUnpin<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for NotUnpin<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+#[test]
+fn test_not_unpin() -> Result<()> {
+    executor::block_on(async {
+        let reader = NotUnpin::new(Cursor::new(vec![1, 2, 3, 4]));
+        let writer = NotUnpin::new(Cursor::new(Vec::<u8>::new()));
+        let tio = MergeIO::new(reader, writer);
+        futures::pin_mut!(tio);
+
+        tio.write_all(&[10, 20]).await?;
+
+        let mut read_buf = Vec::<u8>::new();
+        tio.read_to_end(&mut read_buf).await?;
+        assert_eq!(&read_buf, &[1, 2, 3, 4]);
+
+        assert_eq!(tio.writer().inner.get_ref(), &[10, 20]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pin_split_not_unpin() -> Result<()> {
+    executor::block_on(async {
+        let reader = NotUnpin::new(Cursor::new(vec![1, 2, 3, 4]));
+        let writer = NotUnpin::new(Cursor::new(Vec::<u8>::new()));
+        let tio = MergeIO::new(reader, writer);
+        futures::pin_mut!(tio);
+
+        let (mut read_half, mut write_half) = tio.as_mut().pin_split();
+        let mut read_buf = Vec::<u8>::new();
+        let (read, written) = futures::future::join(
+            read_half.read_to_end(&mut read_buf),
+            write_half.write_all(&[10, 20]),
+        )
+        .await;
+        read?;
+        written?;
+        assert_eq!(&read_buf, &[1, 2, 3, 4]);
+
+        assert_eq!(tio.writer().inner.get_ref(), &[10, 20]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_buf_read() -> Result<()> {
+    executor::block_on(async {
+        let reader = BufReader::new(Cursor::new(b"first\nsecond\n".to_vec()));
+        let writer: Vec<u8> = vec![];
+        let mut stream = MergeIO::new(reader, writer);
+
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        assert_eq!(line, "first\n");
+
+        stream.write_all(b"reply").await?;
+
+        line.clear();
+        stream.read_line(&mut line).await?;
+        assert_eq!(line, "second\n");
+
+        line.clear();
+        assert_eq!(stream.read_line(&mut line).await?, 0);
+
+        assert_eq!(stream.writer(), b"reply");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_clone() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4]);
+        let writer: Vec<u8> = vec![10];
+        let original = MergeIO::new(reader, writer);
+
+        let mut clone = original.clone();
+        clone.write_all(&[20, 30]).await?;
+        let mut read_buf = [0u8; 2];
+        clone.read_exact(&mut read_buf).await?;
+
+        assert_eq!(clone.writer(), &[10, 20, 30]);
+        assert_eq!(clone.reader().position(), 2);
+
+        assert_eq!(original.writer(), &[10]);
+        assert_eq!(original.reader().position(), 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_tuple_conversions() {
+    let stream: MergeIO<_, _> = (Cursor::new(vec![1u8]), vec![2u8]).into();
+    assert_eq!(stream.writer(), &[2]);
+
+    let (reader, writer): (Cursor<Vec<u8>>, Vec<u8>) = stream.into();
+    assert_eq!(reader.into_inner(), vec![1]);
+    assert_eq!(writer, vec![2]);
+}
+
+/// Stands in for a TLS layer: flips every byte passing through it.
+struct FakeTls<T>(T);
+
+impl<T: AsyncRead + Unpin> AsyncRead for FakeTls<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let poll = Pin::new(&mut self.0).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            buf[..n].iter_mut().for_each(|b| *b = !*b);
+        }
+        poll
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for FakeTls<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let flipped: Vec<u8> = buf.iter().map(|b| !*b).collect();
+        Pin::new(&mut self.0).poll_write(cx, &flipped)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+async fn echo_once(stream: &mut DynMergeIO<'_>) -> Result<Vec<u8>> {
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+    stream.write_all(&read_buf).await?;
+    Ok(read_buf)
+}
+
+#[test]
+fn test_boxed() -> Result<()> {
+    executor::block_on(async {
+        for use_tls in [false, true] {
+            let reader = Cursor::new(vec![1, 2, 3, 4]);
+            let writer = Cursor::new(Vec::<u8>::new());
+
+            let mut stream = if use_tls {
+                MergeIO::boxed(FakeTls(reader), FakeTls(writer))
+            } else {
+                MergeIO::boxed(reader, writer)
+            };
+
+            let read_buf = echo_once(&mut stream).await?;
+            if use_tls {
+                assert_eq!(read_buf, vec![!1, !2, !3, !4]);
+            } else {
+                assert_eq!(read_buf, vec![1, 2, 3, 4]);
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_seek() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6]);
+        let writer = Cursor::new(vec![0u8; 6]);
+        let mut stream = MergeIO::new(reader, writer);
+
+        let mut read_buf = [0u8; 2];
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(read_buf, [1, 2]);
+
+        assert_eq!(stream.seek(SeekFrom::Start(4)).await?, 4);
+        assert_eq!(stream.writer().position(), 4);
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(read_buf, [5, 6]);
+
+        assert_eq!(stream.seek_reader(SeekFrom::Start(1)).await?, 1);
+        assert_eq!(stream.writer().position(), 4);
+        stream.read_exact(&mut read_buf).await?;
+        assert_eq!(read_buf, [2, 3]);
+
+        assert_eq!(stream.seek_writer(SeekFrom::End(-1)).await?, 5);
+        assert_eq!(stream.reader().position(), 3);
+        stream.write_all(&[10]).await?;
+        assert_eq!(stream.writer().get_ref(), &[0, 0, 0, 0, 0, 10]);
+
+        Ok(())
+    })
+}
+
+/// Stands in for a writer that needs a while to seek: the first seek returns
+/// `Pending` once.
+struct SlowSeek<T> {
+    inner: T,
+    pending: bool,
+}
+
+impl<T: AsyncSeek + Unpin> AsyncSeek for SlowSeek<T> {
+    fn poll_seek(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<Result<u64>> {
+        if self.pending {
+            self.pending = false;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        Pin::new(&mut self.inner).poll_seek(cx, pos)
+    }
+}
+
+#[test]
+fn test_seek_pending_writer() -> Result<()> {
+    executor::block_on(async {
+        let reader = Cursor::new(vec![1, 2, 3, 4, 5, 6]);
+        let writer = SlowSeek {
+            inner: Cursor::new(vec![0u8; 6]),
+            pending: true,
+        };
+        let mut stream = MergeIO::new(reader, writer);
+
+        // `reader` is seeked again when `writer` is polled again, which
+        // absolute seeks don't mind.
+        assert_eq!(stream.seek(SeekFrom::Start(2)).await?, 2);
+        assert_eq!(stream.reader().position(), 2);
+        assert_eq!(stream.writer().inner.position(), 2);
+
+        stream.writer_mut().pending = true;
+        assert_eq!(stream.seek(SeekFrom::Current(1)).await?, 4);
+        assert_eq!(stream.writer().inner.position(), 3);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_map_halves() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"line\n".to_vec()), Vec::<u8>::new());
+        let mut stream = stream.map_reader(BufReader::new).map_writer(BufWriter::new);
+
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        assert_eq!(line, "line\n");
+
+        stream.write_all(&[1, 2]).await?;
+        assert!(stream.writer().get_ref().is_empty());
+        stream.flush().await?;
+        assert_eq!(stream.writer().get_ref(), &[1, 2]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_replace_halves() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1]), Vec::<u8>::new());
+        let mut stream = stream.with_reader(&[2u8, 3][..]);
+        stream.write_all(&[10]).await?;
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, [2, 3]);
+
+        let stream = stream.with_writer(Cursor::new(Vec::<u8>::new()));
+        assert!(stream.writer().get_ref().is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_read_exact_write_all() -> Result<()> {
+    executor::block_on(async {
+        let reader = BufReader::with_capacity(2, Cursor::new(vec![1, 2, 3, 4, 5]));
+        let writer = BufWriter::with_capacity(2, Vec::<u8>::new());
+        let mut stream = MergeIO::new(reader, writer);
+
+        let mut buf = [0u8; 4];
+        stream.read_exact_into(&mut buf).await?;
+        assert_eq!(buf, [1, 2, 3, 4]);
+        let err = stream.read_exact_into(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        stream.write_all_from(&[9, 8, 7, 6, 5]).await?;
+        stream.flush().await?;
+        assert_eq!(stream.writer().get_ref(), &[9, 8, 7, 6, 5]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_pipe() -> Result<()> {
+    executor::block_on(async {
+        let mut stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+
+        let mut dest = Vec::new();
+        assert_eq!(stream.pipe_into(&mut dest).await?, 3);
+        assert_eq!(dest, [1, 2, 3]);
+
+        let mut src = Cursor::new(vec![4, 5]);
+        assert_eq!(stream.pipe_from(&mut src).await?, 2);
+        assert_eq!(stream.writer(), &[4, 5]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_buf_read_write() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"one\ntwo\n".to_vec()), Vec::<u8>::new());
+        let mut stream = stream.buf_read(16).buf_write(16);
+
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        assert_eq!(line, "one\n");
+
+        stream.write_all(b"reply").await?;
+        assert!(stream.writer().get_ref().is_empty());
+        stream.flush().await?;
+        assert_eq!(stream.writer().get_ref(), b"reply");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_swap() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Vec::<u8>::new(), Cursor::new(vec![1, 2, 3]));
+        let mut stream = stream.swap();
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, [1, 2, 3]);
+        stream.write_all(&[4]).await?;
+        assert_eq!(stream.writer(), &[4]);
+
+        Ok(())
+    })
+}
+
+/// A writer with no buffer of its own.
+struct Unbuffered;
+
+impl merge_io::HasPendingBytes for Unbuffered {}
+
+#[test]
+fn test_try_into_inner() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        assert!(stream.try_into_inner().is_ok());
+
+        let mut stream =
+            MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new()).buf_write(16);
+        stream.write_all(b"unsent").await?;
+        let (mut stream, err) = stream.try_into_inner().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+
+        stream.flush().await?;
+        let (_, writer) = stream.try_into_inner().map_err(|(_, err)| err)?;
+        assert_eq!(writer.into_inner(), b"unsent");
+
+        // Writers without a buffer of their own only need an empty impl.
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Unbuffered);
+        assert!(stream.try_into_inner().is_ok());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_default() -> Result<()> {
+    #[derive(Debug, Default)]
+    struct Config {
+        stream: MergeIO<Cursor<Vec<u8>>, Vec<u8>>,
+    }
+
+    executor::block_on(async {
+        let mut config = Config::default();
+        let mut buf = Vec::new();
+        assert_eq!(config.stream.read_to_end(&mut buf).await?, 0);
+        assert!(config.stream.writer().is_empty());
+
+        config.stream = MergeIO::<Cursor<Vec<u8>>, Vec<u8>>::default();
+        *config.stream.reader_mut() = Cursor::new(vec![1]);
+        assert_eq!(config.stream.read_to_end(&mut buf).await?, 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_eq_and_hash() {
+    use std::collections::HashSet;
+
+    // `futures::io::Cursor` doesn't implement `PartialEq`, `std::io::Cursor` does.
+    let a = MergeIO::new(std::io::Cursor::new(vec![1, 2]), Vec::<u8>::new());
+    let mut b = a.clone();
+    assert_eq!(a, b);
+    b.writer_mut().push(3);
+    assert_ne!(a, b);
+
+    let mut set = HashSet::new();
+    assert!(set.insert(MergeIO::new(vec![1u8], vec![2u8])));
+    assert!(!set.insert(MergeIO::new(vec![1u8], vec![2u8])));
+    assert!(set.insert(MergeIO::new(vec![2u8], vec![1u8])));
+}
+
+#[test]
+fn test_into_boxed_dyn() -> Result<()> {
+    executor::block_on(async {
+        let mut streams: Vec<Pin<Box<dyn merge_io::AsyncReadWrite + Send + Unpin>>> = vec![
+            MergeIO::new(Cursor::new(vec![1]), Vec::<u8>::new()).into_boxed_dyn(),
+            MergeIO::new(&[2u8][..], futures::io::sink()).into_boxed_dyn(),
+            MergeIO::new(
+                BufReader::new(Cursor::new(vec![3])),
+                Cursor::new(Vec::new()),
+            )
+            .into_boxed_dyn(),
+        ];
+
+        let mut read = Vec::new();
+        for stream in &mut streams {
+            stream.read_to_end(&mut read).await?;
+            stream.write_all(b"ok").await?;
+        }
+        assert_eq!(read, [1, 2, 3]);
+
+        let mut pinned = MergeIO::new(Cursor::new(vec![4]), Vec::<u8>::new()).into_pinned();
+        let mut byte = [0u8; 1];
+        pinned.read_exact(&mut byte).await?;
+        assert_eq!(byte, [4]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sink() {
+    use futures::channel::mpsc;
+    use futures::{future, FutureExt, SinkExt, StreamExt};
+
+    executor::block_on(async {
+        let (tx, mut rx) = mpsc::channel::<u32>(0);
+        let mut stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), tx);
+
+        stream.feed(1).await.unwrap();
+        // The channel is full, so the sink reports it isn't ready.
+        assert!(stream.feed(2).now_or_never().is_none());
+        assert_eq!(rx.next().await, Some(1));
+
+        let mut sink = (&mut stream).with(|n: u32| future::ready(Ok::<_, mpsc::SendError>(n * 10)));
+        let (sent, received) = futures::join!(sink.send(3), rx.next());
+        sent.unwrap();
+        assert_eq!(received, Some(30));
+
+        // The reader is still there.
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+
+        stream.close().await.unwrap();
+        assert_eq!(rx.next().await, None);
+    })
+}
+
+#[test]
+fn test_stream() {
+    use futures::channel::mpsc;
+    use futures::{SinkExt, StreamExt};
+
+    executor::block_on(async {
+        let (mut tx, rx) = mpsc::channel::<u32>(4);
+        let stream = MergeIO::new(rx, Vec::<u8>::new());
+
+        for n in 1..=4 {
+            tx.send(n).await.unwrap();
+        }
+        drop(tx);
+
+        let mut stream = stream.filter(|n| futures::future::ready(n % 2 == 0));
+        assert_eq!(stream.next().await, Some(2));
+
+        // The writer is still there.
+        let mut stream = stream.into_inner();
+        stream.write_all(b"hi").await.unwrap();
+        assert_eq!(stream.writer(), b"hi");
+
+        let rest: Vec<_> = stream.map(|n| n * 10).collect().await;
+        assert_eq!(rest, [30, 40]);
+    })
+}
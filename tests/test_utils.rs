@@ -0,0 +1,240 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::Cursor;
+use futures::task::{waker, ArcWake};
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::future::Future;
+use std::io::{ErrorKind, Result};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Context;
+
+use merge_io::test_utils::{
+    MockReader, MockWriter, PanicOnCloseIO, PanicOnFlushIO, PanicOnReadIO, PanicOnWriteIO,
+    PendingIO, PollResult, WouldBlockIO, WouldBlockReadIO, WouldBlockWriteIO, WriteBehavior,
+};
+use merge_io::MergeIO;
+
+#[test]
+fn test_mock_reader() -> Result<()> {
+    executor::block_on(async {
+        let mut reader = MockReader::new(vec![
+            PollResult::Data(vec![1, 2, 3]),
+            PollResult::Pending,
+            PollResult::Error(ErrorKind::Interrupted),
+            PollResult::Data(vec![4]),
+            PollResult::Eof,
+        ]);
+
+        let mut read_buf = [0u8; 2];
+        assert_eq!(reader.read(&mut read_buf).await?, 2);
+        assert_eq!(reader.read(&mut read_buf).await?, 1);
+        assert_eq!(read_buf[0], 3);
+
+        let err = reader.read(&mut read_buf).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+        assert_eq!(reader.calls(), 4);
+
+        assert_eq!(reader.read(&mut read_buf).await?, 1);
+        assert_eq!(reader.read(&mut read_buf).await?, 0);
+        reader.assert_exhausted();
+
+        Ok(())
+    })
+}
+
+#[test]
+#[should_panic(expected = "unread entries")]
+fn test_mock_reader_not_exhausted() {
+    MockReader::new(vec![PollResult::Eof]).assert_exhausted();
+}
+
+#[test]
+fn test_mock_writer() -> Result<()> {
+    executor::block_on(async {
+        let writer = MockWriter::with_script(vec![
+            WriteBehavior::Partial(2),
+            WriteBehavior::Pending,
+            WriteBehavior::Accept,
+            WriteBehavior::Error(ErrorKind::BrokenPipe),
+        ]);
+        let mut stream = MergeIO::new(MockReader::new(vec![]), writer);
+
+        stream.write_all(&[1, 2, 3, 4]).await?;
+        let err = stream.write(&[5]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        stream.flush().await?;
+        stream.close().await?;
+
+        let writer = stream.writer();
+        assert_eq!(writer.written(), &[1, 2, 3, 4]);
+        assert_eq!(writer.calls(), 4);
+        assert_eq!(writer.flushes(), 1);
+        assert!(writer.is_closed());
+        writer.assert_exhausted();
+
+        Ok(())
+    })
+}
+
+#[derive(Debug, Default)]
+struct WakeCounter(AtomicUsize);
+
+impl ArcWake for WakeCounter {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_would_block_never_wakes() {
+    let counter = Arc::new(WakeCounter::default());
+    let waker = waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut stream = MergeIO::new(WouldBlockIO::new(), WouldBlockIO::new());
+    let mut buf = [0u8; 4];
+    {
+        let mut read = stream.read(&mut buf);
+        assert!(Pin::new(&mut read).poll(&mut cx).is_pending());
+    }
+    {
+        let mut write = stream.write(&buf);
+        assert!(Pin::new(&mut write).poll(&mut cx).is_pending());
+    }
+
+    // Each operation polled the stream once and nothing asked to be polled
+    // again.
+    assert_eq!(stream.reader().polls(), 1);
+    assert_eq!(stream.writer().polls(), 1);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_pending_wakes_on_demand() {
+    let counter = Arc::new(WakeCounter::default());
+    let waker = waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut stream = MergeIO::new(PendingIO::new(), PendingIO::new());
+    assert!(!stream.reader().wake());
+
+    let mut buf = [0u8; 4];
+    let mut read = stream.read(&mut buf);
+    assert!(Pin::new(&mut read).poll(&mut cx).is_pending());
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    drop(read);
+
+    assert!(stream.reader().wake());
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert!(!stream.reader().wake());
+
+    let mut read = stream.read(&mut buf);
+    assert!(Pin::new(&mut read).poll(&mut cx).is_pending());
+    drop(read);
+    assert_eq!(stream.reader().polls(), 2);
+    assert_eq!(stream.writer().polls(), 0);
+}
+
+#[test]
+fn test_panic_on_read() {
+    let reader = PanicOnReadIO::new(MockReader::new(vec![]), "unexpected read");
+    let calls = reader.call_counter();
+    let mut stream = MergeIO::new(reader, MockWriter::new());
+
+    executor::block_on(stream.write_all(b"fine")).unwrap();
+    assert_eq!(stream.reader().calls(), 0);
+
+    let panic = catch_unwind(AssertUnwindSafe(|| {
+        let mut buf = [0u8; 4];
+        let _ = executor::block_on(stream.read(&mut buf));
+    }))
+    .unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>().unwrap(), "unexpected read");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_panic_on_write() {
+    let writer = PanicOnWriteIO::new(MockWriter::new(), "unexpected write");
+    let calls = writer.call_counter();
+    let mut stream = MergeIO::new(MockReader::new(vec![PollResult::Data(vec![1])]), writer);
+
+    let mut buf = Vec::new();
+    executor::block_on(stream.read_to_end(&mut buf)).unwrap();
+    executor::block_on(stream.close()).unwrap();
+
+    assert!(catch_unwind(AssertUnwindSafe(|| executor::block_on(stream.flush()))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| executor::block_on(stream.write(b"x")))).is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_panic_on_flush_and_close() {
+    let mut flush = PanicOnFlushIO::new(MockWriter::new(), "unexpected flush");
+    assert_eq!(executor::block_on(flush.write(b"x")).unwrap(), 1);
+    executor::block_on(flush.close()).unwrap();
+    assert!(catch_unwind(AssertUnwindSafe(|| executor::block_on(flush.flush()))).is_err());
+    assert_eq!(flush.calls(), 1);
+
+    let mut close = PanicOnCloseIO::new(MockWriter::new(), "unexpected close");
+    executor::block_on(close.flush()).unwrap();
+    assert!(catch_unwind(AssertUnwindSafe(|| executor::block_on(close.close()))).is_err());
+    assert_eq!(close.calls(), 1);
+}
+
+#[test]
+fn test_would_block_write() -> Result<()> {
+    use futures::task::{noop_waker_ref, Context, Poll};
+    use futures::AsyncWrite;
+    use std::pin::Pin;
+
+    let mut stream = WouldBlockWriteIO::new(MockWriter::new(), 2);
+    let mut cx = Context::from_waker(noop_waker_ref());
+    for left in [1, 0] {
+        assert!(Pin::new(&mut stream)
+            .poll_write(&mut cx, b"ab")
+            .is_pending());
+        assert_eq!(stream.blocks_left(), left);
+    }
+    assert!(matches!(
+        Pin::new(&mut stream).poll_write(&mut cx, b"ab"),
+        Poll::Ready(Ok(2))
+    ));
+    // The blocked writes never reached the inner writer.
+    assert_eq!(stream.get_ref().calls(), 1);
+
+    // The task is woken, so an executor gets through the blocked writes.
+    let mut stream =
+        WouldBlockWriteIO::new(MergeIO::new(Cursor::new(vec![1]), MockWriter::new()), 5);
+    executor::block_on(async {
+        let mut buf = [0];
+        stream.read_exact(&mut buf).await?;
+        stream.write_all(b"hello").await?;
+        stream.flush().await
+    })?;
+    assert_eq!(stream.get_ref().writer().written(), b"hello");
+    Ok(())
+}
+
+#[test]
+fn test_would_block_read() -> Result<()> {
+    executor::block_on(async {
+        let reader = MockReader::new(vec![PollResult::Data(b"abc".to_vec())]);
+        let mut stream = WouldBlockReadIO::new(MergeIO::new(reader, Vec::<u8>::new()), 3);
+
+        stream.write_all(b"unaffected").await?;
+        assert_eq!(stream.blocks_left(), 3);
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"abc");
+        assert_eq!(stream.blocks_left(), 0);
+        assert_eq!(stream.get_ref().writer(), b"unaffected");
+
+        Ok(())
+    })
+}
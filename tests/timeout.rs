@@ -0,0 +1,71 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io::{ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use merge_io::MergeIO;
+
+#[derive(Debug)]
+struct Never;
+
+impl AsyncRead for Never {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Poll::Pending
+    }
+}
+
+impl AsyncWrite for Never {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Pending
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Pending
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Pending
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_timeout() -> Result<()> {
+    let mut stream = MergeIO::new(Never, Vec::<u8>::new()).read_timeout(Duration::from_secs(5));
+
+    let started = tokio::time::Instant::now();
+    let err = stream.read(&mut [0u8; 4]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(started.elapsed(), Duration::from_secs(5));
+
+    stream.write_all(&[1, 2]).await?;
+    assert_eq!(stream.get_ref().writer(), &[1, 2]);
+
+    let err = stream.read(&mut [0u8; 4]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_write_timeout() -> Result<()> {
+    let reader = futures::io::Cursor::new(vec![1, 2]);
+    let mut stream = MergeIO::new(reader, Never).write_timeout(Duration::from_secs(1));
+
+    let err = stream.write(&[1]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    let err = stream.flush().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+    assert_eq!(read_buf, vec![1, 2]);
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use std::io::{Cursor, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use merge_io::MergeIO;
+
+#[tokio::test]
+async fn test_tokio_copy() -> Result<()> {
+    let reader = Cursor::new(vec![1, 2, 3, 4]);
+    let writer: Vec<u8> = vec![];
+    let mut stream = MergeIO::new(reader, writer);
+
+    let copied = tokio::io::copy(&mut stream, &mut tokio::io::sink()).await?;
+    assert_eq!(copied, 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tokio_duplex() -> Result<()> {
+    let reader = Cursor::new(vec![1, 2, 3, 4]);
+    let writer: Vec<u8> = vec![];
+    let mut stream = MergeIO::new(reader, writer);
+
+    stream.write_all(&[10, 20, 30, 40]).await?;
+    stream.shutdown().await?;
+
+    let mut read_buf = Vec::new();
+    stream.read_to_end(&mut read_buf).await?;
+
+    assert_eq!(&read_buf, &[1, 2, 3, 4]);
+    assert_eq!(stream.writer(), &[10, 20, 30, 40]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_from_tcp_stream() -> Result<()> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let (client, server) =
+        tokio::try_join!(tokio::net::TcpStream::connect(addr), listener.accept())?;
+    let mut client = MergeIO::from_tcp_stream(client);
+    let mut server = MergeIO::from_tcp_stream(server.0);
+
+    client.write_all(b"ping").await?;
+    let mut buf = [0u8; 4];
+    server.read_exact(&mut buf).await?;
+    assert_eq!(&buf, b"ping");
+
+    server.write_all(b"pong").await?;
+    server.shutdown().await?;
+    let mut buf = Vec::new();
+    client.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"pong");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_from_unix_stream() -> Result<()> {
+    let (client, server) = tokio::net::UnixStream::pair()?;
+    let mut client = MergeIO::from_unix_stream(client);
+    let mut server = MergeIO::from_unix_stream(server);
+
+    client.write_all(b"ping").await?;
+    client.shutdown().await?;
+    let mut buf = Vec::new();
+    server.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"ping");
+
+    Ok(())
+}
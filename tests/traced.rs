@@ -0,0 +1,51 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use futures::FutureExt;
+use std::io::{ErrorKind, Result};
+use tracing_test::traced_test;
+
+use merge_io::test_utils::{MockWriter, WriteBehavior};
+use merge_io::MergeIO;
+
+#[test]
+#[traced_test]
+fn test_traced_read_write() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![1, 2, 3]), Vec::<u8>::new());
+        let mut stream = stream.traced("client");
+        assert_eq!(stream.name(), "client");
+
+        let mut buf = [0; 8];
+        assert_eq!(stream.read(&mut buf).await?, 3);
+        stream.write_all(b"hello").await?;
+
+        assert!(logs_contain("merge_io{stream_name=\"client\"}"));
+        assert!(logs_contain("direction=\"read\" bytes=3"));
+        assert!(logs_contain("direction=\"write\" bytes=5"));
+        Ok(())
+    })
+}
+
+#[test]
+#[traced_test]
+fn test_traced_pending_and_error() {
+    executor::block_on(async {
+        let writer = MockWriter::with_script(vec![
+            WriteBehavior::Pending,
+            WriteBehavior::Error(ErrorKind::ConnectionReset),
+        ]);
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), writer);
+        let mut stream = stream.traced("server");
+
+        assert!(stream.write(b"a").now_or_never().is_none());
+        assert!(logs_contain("DEBUG"));
+        assert!(logs_contain("pending"));
+
+        let err = stream.write(b"a").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        assert!(logs_contain("WARN"));
+        assert!(logs_contain("stream_name=\"server\""));
+    })
+}
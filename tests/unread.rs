@@ -0,0 +1,67 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::unread::UnreadIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_pushback_larger_than_read_buffer() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(vec![9]), Vec::<u8>::new());
+        let mut stream = UnreadIO::new(stream, 8);
+
+        stream.unread(&[4, 5, 6])?;
+        stream.unread(&[1, 2, 3])?;
+        assert_eq!(stream.pushback_len(), 6);
+
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut buf).await?, 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(stream.read(&mut buf).await?, 2);
+        assert_eq!(&buf[..2], [5, 6]);
+        assert_eq!(stream.read(&mut buf).await?, 1);
+        assert_eq!(buf[0], 9);
+        assert_eq!(stream.read(&mut buf).await?, 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_capacity() {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+    let mut stream = UnreadIO::new(stream, 4);
+
+    stream.unread(&[1, 2, 3]).unwrap();
+    let err = stream.unread(&[4, 5]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+    assert_eq!(stream.pushback_len(), 3);
+    stream.unread(&[4]).unwrap();
+}
+
+#[test]
+fn test_interleaved_pushback_and_writes() -> Result<()> {
+    executor::block_on(async {
+        let stream = MergeIO::new(Cursor::new(b"GET /".to_vec()), Vec::<u8>::new());
+        let mut stream = UnreadIO::new(stream, 16);
+
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await?;
+        stream.unread(&buf[3..])?;
+        stream.write_all(b"method ").await?;
+        stream.write_all(&buf[..3]).await?;
+
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await?;
+        assert_eq!(rest, b" /");
+        stream.write_all(b", path").await?;
+        stream.write_all(&rest).await?;
+
+        assert_eq!(stream.get_ref().writer(), b"method GET, path /");
+
+        Ok(())
+    })
+}
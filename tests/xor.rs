@@ -0,0 +1,48 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+use futures::executor;
+use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+use std::io::{ErrorKind, Result};
+
+use merge_io::xor::XorObfuscationIO;
+use merge_io::MergeIO;
+
+#[test]
+fn test_round_trip() -> Result<()> {
+    executor::block_on(async {
+        let key = b"secret".to_vec();
+        let data: Vec<u8> = (0..=255).collect();
+
+        let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+        let mut encoder = XorObfuscationIO::new(stream, key.clone())?;
+        for chunk in data.chunks(7) {
+            encoder.write_all(chunk).await?;
+        }
+        let encoded = encoder.into_inner().into_inner().1;
+        assert_eq!(encoded.len(), data.len());
+        assert!(encoded != data);
+        assert_eq!(encoded[6], data[6] ^ b's');
+
+        let stream = MergeIO::new(Cursor::new(encoded), Vec::<u8>::new());
+        let mut decoder = XorObfuscationIO::new(stream, key)?;
+        let mut decoded = Vec::new();
+        let mut buf = [0u8; 5];
+        loop {
+            let n = decoder.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(decoded, data);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_empty_key() {
+    let stream = MergeIO::new(Cursor::new(Vec::<u8>::new()), Vec::<u8>::new());
+    let err = XorObfuscationIO::new(stream, Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}